@@ -43,6 +43,9 @@ pub enum ContractError {
     ZeroAmount {
         denom: String,
     },
+
+    #[error("cannot transfer an empty list of coins")]
+    NoCoins,
 }
 
 impl ContractError {
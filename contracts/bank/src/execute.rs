@@ -221,6 +221,11 @@ fn transfer(
     to_addr: &Addr,
     coins: &[Coin],
 ) -> Result<Response, ContractError> {
+    // the coins are emitted as an event attribute, which must not be empty
+    if coins.is_empty() {
+        return Err(ContractError::NoCoins);
+    }
+
     let mut msgs = vec![];
 
     for coin in coins {
@@ -39,18 +39,8 @@ pub struct UpdateNamespaceMsg {
     pub after_transfer_hook: Option<String>,
 }
 
-// TODO: this should be in `cw-sdk` crate
-#[cw_serde]
-pub enum SudoMsg {
-    /// Forcibly transfer coins between two accounts.
-    /// Callable by the state machine when handling gas fee payments and funds
-    /// attached to messages.
-    Transfer {
-        from: String,
-        to: String,
-        coins: Vec<Coin>,
-    },
-}
+/// The bank contract's sudo message is defined by the state machine
+pub use cw_sdk::BankSudoMsg as SudoMsg;
 
 #[cw_serde]
 pub enum ExecuteMsg {
@@ -1,6 +1,7 @@
 use cosmwasm_std::{coin, testing::mock_info, to_binary, SubMsg, Uint128, WasmMsg};
 
 use crate::{
+    error::ContractError,
     execute,
     msg::HookMsg,
     query,
@@ -98,3 +99,12 @@ fn send() {
     assert_supply(deps.as_ref(), "ibc/12AB34CD", 45678);
     assert_supply(deps.as_ref(), "mars/uxmars", 69420);
 }
+
+#[test]
+fn sending_no_coins() {
+    let mut deps = setup_test();
+
+    let err = execute::send(deps.as_mut(), mock_info("jake", &[]), "pumpkin".into(), vec![])
+        .unwrap_err();
+    assert_eq!(err, ContractError::NoCoins);
+}
@@ -0,0 +1,261 @@
+//! Batch files list the messages of a multi-message tx, in JSON or YAML format. Each entry is an
+//! `SdkMsg`, with two conveniences:
+//!
+//! - the `wasm_byte_code` of a `store_code` message can be the path to a `.wasm` file, relative to
+//!   the batch file, instead of the base64-encoded byte code;
+//! - the results of earlier messages can be referred to by placeholders: `${N.code_id}` for the
+//!   code id assigned by the N-th message (counting from zero) if it is a `store_code` message,
+//!   and `${N.contract_address}` for the address of the contract instantiated by it if it is an
+//!   `instantiate` message.
+//!
+//! For example:
+//!
+//! ```yaml
+//! - store_code:
+//!     wasm_byte_code: ./artifacts/cw_token_factory.wasm
+//! - instantiate:
+//!     code_id: ${0.code_id}
+//!     msg: {}
+//!     funds: []
+//!     label: token-factory
+//!     admin: null
+//! - execute:
+//!     contract: ${1.contract_address}
+//!     msg: { create_token: { nonce: "1" } }
+//!     funds: []
+//! ```
+//!
+//! A string that consists of a single placeholder is replaced by the value itself, so `code_id`
+//! above becomes an integer; placeholders within longer strings are interpolated. To write a
+//! literal `${` in a string, escape it as `$${`.
+
+use std::{collections::HashMap, fs, path::Path};
+
+use cosmwasm_std::Binary;
+use cw_sdk::{address, InfoResponse, SdkMsg, SdkQuery};
+use serde_json::Value;
+use tendermint_rpc::HttpClient;
+
+use crate::{client::do_abci_query, DaemonError};
+
+/// Load the messages in a batch file, substituting the placeholders.
+///
+/// Code ids are assigned sequentially, so the ones assigned by the batch's `store_code` messages
+/// are predicted from the number of codes currently on chain. The prediction is wrong if another
+/// tx storing code is included before this one, in which case later messages of the batch likely
+/// fail, reverting the entire tx.
+pub async fn load(client: &HttpClient, path: &Path) -> Result<Vec<SdkMsg>, DaemonError> {
+    if !path.exists() {
+        return Err(DaemonError::file_not_found(path)?);
+    }
+    let content = fs::read(path)?;
+
+    let entries: Vec<Value> = match path.extension().and_then(|ext| ext.to_str()) {
+        Some("yaml" | "yml") => serde_yaml::from_slice(&content)?,
+        _ => serde_json::from_slice(&content)?,
+    };
+
+    let info: InfoResponse = do_abci_query(client, SdkQuery::Info {}).await?;
+    let mut next_code_id = info.code_count + 1;
+
+    // directory against which wasm file paths are resolved
+    let base_dir = path.parent().unwrap_or_else(|| Path::new("."));
+
+    let mut results: HashMap<String, Value> = HashMap::new();
+    let mut msgs = vec![];
+    for (idx, mut entry) in entries.into_iter().enumerate() {
+        substitute(&mut entry, &results)?;
+        load_wasm_byte_code(&mut entry, base_dir)?;
+
+        let msg: SdkMsg = serde_json::from_value(entry)
+            .map_err(|err| DaemonError::invalid_batch(format!("message {idx}: {err}")))?;
+
+        match &msg {
+            SdkMsg::StoreCode {
+                ..
+            } => {
+                results.insert(format!("{idx}.code_id"), next_code_id.into());
+                next_code_id += 1;
+            },
+            SdkMsg::Instantiate {
+                label,
+                ..
+            } => {
+                let contract_addr = address::derive_from_label(label)?;
+                results.insert(format!("{idx}.contract_address"), contract_addr.to_string().into());
+            },
+            _ => (),
+        }
+
+        msgs.push(msg);
+    }
+
+    if msgs.is_empty() {
+        return Err(DaemonError::invalid_batch("the batch contains no message"));
+    }
+
+    Ok(msgs)
+}
+
+/// Replace the placeholders in all strings of the value, recursively.
+fn substitute(value: &mut Value, results: &HashMap<String, Value>) -> Result<(), DaemonError> {
+    match value {
+        Value::String(s) => {
+            // a lone placeholder is replaced by the value itself, so that e.g.
+            // code ids become integers
+            if let Some(name) = s.strip_prefix("${").and_then(|s| s.strip_suffix('}')) {
+                if !name.contains('}') {
+                    *value = lookup(name, results)?.clone();
+                    return Ok(());
+                }
+            }
+
+            let mut interpolated = String::new();
+            let mut rest = s.as_str();
+            while let Some(start) = rest.find('$') {
+                interpolated.push_str(&rest[..start]);
+                let tail = &rest[start..];
+
+                if let Some(tail) = tail.strip_prefix("$${") {
+                    interpolated.push_str("${");
+                    rest = tail;
+                } else if let Some(tail) = tail.strip_prefix("${") {
+                    let Some(len) = tail.find('}') else {
+                        return Err(DaemonError::invalid_batch(format!(
+                            "unterminated placeholder in `{s}`; write `$${{` for a literal `${{`"
+                        )));
+                    };
+                    match lookup(&tail[..len], results)? {
+                        Value::String(result) => interpolated.push_str(result),
+                        result => interpolated.push_str(&result.to_string()),
+                    }
+                    rest = &tail[len + 1..];
+                } else {
+                    interpolated.push('$');
+                    rest = &tail[1..];
+                }
+            }
+            interpolated.push_str(rest);
+            *s = interpolated;
+        },
+        Value::Array(values) => {
+            for value in values {
+                substitute(value, results)?;
+            }
+        },
+        Value::Object(map) => {
+            for value in map.values_mut() {
+                substitute(value, results)?;
+            }
+        },
+        _ => (),
+    }
+
+    Ok(())
+}
+
+fn lookup<'a>(name: &str, results: &'a HashMap<String, Value>) -> Result<&'a Value, DaemonError> {
+    results.get(name).ok_or_else(|| {
+        DaemonError::invalid_batch(format!(
+            "unknown placeholder `${{{name}}}`; it must refer to the result of an earlier message"
+        ))
+    })
+}
+
+/// If the entry is a `store_code` message whose byte code is given as the path to a wasm file,
+/// replace the path with the base64-encoded content of the file.
+fn load_wasm_byte_code(entry: &mut Value, base_dir: &Path) -> Result<(), DaemonError> {
+    let Some(Value::String(wasm_byte_code)) = entry.pointer_mut("/store_code/wasm_byte_code") else {
+        return Ok(());
+    };
+
+    if !wasm_byte_code.ends_with(".wasm") {
+        return Ok(());
+    }
+
+    let path = base_dir.join(&*wasm_byte_code);
+    if !path.exists() {
+        return Err(DaemonError::file_not_found(&path)?);
+    }
+    *wasm_byte_code = Binary::from(fs::read(path)?).to_base64();
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn results() -> HashMap<String, Value> {
+        HashMap::from([
+            ("0.code_id".to_string(), json!(5)),
+            ("1.contract_address".to_string(), json!("cw1contract")),
+        ])
+    }
+
+    fn substituted(mut value: Value) -> Result<Value, DaemonError> {
+        substitute(&mut value, &results())?;
+        Ok(value)
+    }
+
+    #[test]
+    fn substituting_lone_placeholders() {
+        // a lone placeholder takes the type of the value, e.g. an integer
+        let value = substituted(json!({
+            "instantiate": {
+                "code_id": "${0.code_id}",
+                "admin": "${1.contract_address}",
+                "funds": [],
+            },
+        }))
+        .unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "instantiate": {
+                    "code_id": 5,
+                    "admin": "cw1contract",
+                    "funds": [],
+                },
+            }),
+        );
+    }
+
+    #[test]
+    fn interpolating_placeholders() {
+        let value = substituted(json!({
+            "msg": ["code ${0.code_id} at ${1.contract_address}", "${0.code_id}${0.code_id}"],
+        }))
+        .unwrap();
+        assert_eq!(value, json!({ "msg": ["code 5 at cw1contract", "55"] }));
+    }
+
+    #[test]
+    fn escaping_placeholders() {
+        let value = substituted(json!(["$${0.code_id}", "price: $5, code: ${0.code_id}", "$$"]))
+            .unwrap();
+        assert_eq!(value, json!(["${0.code_id}", "price: $5, code: 5", "$$"]));
+    }
+
+    #[test]
+    fn rejecting_unknown_placeholders() {
+        // a message can only refer to the results of earlier messages, which
+        // have been recorded by the time it is substituted
+        for value in [
+            json!("${2.code_id}"),
+            json!("contract ${1.code_id}"),
+            json!(["${0.address}"]),
+        ] {
+            let err = substituted(value).unwrap_err();
+            assert!(err.to_string().contains("unknown placeholder"), "{err}");
+        }
+    }
+
+    #[test]
+    fn rejecting_unterminated_placeholders() {
+        let err = substituted(json!({ "label": "token-${0.code_id" })).unwrap_err();
+        assert!(err.to_string().contains("unterminated placeholder"), "{err}");
+    }
+}
@@ -1,7 +1,7 @@
 use clap::{Args, Subcommand};
 use cw_sdk::address;
 
-use crate::DaemonError;
+use crate::{key::Algorithm, DaemonError};
 
 #[derive(Args)]
 pub struct DebugCmd {
@@ -16,6 +16,10 @@ pub enum DebugSubcmd {
         /// Public key in either hex encoding
         pubkey: String,
 
+        /// Signature scheme of the public key
+        #[arg(long, value_enum, default_value_t = Algorithm::Secp256k1)]
+        algo: Algorithm,
+
         // TODO: add a `--base64` flag to allow using base64-encoded pubkeys
     },
 
@@ -31,9 +35,10 @@ impl DebugCmd {
         match self.subcommand {
             DebugSubcmd::DeriveBaseAddress {
                 pubkey,
+                algo,
             } => {
-                let pubkey_bytes = hex::decode(pubkey)?;
-                let addr = address::derive_from_pubkey(&pubkey_bytes)?;
+                let pubkey = algo.pubkey(hex::decode(pubkey)?);
+                let addr = address::derive_from_pubkey(&pubkey)?;
                 println!("{addr}");
             },
 
@@ -0,0 +1,55 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use clap::Args;
+use tracing::info;
+
+use cw_state_machine::StateMachine;
+use cw_store::Store;
+
+use crate::{path, print, DaemonError};
+
+#[derive(Args)]
+pub struct ExportCmd {
+    /// Height of the state to export. The application database only holds the
+    /// last committed state, so this must be the last committed height if given
+    #[arg(long)]
+    height: Option<u64>,
+
+    /// Write the genesis state to this file instead of printing it
+    #[arg(long)]
+    output: Option<PathBuf>,
+}
+
+impl ExportCmd {
+    pub fn run(&self, home_dir: &Path) -> Result<(), DaemonError> {
+        // load merk store from disk. this fails if the node is running, as
+        // the database can only be opened by one process at a time
+        let store = Store::open(home_dir.join("./data"))?;
+        let state_machine = StateMachine::new(store);
+
+        let gen_state = state_machine.export_genesis()?;
+
+        // the snapshot is always included by `export_genesis`
+        let last_height = gen_state.snapshot.as_ref().map(|snapshot| snapshot.height).unwrap_or(0);
+        if let Some(height) = self.height {
+            if height != last_height {
+                return Err(DaemonError::height_unavailable(height, last_height));
+            }
+        }
+
+        match &self.output {
+            Some(output) => {
+                fs::write(output, serde_json::to_vec_pretty(&gen_state)?)?;
+                info!(
+                    "Exported state at height {last_height} to {}",
+                    path::stringify(output)?,
+                );
+                Ok(())
+            },
+            None => print::json(gen_state),
+        }
+    }
+}
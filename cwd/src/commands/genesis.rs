@@ -1,15 +1,30 @@
-use std::fs;
-use std::path::{Path, PathBuf};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
 
 use clap::{Args, Subcommand};
 use serde::Serialize;
+use serde_json::{json, Value};
 use tendermint::genesis::Genesis as TmGenesis;
+use cosmwasm_std::{Addr, Coin, Event};
 use tracing::info;
 
-use cw_sdk::{address, hash::sha256, GenesisState, SdkMsg};
+use cw_bank::{
+    denom::{Denom, Namespace},
+    error::ContractError,
+    msg::{Balance, InstantiateMsg as BankInstantiateMsg, UpdateNamespaceMsg},
+};
+use cw_sdk::{address, hash::sha256, helpers::parse_coins, label, GenesisState, SdkMsg};
+use cw_state_machine::StateMachine;
+use cw_store::Store;
 
 use crate::{path, print, DaemonError};
 
+/// Label of the bank contract, by which its instantiate message is found in the genesis state
+const BANK_LABEL: &str = "bank";
+
 #[derive(Args)]
 pub struct GenesisCmd {
     #[command(subcommand)]
@@ -24,11 +39,24 @@ pub struct GenesisCmd {
 /// NOTE: We do not support migrating contracts in the genesis state
 #[derive(Subcommand)]
 pub enum GenesisSubcommand {
+    /// Write a skeleton genesis state to the genesis file, e.g. one just created by
+    /// `tendermint init`
+    Init {
+        /// Replace the genesis state if the genesis file already has one
+        #[arg(long)]
+        overwrite: bool,
+    },
+
     /// Set the deployer address to be used for the genesis
     SetDeployer {
         address: String,
     },
 
+    /// Set the account allowed to schedule chain upgrades, by address or label
+    SetUpgradeAuthority {
+        address: String,
+    },
+
     /// Add a "store code" message to the genesis state
     Store {
         /// Path to the wasm byte code
@@ -67,11 +95,39 @@ pub enum GenesisSubcommand {
         funds: Option<String>,
     },
 
+    /// Add initial balances of an account to the bank contract's instantiate message
+    AddBalance {
+        /// Address of the account
+        address: String,
+
+        /// Coins to add to the account's balances, e.g. 100uatom,20ufoo
+        coins: String,
+    },
+
+    /// Add a namespace to the bank contract's instantiate message
+    AddNamespace {
+        /// The namespace, e.g. factory
+        namespace: String,
+
+        /// Address of the account that can mint, burn and force transfer coins under the
+        /// namespace
+        #[arg(long)]
+        admin: Option<String>,
+
+        /// Address of the contract to be called after each transfer of coins under the namespace
+        #[arg(long)]
+        hook: Option<String>,
+    },
+
     /// List all codes in the genesis state
     ListCodes,
 
     /// List all contracts in the genesis state
     ListContracts,
+
+    /// Run the genesis state against a temporary store, the same way the node does upon
+    /// InitChain, and report the result of each message
+    Validate,
 }
 
 impl GenesisCmd {
@@ -89,19 +145,45 @@ impl GenesisCmd {
 
         let genesis_bytes = fs::read(&genesis_path)?;
 
-        // TODO: If using `tendermint init` command, the `app_state` field is actually missing
-        // in the genesis file, which causes the deserialization to fail
-        let mut genesis: TmGenesis = serde_json::from_slice(&genesis_bytes)?;
+        // the genesis file created by `tendermint init` doesn't have the `app_state` field, which
+        // is required by `TmGenesis`, so an empty one is inserted
+        let mut genesis_value: Value = serde_json::from_slice(&genesis_bytes)?;
+        if let Value::Object(map) = &mut genesis_value {
+            map.entry("app_state").or_insert_with(|| json!({}));
+        }
+        let mut genesis: TmGenesis = serde_json::from_value(genesis_value)?;
 
-        let mut app_state: GenesisState =
-            serde_json::from_value(genesis.app_state.clone()).unwrap_or_default();
+        let app_state_exists = !is_empty(&genesis.app_state);
+        let mut app_state: GenesisState = if app_state_exists {
+            serde_json::from_value(genesis.app_state.clone())?
+        } else {
+            GenesisState::default()
+        };
 
         match self.subcommand {
+            GenesisSubcommand::Init {
+                overwrite,
+            } => {
+                if app_state_exists && !overwrite {
+                    return Err(DaemonError::app_state_exists(&genesis_path)?);
+                }
+                update_and_write(&mut genesis, &GenesisState::default(), &genesis_path)
+            },
+
             GenesisSubcommand::SetDeployer {
                 address,
             } => {
-                // TODO: validate deployer address
-                app_state.deployer = address;
+                app_state.deployer = address::validate(&address)?.into();
+                update_and_write(&mut genesis, &app_state, &genesis_path)
+            },
+
+            GenesisSubcommand::SetUpgradeAuthority {
+                address,
+            } => {
+                // a label is kept as is, and resolved when an upgrade is
+                // scheduled; an address must be valid
+                label::resolve_raw_address(&address)?;
+                app_state.upgrade_authority = Some(address);
                 update_and_write(&mut genesis, &app_state, &genesis_path)
             },
 
@@ -152,6 +234,34 @@ impl GenesisCmd {
                 update_and_write(&mut genesis, &app_state, &genesis_path)
             },
 
+            GenesisSubcommand::AddBalance {
+                address,
+                coins,
+            } => {
+                let addr = address::validate(&address)?;
+                let coins = parse_coins(&coins)?;
+                update_bank_instantiate_msg(&mut app_state, |msg| add_balance(msg, &addr, coins))?;
+                update_and_write(&mut genesis, &app_state, &genesis_path)
+            },
+
+            GenesisSubcommand::AddNamespace {
+                namespace,
+                admin,
+                hook,
+            } => {
+                let cfg = UpdateNamespaceMsg {
+                    namespace,
+                    admin: admin.as_deref().map(address::validate).transpose()?.map(Into::into),
+                    after_transfer_hook: hook
+                        .as_deref()
+                        .map(address::validate)
+                        .transpose()?
+                        .map(Into::into),
+                };
+                update_bank_instantiate_msg(&mut app_state, |msg| add_namespace(msg, cfg))?;
+                update_and_write(&mut genesis, &app_state, &genesis_path)
+            },
+
             GenesisSubcommand::ListCodes => {
                 let mut code_count = 0;
                 let mut codes = vec![];
@@ -171,27 +281,178 @@ impl GenesisCmd {
                 print::json(&codes)
             },
 
-            GenesisSubcommand::ListContracts => {
-                let mut contracts = vec![];
-                for msg in &app_state.msgs {
-                    if let SdkMsg::Instantiate {
-                        code_id,
-                        label,
-                        admin,
-                        ..
-                    } = msg
-                    {
-                        contracts.push(ContractInfo {
-                            address: address::derive_from_label(label)?.into(),
-                            code_id: *code_id,
-                            label: label.clone(),
-                            admin: admin.clone(),
-                        });
-                    }
+            GenesisSubcommand::ListContracts => print::json(list_contracts(&app_state.msgs)?),
+
+            GenesisSubcommand::Validate => validate(genesis.chain_id.to_string(), app_state),
+        }
+    }
+}
+
+/// Run init chain against a temporary store, and print the report. Return error if init chain
+/// fails.
+fn validate(chain_id: String, app_state: GenesisState) -> Result<(), DaemonError> {
+    // the directory is deleted once dropped, including if init chain fails
+    let temp_dir = tempfile::Builder::new().prefix("cwd-genesis-validate-").tempdir()?;
+    let store_path = temp_dir.path().join("data");
+
+    let msgs = app_state.msgs.clone();
+    let mut reports = vec![];
+
+    let result = {
+        let state_machine = StateMachine::new(Store::open(&store_path)?);
+        state_machine.init_chain_inspect(chain_id.clone(), app_state, |idx, events| {
+            reports.push(MsgReport {
+                index: idx,
+                msg_type: msgs[idx].msg_type(),
+                events,
+            });
+        })
+    };
+
+    // the store is dropped by now, so its files can be deleted
+    temp_dir.close()?;
+
+    // only the contracts instantiated by the messages that were executed
+    let contracts = list_contracts(&msgs[..reports.len()])?;
+
+    match result {
+        Ok(app_hash) => {
+            print::json(ValidationReport {
+                chain_id,
+                msgs: reports,
+                contracts,
+                app_hash: Some(hex::encode(app_hash)),
+                error: None,
+            })?;
+            info!("Genesis state is valid");
+            Ok(())
+        },
+        Err(err) => {
+            print::json(ValidationReport {
+                chain_id,
+                msgs: reports,
+                contracts,
+                app_hash: None,
+                error: Some(err.to_string()),
+            })?;
+            Err(err.into())
+        },
+    }
+}
+
+/// Find the bank contract's instantiate message in the genesis state by its label, and update it
+fn update_bank_instantiate_msg(
+    app_state: &mut GenesisState,
+    update: impl FnOnce(&mut BankInstantiateMsg) -> Result<(), ContractError>,
+) -> Result<(), DaemonError> {
+    let msg = app_state
+        .msgs
+        .iter_mut()
+        .find_map(|sdk_msg| match sdk_msg {
+            SdkMsg::Instantiate {
+                label,
+                msg,
+                ..
+            } if *label == BANK_LABEL => Some(msg),
+            _ => None,
+        })
+        .ok_or(DaemonError::BankNotFound)?;
+
+    let mut bank_msg: BankInstantiateMsg = serde_json::from_value(msg.clone())?;
+    update(&mut bank_msg)?;
+    *msg = serde_json::to_value(bank_msg)?;
+
+    Ok(())
+}
+
+/// Add coins to an account's initial balances, with the same checks as the bank contract
+/// performs upon instantiation: amounts must be non-zero, denoms must be valid, and each account
+/// may only have one balance of each denom.
+fn add_balance(
+    msg: &mut BankInstantiateMsg,
+    addr: &Addr,
+    coins: Vec<Coin>,
+) -> Result<(), ContractError> {
+    // the coins are added to the account's first entry. the genesis file may have been edited by
+    // hand to contain more than one, so all of them are checked for duplicates
+    let idx = match msg.balances.iter().position(|balance| balance.address == addr.as_str()) {
+        Some(idx) => idx,
+        None => {
+            msg.balances.push(Balance {
+                address: addr.into(),
+                coins: vec![],
+            });
+            msg.balances.len() - 1
+        },
+    };
+
+    for coin in coins {
+        if coin.amount.is_zero() {
+            return Err(ContractError::zero_init_balance(addr, coin.denom));
+        }
+
+        let denom = Denom::from_str(&coin.denom)?;
+
+        for balance in msg.balances.iter().filter(|balance| balance.address == addr.as_str()) {
+            for existing in &balance.coins {
+                if Denom::from_str(&existing.denom)? == denom {
+                    return Err(ContractError::duplicate_balance(addr, denom));
                 }
-                print::json(&contracts)
-            },
+            }
         }
+
+        msg.balances[idx].coins.push(coin);
+    }
+
+    Ok(())
+}
+
+/// Add a namespace config, with the same checks as the bank contract performs upon
+/// instantiation: the namespace must be valid, and not configured already.
+fn add_namespace(
+    msg: &mut BankInstantiateMsg,
+    cfg: UpdateNamespaceMsg,
+) -> Result<(), ContractError> {
+    Namespace::from_str(&cfg.namespace)?;
+
+    if msg.namespace_cfgs.iter().any(|existing| existing.namespace == cfg.namespace) {
+        return Err(ContractError::duplicate_namespace(cfg.namespace));
+    }
+
+    msg.namespace_cfgs.push(cfg);
+
+    Ok(())
+}
+
+/// List the contracts instantiated by the genesis messages
+fn list_contracts(msgs: &[SdkMsg]) -> Result<Vec<ContractInfo>, DaemonError> {
+    let mut contracts = vec![];
+    for msg in msgs {
+        if let SdkMsg::Instantiate {
+            code_id,
+            label,
+            admin,
+            ..
+        } = msg
+        {
+            contracts.push(ContractInfo {
+                address: address::derive_from_label(label)?.into(),
+                code_id: *code_id,
+                label: label.clone(),
+                admin: admin.clone(),
+            });
+        }
+    }
+    Ok(contracts)
+}
+
+/// Whether the app state in the genesis file is missing or empty, as is the case for genesis
+/// files created by `tendermint init`
+fn is_empty(app_state: &Value) -> bool {
+    match app_state {
+        Value::Null => true,
+        Value::Object(map) => map.is_empty(),
+        _ => false,
     }
 }
 
@@ -222,3 +483,137 @@ struct ContractInfo {
     label: String,
     admin: Option<String>,
 }
+
+#[derive(Serialize)]
+struct MsgReport {
+    index: usize,
+    msg_type: &'static str,
+    events: Vec<Event>,
+}
+
+#[derive(Serialize)]
+struct ValidationReport {
+    chain_id: String,
+    /// The messages that were executed successfully
+    msgs: Vec<MsgReport>,
+    contracts: Vec<ContractInfo>,
+    /// Hex-encoded app hash returned by init chain, if successful
+    app_hash: Option<String>,
+    /// The error that init chain failed with, if any. If it was a message that
+    /// failed, it is the one following the successful ones
+    error: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::coin;
+
+    use super::*;
+
+    fn mock_bank_msg() -> BankInstantiateMsg {
+        BankInstantiateMsg {
+            owner: "owner".into(),
+            balances: vec![],
+            namespace_cfgs: vec![],
+        }
+    }
+
+    fn mock_namespace_cfg(namespace: &str) -> UpdateNamespaceMsg {
+        UpdateNamespaceMsg {
+            namespace: namespace.into(),
+            admin: None,
+            after_transfer_hook: None,
+        }
+    }
+
+    #[test]
+    fn adding_balances() {
+        let alice = Addr::unchecked("alice");
+        let bob = Addr::unchecked("bob");
+        let mut msg = mock_bank_msg();
+
+        add_balance(&mut msg, &alice, vec![coin(100, "uatom")]).unwrap();
+        add_balance(&mut msg, &bob, vec![coin(50, "uatom")]).unwrap();
+        // coins added to an account that already has a balance go to the same entry
+        add_balance(&mut msg, &alice, vec![coin(25, "uosmo"), coin(10, "ibc/1234ABCD")]).unwrap();
+
+        assert_eq!(
+            msg.balances,
+            vec![
+                Balance {
+                    address: alice.to_string(),
+                    coins: vec![coin(100, "uatom"), coin(25, "uosmo"), coin(10, "ibc/1234ABCD")],
+                },
+                Balance {
+                    address: bob.to_string(),
+                    coins: vec![coin(50, "uatom")],
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn rejecting_invalid_balances() {
+        let alice = Addr::unchecked("alice");
+        let mut msg = mock_bank_msg();
+        add_balance(&mut msg, &alice, vec![coin(100, "uatom")]).unwrap();
+
+        let err = add_balance(&mut msg, &alice, vec![coin(0, "uosmo")]).unwrap_err();
+        assert_eq!(err.to_string(), ContractError::zero_init_balance(&alice, "uosmo").to_string());
+
+        let err = add_balance(&mut msg, &alice, vec![coin(1, "1uosmo")]).unwrap_err();
+        assert!(matches!(err, ContractError::Denom(_)));
+
+        // the denom is already in the account's balance
+        let err = add_balance(&mut msg, &alice, vec![coin(1, "uatom")]).unwrap_err();
+        assert_eq!(err.to_string(), ContractError::duplicate_balance(&alice, "uatom").to_string());
+
+        // the same denom appears twice among the coins being added
+        let err =
+            add_balance(&mut msg, &alice, vec![coin(1, "uosmo"), coin(2, "uosmo")]).unwrap_err();
+        assert_eq!(err.to_string(), ContractError::duplicate_balance(&alice, "uosmo").to_string());
+    }
+
+    #[test]
+    fn rejecting_duplicate_balances_across_entries() {
+        let alice = Addr::unchecked("alice");
+        let mut msg = mock_bank_msg();
+        // a genesis file edited by hand may have more than one entry for the same account
+        msg.balances = vec![
+            Balance {
+                address: alice.to_string(),
+                coins: vec![coin(100, "uatom")],
+            },
+            Balance {
+                address: alice.to_string(),
+                coins: vec![coin(100, "uosmo")],
+            },
+        ];
+
+        let err = add_balance(&mut msg, &alice, vec![coin(1, "uosmo")]).unwrap_err();
+        assert_eq!(err.to_string(), ContractError::duplicate_balance(&alice, "uosmo").to_string());
+
+        add_balance(&mut msg, &alice, vec![coin(1, "ujuno")]).unwrap();
+        assert_eq!(msg.balances[0].coins, vec![coin(100, "uatom"), coin(1, "ujuno")]);
+    }
+
+    #[test]
+    fn adding_namespaces() {
+        let mut msg = mock_bank_msg();
+
+        add_namespace(&mut msg, mock_namespace_cfg("ibc")).unwrap();
+        add_namespace(&mut msg, mock_namespace_cfg("factory")).unwrap();
+        assert_eq!(
+            msg.namespace_cfgs,
+            vec![mock_namespace_cfg("ibc"), mock_namespace_cfg("factory")],
+        );
+
+        let err = add_namespace(&mut msg, mock_namespace_cfg("ibc")).unwrap_err();
+        assert_eq!(err.to_string(), ContractError::duplicate_namespace("ibc").to_string());
+
+        let err = add_namespace(&mut msg, mock_namespace_cfg("123abc")).unwrap_err();
+        assert!(matches!(err, ContractError::Denom(_)));
+
+        assert_eq!(msg.namespace_cfgs.len(), 2);
+    }
+}
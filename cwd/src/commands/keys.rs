@@ -1,15 +1,29 @@
-use std::path::Path;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
 
 use bip32::{Language, Mnemonic};
 use clap::{Args, Subcommand};
+use colored::Colorize;
+use cosmwasm_std::Binary;
+use cw_sdk::OffchainSignDoc;
 use rand_core::OsRng;
 
-use crate::{print, prompt, DaemonError, Key, Keyring};
+use crate::{
+    key::Algorithm,
+    print::{self, OutputFormat},
+    prompt, DaemonError, GlobalOpts, Key, Keyring,
+};
 
 #[derive(Args)]
 pub struct KeysCmd {
     #[command(subcommand)]
     pub subcommand: KeysSubcmd,
+
+    /// Output format
+    #[arg(long, global = true, value_enum, default_value_t = OutputFormat::Text)]
+    output: OutputFormat,
 }
 
 #[derive(Subcommand)]
@@ -26,6 +40,10 @@ pub enum KeysSubcmd {
         /// BIP-44 coin type for HD derivation
         #[arg(long, default_value_t = 118)]
         coin_type: u32,
+
+        /// Signature scheme of the key
+        #[arg(long, value_enum, default_value_t = Algorithm::Secp256k1)]
+        algo: Algorithm,
     },
 
     /// Delete a given key
@@ -44,29 +62,100 @@ pub enum KeysSubcmd {
     /// List all keys
     #[command(alias = "ls")]
     List,
+
+    /// Rename a key
+    #[command(alias = "mv")]
+    Rename {
+        /// Name of the key to rename
+        name: String,
+
+        /// The key's new name
+        new_name: String,
+    },
+
+    /// Export a key as an armored text block, encrypted with a passphrase. If the keyring password
+    /// is provided by --password-fd or CWD_KEYRING_PASSWORD, it's used as the passphrase
+    Export {
+        /// Name of the key to export
+        name: String,
+
+        /// Print the raw private key in hex encoding instead, unencrypted. Anyone who sees the
+        /// output has full control over the account
+        #[arg(long)]
+        unsafe_hex: bool,
+    },
+
+    /// Sign an arbitrary message off-chain, to prove the ownership of the key's address
+    SignMessage {
+        /// Name of the key to sign with
+        name: String,
+
+        /// The message to sign
+        text: String,
+    },
+
+    /// Verify a signature produced by `cwd keys sign-message`
+    VerifyMessage {
+        /// Address of the account that signed the message
+        address: String,
+
+        /// The signer's public key, in hex encoding
+        pubkey: String,
+
+        /// The signature, in base64 encoding
+        signature: String,
+
+        /// The message that was signed
+        text: String,
+
+        /// Signature scheme of the public key
+        #[arg(long, value_enum, default_value_t = Algorithm::Secp256k1)]
+        algo: Algorithm,
+    },
+
+    /// Import a key exported with `cwd keys export`. If the keyring password is provided by
+    /// --password-fd or CWD_KEYRING_PASSWORD, it's used as the passphrase
+    Import {
+        /// Name under which the key will be saved
+        name: String,
+
+        /// Path to the armored key
+        file: PathBuf,
+    },
 }
 
 impl KeysCmd {
-    pub fn run(&self, home_dir: &Path) -> Result<(), DaemonError> {
-        let keyring = Keyring::new(home_dir.join("keys"))?;
+    pub fn run(&self, home_dir: &Path, opts: &GlobalOpts) -> Result<(), DaemonError> {
+        let keyring = Keyring::new(home_dir, opts.keyring_backend, opts.password_fd)?;
 
         match &self.subcommand {
             KeysSubcmd::Add {
                 name,
                 recover,
                 coin_type,
+                algo,
             } => {
                 let mnemonic = if *recover {
                     let phrase: String = prompt::input("enter your BIP-39 mnemonic")?;
-                    println!("\n");
+                    print::banner(self.output, "\n");
                     Mnemonic::new(phrase, Language::English)?
                 } else {
                     Mnemonic::random(OsRng, Language::English)
                 };
 
-                let key = Key::from_mnemonic(name, &mnemonic, *coin_type)?;
+                let key = Key::from_mnemonic(name, &mnemonic, *coin_type, *algo)?;
                 keyring.set(&key)?;
 
+                if self.output == OutputFormat::Json {
+                    // the mnemonic of a recovered key is already known to the
+                    // user, so it's not printed again
+                    return if *recover {
+                        print::key(&key)
+                    } else {
+                        print::key_with_mnemonic(&key, mnemonic.phrase())
+                    };
+                }
+
                 println!();
                 print::key(&key)?;
 
@@ -93,6 +182,73 @@ impl KeysCmd {
             KeysSubcmd::Delete {
                 name,
             } => keyring.delete(name)?,
+
+            KeysSubcmd::Rename {
+                name,
+                new_name,
+            } => {
+                let key = keyring.rename(name, new_name)?;
+                print::key(&key)?;
+            },
+
+            KeysSubcmd::Export {
+                name,
+                unsafe_hex,
+            } => {
+                if *unsafe_hex {
+                    let msg = "⚠️ Print the private key unencrypted? Anyone who sees it \
+                        controls the account";
+                    if opts.yes || prompt::confirm(format!("{}", msg.bold()))? {
+                        let key = keyring.get(name)?;
+                        println!("{}", hex::encode(key.privkey_bytes()));
+                    }
+                } else {
+                    let passphrase = keyring.read_passphrase(
+                        format!("{}", "🔑 Enter a passphrase to encrypt the exported key".bold()),
+                        true,
+                    )?;
+                    println!("{}", keyring.export(name, &passphrase)?);
+                }
+            },
+
+            KeysSubcmd::SignMessage {
+                name,
+                text,
+            } => {
+                let key = keyring.get(name)?;
+                let signature = key.sign_offchain(text.as_bytes())?;
+                print::offchain_signature(&key, &signature)?;
+            },
+
+            KeysSubcmd::VerifyMessage {
+                address,
+                pubkey,
+                signature,
+                text,
+                algo,
+            } => {
+                let pubkey = algo.pubkey(hex::decode(pubkey)?);
+                let signature = Binary::from_base64(signature)?;
+                OffchainSignDoc::new(address, text.as_bytes().to_vec())
+                    .verify(&pubkey, &signature)?;
+                print::banner(self.output, "✅ Signature is valid".bold());
+            },
+
+            KeysSubcmd::Import {
+                name,
+                file,
+            } => {
+                if !file.exists() {
+                    return Err(DaemonError::file_not_found(file)?);
+                }
+                let armor = fs::read_to_string(file)?;
+                let passphrase = keyring.read_passphrase(
+                    format!("{}", "🔑 Enter the passphrase of the exported key".bold()),
+                    false,
+                )?;
+                let key = keyring.import(name, &armor, &passphrase)?;
+                print::key(&key)?;
+            },
         }
 
         Ok(())
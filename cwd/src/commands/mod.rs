@@ -1,4 +1,5 @@
 mod debug;
+mod export;
 mod genesis;
 mod init;
 mod keys;
@@ -9,6 +10,6 @@ mod tendermint;
 mod tx;
 
 pub use self::{
-    debug::DebugCmd, genesis::GenesisCmd, init::InitCmd, keys::KeysCmd, query::QueryCmd,
-    reset::ResetCmd, start::StartCmd, tendermint::TendermintCmd, tx::TxCmd,
+    debug::DebugCmd, export::ExportCmd, genesis::GenesisCmd, init::InitCmd, keys::KeysCmd,
+    query::QueryCmd, reset::ResetCmd, start::StartCmd, tendermint::TendermintCmd, tx::TxCmd,
 };
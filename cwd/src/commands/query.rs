@@ -5,12 +5,14 @@ use std::{
 };
 
 use chrono::{DateTime, SecondsFormat, Utc};
-use clap::{Args, Subcommand};
-use cosmwasm_std::{BlockInfo, ContractResult};
+use clap::{ArgGroup, Args, Subcommand};
+use cosmwasm_std::{Binary, BlockInfo, ContractResult};
 use cw_sdk::{
-    hash::sha256, AccountResponse, CodeResponse, ContractResponse, InfoResponse, SdkQuery,
+    hash::sha256, AccountResponse, CodeResponse, ContractHistoryEntry, ContractResponse,
+    FeeAllowanceResponse, GrantResponse, InfoResponse, SdkQuery, UpgradePlan, WasmRawRecord,
     WasmRawResponse, WasmSmartResponse,
 };
+use cw_store::prefix::namespace_upper_bound;
 use serde::Serialize;
 use serde_json::Value;
 use tracing::{error, info};
@@ -58,9 +60,19 @@ pub enum QuerySubcmd {
         label: String,
     },
 
-    /// Enumerate all contracts by label
+    /// Enumerate all contracts by label, or only those running a code id or administered by an
+    /// account
+    #[command(group(ArgGroup::new("filter").args(["code_id", "admin"])))]
     Contracts {
-        /// Start after this contract label
+        /// Only list contracts running this code id, by address
+        #[arg(long)]
+        code_id: Option<u64>,
+
+        /// Only list contracts administered by this account, by address
+        #[arg(long)]
+        admin: Option<String>,
+
+        /// Start after this contract label, or address if filtering by code id or admin
         #[arg(long)]
         start_after: Option<String>,
 
@@ -69,6 +81,12 @@ pub enum QuerySubcmd {
         limit: Option<u32>,
     },
 
+    /// Query the code history of a contract, i.e. its instantiation and migrations
+    ContractHistory {
+        /// Contract address or label
+        contract: String,
+    },
+
     /// Retrieve the metadata and wasm byte code corresponding to the given code id
     Code {
         /// Code id
@@ -90,6 +108,51 @@ pub enum QuerySubcmd {
         limit: Option<u32>,
     },
 
+    /// Query the fee allowance granted by a granter to a grantee
+    FeeAllowance {
+        /// Granter address
+        granter: String,
+        /// Grantee address
+        grantee: String,
+    },
+
+    /// Enumerate all fee allowances granted by a granter
+    FeeAllowances {
+        /// Granter address
+        granter: String,
+
+        /// Start after this grantee address
+        #[arg(long)]
+        start_after: Option<String>,
+
+        /// The maximum number of results to be returned in this query
+        #[arg(long)]
+        limit: Option<u32>,
+    },
+
+    /// Enumerate all grants given by a granter
+    Grants {
+        /// Granter address
+        granter: String,
+
+        /// Start after this grantee address
+        #[arg(long)]
+        start_after: Option<String>,
+
+        /// The maximum number of results to be returned in this query
+        #[arg(long)]
+        limit: Option<u32>,
+    },
+
+    /// Query the chain upgrade that is scheduled but not yet applied
+    UpgradePlan,
+
+    /// Query the height at which a chain upgrade was applied
+    AppliedUpgrade {
+        /// Name of the upgrade
+        name: String,
+    },
+
     /// Perform a wasm raw query
     WasmRaw {
         /// Contract address
@@ -98,6 +161,21 @@ pub enum QuerySubcmd {
         key: String,
     },
 
+    /// Dump the raw key-value pairs in a wasm contract's storage
+    WasmDump {
+        /// Contract address
+        contract: String,
+
+        /// Only dump keys with this prefix: hex encoding if starting with 0x, otherwise UTF-8.
+        /// For a cw-storage-plus map, use --namespace instead
+        #[arg(long)]
+        prefix: Option<String>,
+
+        /// Only dump entries of the cw-storage-plus map with this namespace
+        #[arg(long, conflicts_with = "prefix")]
+        namespace: Option<String>,
+    },
+
     /// Perform a wasm smart query
     WasmSmart {
         /// Contract address
@@ -164,15 +242,42 @@ impl QueryCmd {
             },
 
             QuerySubcmd::Contracts {
+                code_id,
+                admin,
                 start_after,
                 limit,
             } => {
-                let response: Vec<ContractResponse> = do_abci_query(
-                    &client,
-                    SdkQuery::Contracts {
+                // clap ensures at most one of the filters is provided
+                let query = match (code_id, admin) {
+                    (Some(code_id), _) => SdkQuery::ContractsByCode {
+                        code_id,
+                        start_after,
+                        limit,
+                    },
+                    (None, Some(admin)) => SdkQuery::ContractsByAdmin {
+                        admin,
+                        start_after,
+                        limit,
+                    },
+                    (None, None) => SdkQuery::Contracts {
                         start_after,
                         limit,
                     },
+                };
+
+                let response: Vec<ContractResponse> = do_abci_query(&client, query).await?;
+
+                print::json(response)?;
+            },
+
+            QuerySubcmd::ContractHistory {
+                contract,
+            } => {
+                let response: Vec<ContractHistoryEntry> = do_abci_query(
+                    &client,
+                    SdkQuery::ContractHistory {
+                        contract,
+                    },
                 )
                 .await?;
 
@@ -220,6 +325,79 @@ impl QueryCmd {
                 print::json(response)?;
             },
 
+            QuerySubcmd::FeeAllowance {
+                granter,
+                grantee,
+            } => {
+                let response: FeeAllowanceResponse = do_abci_query(
+                    &client,
+                    SdkQuery::FeeAllowance {
+                        granter,
+                        grantee,
+                    },
+                )
+                .await?;
+
+                print::json(response)?;
+            },
+
+            QuerySubcmd::FeeAllowances {
+                granter,
+                start_after,
+                limit,
+            } => {
+                let response: Vec<FeeAllowanceResponse> = do_abci_query(
+                    &client,
+                    SdkQuery::FeeAllowances {
+                        granter,
+                        start_after,
+                        limit,
+                    },
+                )
+                .await?;
+
+                print::json(response)?;
+            },
+
+            QuerySubcmd::Grants {
+                granter,
+                start_after,
+                limit,
+            } => {
+                let response: Vec<GrantResponse> = do_abci_query(
+                    &client,
+                    SdkQuery::Grants {
+                        granter,
+                        start_after,
+                        limit,
+                    },
+                )
+                .await?;
+
+                print::json(response)?;
+            },
+
+            QuerySubcmd::UpgradePlan => {
+                let response: Option<UpgradePlan> =
+                    do_abci_query(&client, SdkQuery::UpgradePlan {}).await?;
+
+                print::json(response)?;
+            },
+
+            QuerySubcmd::AppliedUpgrade {
+                name,
+            } => {
+                let response: Option<u64> = do_abci_query(
+                    &client,
+                    SdkQuery::AppliedUpgrade {
+                        name,
+                    },
+                )
+                .await?;
+
+                print::json(response)?;
+            },
+
             QuerySubcmd::WasmRaw {
                 contract,
                 key,
@@ -236,6 +414,78 @@ impl QueryCmd {
                 print::json(response)?;
             },
 
+            QuerySubcmd::WasmDump {
+                contract,
+                prefix,
+                namespace,
+            } => {
+                let prefix = match (prefix, namespace) {
+                    (Some(prefix), _) => match prefix.strip_prefix("0x") {
+                        Some(hex_str) => hex::decode(hex_str)?,
+                        None => prefix.into_bytes(),
+                    },
+                    // cw-storage-plus prefixes map keys with the namespace's
+                    // length as a 2-byte big endian integer
+                    (None, Some(namespace)) => {
+                        let len = u16::try_from(namespace.len())
+                            .map_err(|_| DaemonError::malformed_payload("namespace too long"))?;
+                        [len.to_be_bytes().as_slice(), namespace.as_bytes()].concat()
+                    },
+                    (None, None) => vec![],
+                };
+
+                let mut records = vec![];
+
+                // the range query's lower bound is exclusive, so the key that
+                // equals the prefix, if exists, needs to be queried separately
+                if !prefix.is_empty() {
+                    let response: WasmRawResponse = do_abci_query(
+                        &client,
+                        SdkQuery::WasmRaw {
+                            contract: contract.clone(),
+                            key: prefix.clone().into(),
+                        },
+                    )
+                    .await?;
+
+                    if let Some(value) = response.value {
+                        records.push(DecodedRecord::from(WasmRawRecord {
+                            key: prefix.clone().into(),
+                            value,
+                        }));
+                    }
+                }
+
+                let (start_after, end) = prefix_bounds(&prefix);
+                let mut start_after = start_after.map(Binary::from);
+                let end = end.map(Binary::from);
+
+                // fetch pages until one is not full
+                loop {
+                    let page: Vec<WasmRawRecord> = do_abci_query(
+                        &client,
+                        SdkQuery::WasmRawRange {
+                            contract: contract.clone(),
+                            start_after: start_after.clone(),
+                            end: end.clone(),
+                            limit: Some(WASM_DUMP_PAGE_SIZE),
+                            order: None,
+                        },
+                    )
+                    .await?;
+
+                    let page_len = page.len();
+                    start_after = page.last().map(|record| record.key.clone());
+                    records.extend(page.into_iter().map(DecodedRecord::from));
+
+                    if page_len < WASM_DUMP_PAGE_SIZE as usize {
+                        break;
+                    }
+                }
+
+                print::json(records)?;
+            },
+
             QuerySubcmd::WasmSmart {
                 contract,
                 msg,
@@ -270,6 +520,76 @@ impl QueryCmd {
     }
 }
 
+/// The number of records to request in each query when dumping a contract's
+/// storage. This is the maximum allowed by the state machine.
+const WASM_DUMP_PAGE_SIZE: u32 = 100;
+
+/// A raw key-value pair in a contract's storage, decoded for readability.
+#[derive(Serialize)]
+pub struct DecodedRecord {
+    /// The key split into its cw-storage-plus namespaces followed by the
+    /// remainder. Each part is displayed as UTF-8 if possible, or hex otherwise
+    key: Vec<String>,
+    key_hex: String,
+    /// The value decoded as JSON, or hex-encoded if it's not valid JSON
+    value: Value,
+}
+
+impl From<WasmRawRecord> for DecodedRecord {
+    fn from(record: WasmRawRecord) -> Self {
+        let value = serde_json::from_slice(&record.value)
+            .unwrap_or_else(|_| Value::String(hex::encode(&record.value)));
+        Self {
+            key: split_key(&record.key).into_iter().map(display_bytes).collect(),
+            key_hex: hex::encode(&record.key),
+            value,
+        }
+    }
+}
+
+/// The bounds of a range query that covers all keys with the given prefix,
+/// except the prefix itself: start after the prefix, and end before the first
+/// key that is greater than all keys with the prefix.
+///
+/// The end is unbounded if there is no such key, i.e. if the prefix consists
+/// only of 0xff bytes. Both bounds are unbounded if the prefix is empty.
+fn prefix_bounds(prefix: &[u8]) -> (Option<Vec<u8>>, Option<Vec<u8>>) {
+    if prefix.is_empty() {
+        return (None, None);
+    }
+    let end = (!prefix.iter().all(|byte| *byte == 0xff)).then(|| namespace_upper_bound(prefix));
+    (Some(prefix.to_vec()), end)
+}
+
+/// Split a key into the length-prefixed namespaces that cw-storage-plus
+/// prepends to map keys, followed by the remainder.
+///
+/// This is a best effort: a remainder that happens to begin with what looks
+/// like a length prefix is split as well.
+fn split_key(mut key: &[u8]) -> Vec<&[u8]> {
+    let mut parts = vec![];
+    while key.len() > 2 {
+        let len = u16::from_be_bytes([key[0], key[1]]) as usize;
+        if len == 0 || key.len() < 2 + len {
+            break;
+        }
+        parts.push(&key[2..2 + len]);
+        key = &key[2 + len..];
+    }
+    if !key.is_empty() {
+        parts.push(key);
+    }
+    parts
+}
+
+/// Display bytes as UTF-8 if they are printable, or hex otherwise
+fn display_bytes(bytes: &[u8]) -> String {
+    match std::str::from_utf8(bytes) {
+        Ok(s) if !s.chars().any(char::is_control) => s.to_string(),
+        _ => format!("0x{}", hex::encode(bytes)),
+    }
+}
+
 /// Just like `CodeResponse` but includes the byte code's hash instead of the
 /// full byte code. Used for CLI output.
 #[derive(Serialize)]
@@ -292,6 +612,7 @@ impl From<&CodeResponse> for HashedCodeResponse {
 pub struct PrettyInfoResponse {
     last_committed_block: PrettyBlockInfo,
     code_count: u64,
+    app_version: u64,
 }
 
 impl From<InfoResponse> for PrettyInfoResponse {
@@ -299,6 +620,7 @@ impl From<InfoResponse> for PrettyInfoResponse {
         Self {
             last_committed_block: res.last_committed_block.into(),
             code_count: res.code_count,
+            app_version: res.app_version,
         }
     }
 }
@@ -324,3 +646,52 @@ impl From<BlockInfo> for PrettyBlockInfo {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splitting_keys() {
+        // a map key under the "balances" namespace
+        let key: Vec<u8> = [b"\x00\x08balances".as_slice(), b"\x00\x04addr", b"uatom"].concat();
+        let parts: Vec<&[u8]> = vec![b"balances", b"addr", b"uatom"];
+        assert_eq!(split_key(&key), parts);
+
+        // an item, which isn't length-prefixed
+        assert_eq!(split_key(b"config"), vec![b"config".as_slice()]);
+
+        // a length prefix longer than the rest of the key is not split
+        assert_eq!(split_key(b"\x00\x09balances"), vec![b"\x00\x09balances".as_slice()]);
+
+        // neither is a zero length prefix
+        assert_eq!(split_key(b"\x00\x00abc"), vec![b"\x00\x00abc".as_slice()]);
+
+        assert!(split_key(b"").is_empty());
+    }
+
+    #[test]
+    fn displaying_bytes() {
+        assert_eq!(display_bytes(b"balances"), "balances");
+        assert_eq!(display_bytes("ラベル".as_bytes()), "ラベル");
+        // control characters and invalid UTF-8 are displayed as hex
+        assert_eq!(display_bytes(b"\x00\x08"), "0x0008");
+        assert_eq!(display_bytes(b"\xff\xfe"), "0xfffe");
+    }
+
+    #[test]
+    fn computing_prefix_bounds() {
+        assert_eq!(prefix_bounds(b""), (None, None));
+        assert_eq!(
+            prefix_bounds(b"\x00\x01"),
+            (Some(b"\x00\x01".to_vec()), Some(b"\x00\x02".to_vec())),
+        );
+        // trailing 0xff bytes carry over
+        assert_eq!(
+            prefix_bounds(b"\x01\xff\xff"),
+            (Some(b"\x01\xff\xff".to_vec()), Some(b"\x02\x00\x00".to_vec())),
+        );
+        // no key is greater than all keys prefixed by 0xff bytes only
+        assert_eq!(prefix_bounds(b"\xff\xff"), (Some(b"\xff\xff".to_vec()), None));
+    }
+}
@@ -3,11 +3,11 @@ use std::sync::mpsc;
 
 use clap::Args;
 use tendermint_abci::ServerBuilder;
-use tracing::info;
+use tracing::{error, info};
 
-use cw_server::{App, AppDriver};
+use cw_server::{query_views, serve_metrics, serve_rest, App, AppDriver};
 use cw_state_machine::StateMachine;
-use cw_store::Store;
+use cw_store::{SnapshotManager, Store};
 
 use crate::{AppConfig, DaemonError};
 
@@ -25,21 +25,62 @@ impl StartCmd {
         info!("Loaded Merk store");
 
         // create a new state machine instance wrapping the store
-        let state_machine = StateMachine::new(store);
+        let mut state_machine = StateMachine::new(store);
+
+        // enable snapshots for state sync, if configured
+        if app_cfg.snapshot_interval > 0 {
+            state_machine = state_machine.with_snapshots(SnapshotManager::new(
+                home_dir.join("snapshots"),
+                app_cfg.snapshot_interval,
+                app_cfg.snapshot_keep_recent,
+            ));
+            info!(
+                "Enabled snapshots every {} blocks, keeping the most recent {}",
+                app_cfg.snapshot_interval,
+                app_cfg.snapshot_keep_recent,
+            );
+        }
 
         // create a channel between the App and AppDriver
         let (cmd_tx, cmd_rx) = mpsc::channel();
         let app = App {
             cmd_tx,
+            index_events: app_cfg.index_events.iter().cloned().collect(),
         };
         let mut driver = AppDriver {
             state_machine,
             cmd_rx,
+            views: None,
         };
 
         // create the ABCI server
         let server = ServerBuilder::default().bind(app_cfg.listen_addr, app)?;
 
+        // serve metrics, if configured
+        if let Some(metrics_listen_addr) = app_cfg.metrics_listen_addr {
+            info!("Serving metrics at {metrics_listen_addr}");
+            std::thread::spawn(move || {
+                if let Err(err) = serve_metrics(&metrics_listen_addr) {
+                    error!("Failed to serve metrics: {err}");
+                }
+            });
+        }
+
+        // serve the REST API, if configured. the queries are served from views
+        // of the committed state that the AppDriver publishes periodically
+        if let Some(rest_listen_addr) = app_cfg.rest_listen_addr {
+            let (publisher, view) =
+                query_views(home_dir.join("query_views"), app_cfg.rest_view_interval)?;
+            driver.views = Some(publisher);
+
+            info!("Serving REST API at {rest_listen_addr}");
+            std::thread::spawn(move || {
+                if let Err(err) = serve_rest(&rest_listen_addr, view) {
+                    error!("Failed to serve REST API: {err}");
+                }
+            });
+        }
+
         // spin up the App and AppDriver
         std::thread::spawn(move || server.listen().unwrap());
 
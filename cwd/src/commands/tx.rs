@@ -2,18 +2,28 @@ use std::{
     any::type_name,
     fs,
     path::{Path, PathBuf},
+    time::Duration,
 };
 
-use clap::{Args, Subcommand};
+use chrono::DateTime;
+use clap::{ArgGroup, Args, Subcommand, ValueEnum};
 use colored::*;
-use cosmwasm_std::Addr;
-use cw_sdk::{Account, AccountResponse, SdkMsg, SdkQuery, TxBody};
-use tendermint_rpc::Client;
+use cosmwasm_std::{Addr, Binary, Timestamp};
+use cw_sdk::{
+    address,
+    helpers::parse_coins, Account, AccountResponse, Authorization, FeeAllowance, SdkMsg, SdkQuery,
+    SimulateResponse, Tx, TxBody,
+};
+use serde::Serialize;
+use tendermint::{abci, Hash};
+use tendermint_rpc::{Client, HttpClient};
 use tracing::warn;
 
 use crate::{
     client::{create_http_client, do_abci_query},
-    print, prompt, ClientConfig, DaemonError, Keyring,
+    batch,
+    print::{self, OutputFormat},
+    prompt, ClientConfig, DaemonError, GlobalOpts, Keyring,
 };
 
 #[derive(Args)]
@@ -21,21 +31,117 @@ pub struct TxCmd {
     #[command(subcommand)]
     pub subcommand: TxSubcmd,
 
-    /// Name of the key which will sign the transaction
-    #[arg(long)]
-    from: String,
+    /// Name of the key which will sign the transaction. With --generate-only,
+    /// this can also be the sender's address
+    #[arg(long, global = true)]
+    from: Option<String>,
 
     /// Chain id; overrides default value in client config
-    #[arg(long)]
+    #[arg(long, global = true)]
     chain_id: Option<String>,
 
     /// Sequence number of the signing account
-    #[arg(long)]
+    #[arg(long, global = true)]
     sequence: Option<u64>,
 
     /// Tendermint RPC endpoint; overrides default value in client config
-    #[arg(long)]
+    #[arg(long, global = true)]
     node: Option<String>,
+
+    /// Block height after which the transaction can no longer be included
+    #[arg(long)]
+    timeout_height: Option<u64>,
+
+    /// Time after which the transaction can no longer be included, in RFC 3339 format,
+    /// e.g. 2023-01-01T00:00:00Z
+    #[arg(long, value_parser = parse_timestamp)]
+    timeout: Option<Timestamp>,
+
+    /// Fee to pay for the transaction, e.g. 100uatom,20ufoo
+    #[arg(long)]
+    fees: Option<String>,
+
+    /// Address of the account paying the fee, which must have granted a fee allowance to the
+    /// sender
+    #[arg(long, conflicts_with = "fee_payer_key")]
+    fee_payer: Option<String>,
+
+    /// Name of the key which will pay the fee and co-sign the transaction
+    #[arg(long, conflicts_with = "generate_only")]
+    fee_payer_key: Option<String>,
+
+    /// Execute the message on behalf of this account, which must have given the sender a grant
+    /// that permits the message
+    #[arg(long)]
+    granter: Option<String>,
+
+    /// Gas limit of the transaction, or "auto" to estimate it by simulating the transaction.
+    /// The chain's default gas limit applies if not provided
+    #[arg(long, value_parser = parse_gas)]
+    gas: Option<Gas>,
+
+    /// When estimating the gas limit, multiply the simulated gas usage by this factor
+    #[arg(long, default_value_t = 1.3)]
+    gas_adjustment: f64,
+
+    /// Simulate the transaction and print the result, without broadcasting it
+    #[arg(long)]
+    simulate: bool,
+
+    /// Print the unsigned transaction body, without signing or broadcasting it,
+    /// so that it can be signed offline with `cwd tx sign`
+    #[arg(long, conflicts_with = "simulate")]
+    generate_only: bool,
+
+    /// How to broadcast the transaction, and how long to wait for its result
+    #[arg(long, global = true, value_enum, default_value_t = BroadcastMode::Wait)]
+    broadcast_mode: BroadcastMode,
+
+    /// Output format
+    #[arg(long, global = true, value_enum, default_value_t = OutputFormat::Text)]
+    output: OutputFormat,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum BroadcastMode {
+    /// Return right away, without waiting for the result of CheckTx
+    Async,
+    /// Wait for the result of CheckTx
+    Sync,
+    /// Wait for the tx to be included in a block, using Tendermint's
+    /// broadcast_tx_commit, which is not recommended for production
+    Commit,
+    /// Wait for the result of CheckTx, then poll until the tx is included in a
+    /// block
+    Wait,
+}
+
+/// How often to query whether the tx has been included in a block
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How many times to query before giving up on the tx being included
+const POLL_ATTEMPTS: u32 = 60;
+
+#[derive(Clone, Copy)]
+enum Gas {
+    Auto,
+    Limit(u64),
+}
+
+/// Parse the gas limit, which is either an integer or "auto"
+fn parse_gas(s: &str) -> Result<Gas, String> {
+    if s == "auto" {
+        return Ok(Gas::Auto);
+    }
+    s.parse().map(Gas::Limit).map_err(|_| format!("expecting an integer or `auto`, found `{s}`"))
+}
+
+/// Parse an RFC 3339 datetime string into a timestamp
+fn parse_timestamp(s: &str) -> Result<Timestamp, String> {
+    let datetime = DateTime::parse_from_rfc3339(s).map_err(|err| err.to_string())?;
+    let nanos = u64::try_from(datetime.timestamp_nanos())
+        .map_err(|_| "timestamp must not be before 1970-01-01T00:00:00Z".to_string())?;
+    Ok(Timestamp::from_nanos(nanos))
 }
 
 #[derive(Subcommand)]
@@ -87,81 +193,286 @@ pub enum TxSubcmd {
         /// Migrate message in JSON format
         msg: String,
     },
+
+    /// Allow an account to send transactions whose fees are paid by the sender
+    FeeGrant {
+        /// Address of the grantee
+        grantee: String,
+
+        /// The maximum amount of coins the grantee can spend on fees, e.g. 100uatom,20ufoo.
+        /// The allowance is unlimited if not provided
+        #[arg(long)]
+        spend_limit: Option<String>,
+
+        /// Time after which the allowance can no longer be used, in RFC 3339 format
+        #[arg(long, value_parser = parse_timestamp)]
+        expiration: Option<Timestamp>,
+    },
+
+    /// Revoke a fee allowance previously granted by the sender
+    FeeRevoke {
+        /// Address of the grantee
+        grantee: String,
+    },
+
+    /// Allow an account to execute messages on behalf of the sender
+    #[command(group(ArgGroup::new("authorization").required(true).args(["msg_type", "contract"])))]
+    Grant {
+        /// Address of the grantee
+        grantee: String,
+
+        /// Permit any message of this type, e.g. execute
+        #[arg(long)]
+        msg_type: Option<String>,
+
+        /// Permit executing this contract, and nothing else
+        #[arg(long)]
+        contract: Option<String>,
+
+        /// Time after which the grant can no longer be used, in RFC 3339 format
+        #[arg(long, value_parser = parse_timestamp)]
+        expiration: Option<Timestamp>,
+    },
+
+    /// Revoke a grant previously given by the sender
+    Revoke {
+        /// Address of the grantee
+        grantee: String,
+    },
+
+    /// Schedule a chain upgrade. The sender must be the upgrade authority
+    ScheduleUpgrade {
+        /// Name of the upgrade, for which the new binary must have a handler
+        name: String,
+
+        /// Height at which the upgrade is applied
+        height: u64,
+
+        /// Additional info for node operators, e.g. where to find the new binary
+        #[arg(long)]
+        info: Option<String>,
+    },
+
+    /// Sign an unsigned transaction body generated with --generate-only, and
+    /// print the signed transaction. No node is contacted, so --from,
+    /// --sequence and --chain-id are required
+    Sign {
+        /// Path to the unsigned transaction body in JSON format
+        file: PathBuf,
+    },
+
+    /// Execute multiple messages in a single transaction, which are listed in a JSON or YAML
+    /// batch file. See the `batch` module for the file's format
+    Batch {
+        /// Path to the batch file
+        file: PathBuf,
+    },
+
+    /// Broadcast a transaction signed with `cwd tx sign`
+    Broadcast {
+        /// Path to the signed transaction in JSON format
+        file: PathBuf,
+    },
 }
 
 impl TxCmd {
-    pub async fn run(self, home_dir: &Path) -> Result<(), DaemonError> {
-        // load sender key
-        let keyring = Keyring::new(home_dir.join("keys"))?;
-        let key = keyring.get(&self.from)?;
-        let sender_addr = key.address()?;
+    pub async fn run(self, home_dir: &Path, opts: &GlobalOpts) -> Result<(), DaemonError> {
+        // these subcommands work on txs generated previously, rather than
+        // creating new ones
+        match &self.subcommand {
+            TxSubcmd::Sign {
+                file,
+            } => return self.sign(home_dir, opts, file),
+            TxSubcmd::Broadcast {
+                file,
+            } => return self.broadcast(home_dir, opts, file).await,
+            _ => (),
+        }
 
         // create tendermint client
         let client_cfg = ClientConfig::load(home_dir)?;
         let client = create_http_client(self.node.as_ref(), &client_cfg)?;
 
+        let msgs = match self.subcommand {
+            TxSubcmd::Batch {
+                file,
+            } => batch::load(&client, &file).await?,
+
+            subcmd => vec![subcmd.into_msg()?],
+        };
+
+        // load sender key. when only generating the tx body, the sender may be
+        // given as an address instead, as its key may not be on this machine
+        let from = self.from.as_deref().ok_or_else(|| DaemonError::missing_argument("--from"))?;
+        let keyring = Keyring::new(home_dir, opts.keyring_backend, opts.password_fd)?;
+        let (key, sender_addr) = if self.generate_only && !keyring.filename(from).exists() {
+            (None, address::validate(from)?)
+        } else {
+            let key = keyring.get(from)?;
+            let sender_addr = key.address()?;
+            (Some(key), sender_addr)
+        };
+
         // find chain id
         let chain_id = self.chain_id.as_ref().unwrap_or(&client_cfg.chain_id);
 
         // query the sender's sequence number if not provided
         let sequence = match self.sequence {
-            None => {
-                let result = do_abci_query::<_, AccountResponse>(
-                    &client,
-                    SdkQuery::Account {
-                        address: sender_addr.to_string(),
-                    },
-                )
-                .await;
-
-                let sequence = match result {
-                    // if the account exists and is a base account, we take the
-                    // sequence number
-                    Ok(AccountResponse {
-                        account: Account::Base {
-                            sequence,
-                            ..
-                        },
-                        ..
-                    }) => sequence,
-
-                    // if the account exists but is a contract, we throw error
-                    // because contracts can't sign txs
-                    Ok(AccountResponse {
-                        account: Account::Contract {
-                            ..
-                        },
-                        ..
-                    }) => return Err(DaemonError::sender_is_contract(&sender_addr)),
-
-                    // if query results in an error, and the error is that the
-                    // account is not found, we use zero.
-                    // the first tx ever to be submitted should have the
-                    // sequence of 1.
-                    //
-                    // TODO: instead of string matching, we should establish a
-                    // standardized list of error codes and match the code instead
-                    Err(DaemonError::QueryFailed {
-                        err,
-                    }) if err.contains(&format!("{} not found", type_name::<Account<Addr>>())) => {
-                        warn!(
-                            "Account with address {} not found on chain. Use default sequence number of 1",
-                            &sender_addr,
-                        );
-                        0
-                    },
+            None => query_sequence(&client, &sender_addr).await?,
+            Some(sequence) => sequence,
+        };
 
-                    // for other errors, we cannot handle them here, so we throw
-                    Err(err) => return Err(err),
-                };
+        // load the fee payer's key and query its sequence number, if the fee
+        // payer is to co-sign the tx
+        let fee_payer_key = self.fee_payer_key.as_ref().map(|name| keyring.get(name)).transpose()?;
+        let (fee_payer, fee_payer_sequence) = match &fee_payer_key {
+            Some(fee_payer_key) => {
+                let fee_payer_addr = fee_payer_key.address()?;
+                let fee_payer_sequence = query_sequence(&client, &fee_payer_addr).await?;
+                (Some(fee_payer_addr.into()), Some(fee_payer_sequence))
+            },
+            None => (self.fee_payer, None),
+        };
+
+        // wrap the messages in an exec message if they're to be executed on
+        // behalf of another account
+        let msgs = match self.granter {
+            Some(granter) => vec![SdkMsg::Exec {
+                granter,
+                msgs,
+            }],
+            None => msgs,
+        };
+
+        let mut body = TxBody {
+            sender: sender_addr.into(),
+            msgs,
+            chain_id: chain_id.into(),
+            sequence,
+            timeout_height: self.timeout_height,
+            timeout_timestamp: self.timeout,
+            fee: self.fees.as_deref().map(parse_coins).transpose()?.unwrap_or_default(),
+            fee_payer,
+            fee_payer_sequence,
+            gas_limit: None,
+        };
 
-                // needs to be 1 greater than the on-chain sequence
-                sequence + 1
+        body.gas_limit = match self.gas {
+            None => None,
+            Some(Gas::Limit(gas_limit)) => Some(gas_limit),
+            Some(Gas::Auto) => {
+                // simulate the tx without signing it; the signatures are not
+                // verified, but the pubkeys are needed in case the accounts
+                // haven't signed any tx before
+                let tx = Tx {
+                    body: body.clone(),
+                    pubkey: key.as_ref().map(|k| k.pubkey()),
+                    signature: Binary::default(),
+                    fee_payer_pubkey: fee_payer_key.as_ref().map(|k| k.pubkey()),
+                    fee_payer_signature: fee_payer_key.as_ref().map(|_| Binary::default()),
+                };
+                let res = simulate(&client, tx, true).await?;
+                let gas_limit = (res.gas_used as f64 * self.gas_adjustment).ceil() as u64;
+                if !self.generate_only {
+                    print::banner(
+                        self.output,
+                        format!("{} {gas_limit}", "⛽ Estimated gas limit:".bold()),
+                    );
+                }
+                Some(gas_limit)
             },
-            Some(sequence) => sequence,
         };
 
-        let msg = match self.subcommand {
+        // print nothing but the tx body, so that it can be redirected to a file
+        let key = match key {
+            Some(key) if !self.generate_only => key,
+            _ => return print::json(body),
+        };
+
+        let mut tx = key.sign_tx(&body)?;
+        if let Some(fee_payer_key) = &fee_payer_key {
+            fee_payer_key.co_sign_tx_as_fee_payer(&mut tx)?;
+        }
+        let tx_bytes = serde_json::to_vec(&tx)?;
+
+        if self.output == OutputFormat::Text {
+            println!("{}", "🤖 Transaction signed:".bold());
+            print::json(&tx)?;
+        }
+
+        if self.simulate {
+            let res = simulate(&client, tx, false).await?;
+            print::banner(self.output, "🔮 Transaction simulated:".bold());
+            return print::json(res);
+        }
+
+        if opts.yes || prompt::confirm(format!("{}", "🤔 Broadcast?".bold()))? {
+            broadcast(&client, tx_bytes, self.broadcast_mode, self.output).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Sign a tx body offline, and print the signed tx.
+    fn sign(&self, home_dir: &Path, opts: &GlobalOpts, file: &Path) -> Result<(), DaemonError> {
+        let from = self.from.as_deref().ok_or_else(|| DaemonError::missing_argument("--from"))?;
+        let sequence = self.sequence.ok_or_else(|| DaemonError::missing_argument("--sequence"))?;
+        let chain_id =
+            self.chain_id.clone().ok_or_else(|| DaemonError::missing_argument("--chain-id"))?;
+
+        if !file.exists() {
+            return Err(DaemonError::file_not_found(file)?);
+        }
+        let mut body: TxBody = serde_json::from_slice(&fs::read(file)?)?;
+
+        let keyring = Keyring::new(home_dir, opts.keyring_backend, opts.password_fd)?;
+        let key = keyring.get(from)?;
+
+        // the sequence and chain id are part of the sign doc; they are given
+        // explicitly, so that the signer doesn't sign values it hasn't checked
+        let sender_addr = key.address()?;
+        if body.sender != sender_addr.as_str() {
+            return Err(DaemonError::sender_mismatch(&body.sender, sender_addr));
+        }
+        body.sequence = sequence;
+        body.chain_id = chain_id;
+
+        print::json(key.sign_tx(&body)?)
+    }
+
+    /// Broadcast a tx signed offline.
+    async fn broadcast(
+        &self,
+        home_dir: &Path,
+        opts: &GlobalOpts,
+        file: &Path,
+    ) -> Result<(), DaemonError> {
+        if !file.exists() {
+            return Err(DaemonError::file_not_found(file)?);
+        }
+        let tx: Tx = serde_json::from_slice(&fs::read(file)?)?;
+
+        let client_cfg = ClientConfig::load(home_dir)?;
+        let client = create_http_client(self.node.as_ref(), &client_cfg)?;
+
+        if self.output == OutputFormat::Text {
+            println!("{}", "🤖 Transaction loaded:".bold());
+            print::json(&tx)?;
+        }
+
+        if opts.yes || prompt::confirm(format!("{}", "🤔 Broadcast?".bold()))? {
+            let tx_bytes = serde_json::to_vec(&tx)?;
+            broadcast(&client, tx_bytes, self.broadcast_mode, self.output).await?;
+        }
+
+        Ok(())
+    }
+}
+
+impl TxSubcmd {
+    /// Build the message of a subcommand that creates a single message.
+    fn into_msg(self) -> Result<SdkMsg, DaemonError> {
+        let msg = match self {
             TxSubcmd::Store {
                 wasm_byte_code_path,
             } => {
@@ -215,27 +526,275 @@ impl TxCmd {
                 code_id,
                 msg: serde_json::from_str(&msg)?,
             },
-        };
 
-        let body = TxBody {
-            sender: sender_addr.into(),
-            msgs: vec![msg],
-            chain_id: chain_id.into(),
-            sequence,
-        };
+            TxSubcmd::FeeGrant {
+                grantee,
+                spend_limit,
+                expiration,
+            } => SdkMsg::GrantFeeAllowance {
+                grantee,
+                allowance: FeeAllowance {
+                    spend_limit: spend_limit.as_deref().map(parse_coins).transpose()?,
+                    expiration,
+                },
+            },
 
-        let tx = key.sign_tx(&body)?;
-        let tx_bytes = serde_json::to_vec(&tx)?;
+            TxSubcmd::FeeRevoke {
+                grantee,
+            } => SdkMsg::RevokeFeeAllowance {
+                grantee,
+            },
+
+            TxSubcmd::Grant {
+                grantee,
+                msg_type,
+                contract,
+                expiration,
+            } => {
+                // clap ensures exactly one of the two is provided
+                let authorization = match (msg_type, contract) {
+                    (Some(msg_type), _) => Authorization::MsgType {
+                        msg_type,
+                    },
+                    (None, Some(contract)) => Authorization::Contract {
+                        contract,
+                    },
+                    (None, None) => unreachable!(),
+                };
+                SdkMsg::Grant {
+                    grantee,
+                    authorization,
+                    expiration,
+                }
+            },
+
+            TxSubcmd::Revoke {
+                grantee,
+            } => SdkMsg::Revoke {
+                grantee,
+            },
+
+            TxSubcmd::ScheduleUpgrade {
+                name,
+                height,
+                info,
+            } => SdkMsg::ScheduleUpgrade {
+                name,
+                height,
+                info,
+            },
+
+            TxSubcmd::Sign {
+                ..
+            }
+            | TxSubcmd::Batch {
+                ..
+            }
+            | TxSubcmd::Broadcast {
+                ..
+            } => unreachable!("handled by TxCmd::run"),
+        };
 
-        println!("{}", "🤖 Transaction signed:".bold());
-        print::json(&tx)?;
+        Ok(msg)
+    }
+}
 
-        if prompt::confirm(format!("{}", "🤔 Broadcast?".bold()))? {
+/// Broadcast a signed tx and print the result. Return error if the tx fails
+/// CheckTx or, in the modes that wait for it to be included, DeliverTx.
+async fn broadcast(
+    client: &HttpClient,
+    tx_bytes: Vec<u8>,
+    mode: BroadcastMode,
+    output: OutputFormat,
+) -> Result<(), DaemonError> {
+    match mode {
+        BroadcastMode::Async => {
             let response = client.broadcast_tx_async(tx_bytes).await?;
             print::json(response)?;
-            println!("{}", "🙌 Successfully broadcasted!".bold());
+            print::banner(output, "🙌 Successfully broadcasted!".bold());
+        },
+
+        BroadcastMode::Sync => {
+            let response = client.broadcast_tx_sync(tx_bytes).await?;
+            print::json(&response)?;
+            if response.code.is_err() {
+                return Err(DaemonError::tx_failed(response.code.value(), response.log));
+            }
+            print::banner(output, "🙌 Transaction passed CheckTx!".bold());
+        },
+
+        BroadcastMode::Commit => {
+            let response = client.broadcast_tx_commit(tx_bytes).await?;
+            if response.check_tx.code.is_err() {
+                print::json(&response)?;
+                return Err(DaemonError::tx_failed(
+                    response.check_tx.code.value(),
+                    response.check_tx.log,
+                ));
+            }
+            print_tx_result(response.hash, response.height.value(), response.deliver_tx, output)?;
+        },
+
+        BroadcastMode::Wait => {
+            let response = client.broadcast_tx_sync(tx_bytes).await?;
+            if response.code.is_err() {
+                print::json(&response)?;
+                return Err(DaemonError::tx_failed(response.code.value(), response.log));
+            }
+            let msg = "⏳ Waiting for transaction to be included:".bold();
+            print::banner(output, format!("{msg} {}", response.hash));
+            let response = wait_for_tx(client, response.hash).await?;
+            print_tx_result(response.hash, response.height.value(), response.tx_result, output)?;
+        },
+    }
+
+    Ok(())
+}
+
+/// Poll the node until the tx with the given hash is included in a block.
+async fn wait_for_tx(
+    client: &HttpClient,
+    hash: Hash,
+) -> Result<tendermint_rpc::endpoint::tx::Response, DaemonError> {
+    for _ in 0..POLL_ATTEMPTS {
+        tokio::time::sleep(POLL_INTERVAL).await;
+        // the node returns error if the tx isn't found, which is the case until
+        // the tx is included in a block
+        if let Ok(response) = client.tx(hash, false).await {
+            return Ok(response);
         }
+    }
 
-        Ok(())
+    Err(DaemonError::tx_not_included(hash.to_string()))
+}
+
+#[derive(Serialize)]
+struct PrintableTxResult {
+    hash: String,
+    height: u64,
+    code: u32,
+    log: String,
+    gas_wanted: i64,
+    gas_used: i64,
+    events: Vec<PrintableEvent>,
+}
+
+#[derive(Serialize)]
+struct PrintableEvent {
+    #[serde(rename = "type")]
+    ty: String,
+    attributes: Vec<(String, String)>,
+}
+
+/// Print the result of an included tx, with its events decoded. Return error if
+/// the tx failed DeliverTx.
+fn print_tx_result(
+    hash: Hash,
+    height: u64,
+    result: abci::response::DeliverTx,
+    output: OutputFormat,
+) -> Result<(), DaemonError> {
+    let code = result.code.value();
+    let failed = result.code.is_err();
+
+    print::json(PrintableTxResult {
+        hash: hash.to_string(),
+        height,
+        code,
+        log: result.log.clone(),
+        gas_wanted: result.gas_wanted,
+        gas_used: result.gas_used,
+        events: result
+            .events
+            .into_iter()
+            .map(|event| PrintableEvent {
+                ty: event.kind,
+                attributes: event
+                    .attributes
+                    .into_iter()
+                    .map(|attr| (attr.key, attr.value))
+                    .collect(),
+            })
+            .collect(),
+    })?;
+
+    if failed {
+        return Err(DaemonError::tx_failed(code, result.log));
     }
+
+    print::banner(output, "🙌 Transaction included!".bold());
+
+    Ok(())
+}
+
+/// Execute a tx against the latest committed state without broadcasting it
+async fn simulate(
+    client: &HttpClient,
+    tx: Tx,
+    skip_signature: bool,
+) -> Result<SimulateResponse, DaemonError> {
+    do_abci_query(
+        client,
+        SdkQuery::Simulate {
+            tx,
+            skip_signature,
+        },
+    )
+    .await
+}
+
+/// Query an account's sequence number, and return the sequence number that the
+/// account's next tx should use, i.e. one greater than the on-chain value.
+async fn query_sequence(client: &HttpClient, address: &Addr) -> Result<u64, DaemonError> {
+    let result = do_abci_query::<_, AccountResponse>(
+        client,
+        SdkQuery::Account {
+            address: address.to_string(),
+        },
+    )
+    .await;
+
+    let sequence = match result {
+        // if the account exists and is a base account, we take the
+        // sequence number
+        Ok(AccountResponse {
+            account: Account::Base {
+                sequence,
+                ..
+            },
+            ..
+        }) => sequence,
+
+        // if the account exists but is a contract, we throw error
+        // because contracts can't sign txs
+        Ok(AccountResponse {
+            account: Account::Contract {
+                ..
+            },
+            ..
+        }) => return Err(DaemonError::sender_is_contract(address)),
+
+        // if query results in an error, and the error is that the
+        // account is not found, we use zero.
+        // the first tx ever to be submitted should have the
+        // sequence of 1.
+        //
+        // TODO: instead of string matching, we should establish a
+        // standardized list of error codes and match the code instead
+        Err(DaemonError::QueryFailed {
+            err,
+        }) if err.contains(&format!("{} not found", type_name::<Account<Addr>>())) => {
+            warn!(
+                "Account with address {} not found on chain. Use default sequence number of 1",
+                address,
+            );
+            0
+        },
+
+        // for other errors, we cannot handle them here, so we throw
+        Err(err) => return Err(err),
+    };
+
+    // needs to be 1 greater than the on-chain sequence
+    Ok(sequence + 1)
 }
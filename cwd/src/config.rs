@@ -9,6 +9,31 @@ use crate::DaemonError;
 pub struct AppConfig {
     /// Address to listen for ABCI requests
     pub listen_addr: String,
+    /// Take a snapshot of the state every this many blocks, to be served to
+    /// nodes that are state syncing. 0 disables snapshots
+    #[serde(default)]
+    pub snapshot_interval: u64,
+    /// Number of most recent snapshots to keep on disk
+    #[serde(default = "default_snapshot_keep_recent")]
+    pub snapshot_keep_recent: u32,
+    /// Event attributes to be indexed by Tendermint, in the format
+    /// `{event_type}.{attribute_key}`, e.g. `message.sender`. If empty, all
+    /// attributes are indexed
+    #[serde(default)]
+    pub index_events: Vec<String>,
+    /// Address to serve Prometheus metrics at, e.g. 127.0.0.1:26660. Metrics
+    /// are not served if not provided
+    #[serde(default)]
+    pub metrics_listen_addr: Option<String>,
+    /// Address to serve the JSON REST API at, e.g. 127.0.0.1:1317. The API is
+    /// not served if not provided
+    #[serde(default)]
+    pub rest_listen_addr: Option<String>,
+    /// Refresh the state that the REST API serves queries from every this many
+    /// blocks. Each refresh creates a checkpoint of the store, so queries may
+    /// lag behind the chain by up to this many blocks
+    #[serde(default = "default_rest_view_interval")]
+    pub rest_view_interval: u64,
 }
 
 impl Default for AppConfig {
@@ -16,10 +41,24 @@ impl Default for AppConfig {
         Self {
             // including the `tcp://` prefix causes an error...?
             listen_addr: "127.0.0.1:26658".into(),
+            snapshot_interval: 0,
+            snapshot_keep_recent: default_snapshot_keep_recent(),
+            index_events: vec![],
+            metrics_listen_addr: None,
+            rest_listen_addr: None,
+            rest_view_interval: default_rest_view_interval(),
         }
     }
 }
 
+fn default_snapshot_keep_recent() -> u32 {
+    2
+}
+
+fn default_rest_view_interval() -> u64 {
+    10
+}
+
 impl AppConfig {
     pub fn load(home_dir: &Path) -> Result<Self, DaemonError> {
         let cfg_path = home_dir.join("config/app.toml");
@@ -9,6 +9,9 @@ pub enum DaemonError {
     #[error(transparent)]
     Address(#[from] cw_sdk::address::AddressError),
 
+    #[error(transparent)]
+    Bank(#[from] cw_bank::error::ContractError),
+
     #[error(transparent)]
     BCrypt(#[from] bcrypt::BcryptError),
 
@@ -36,6 +39,18 @@ pub enum DaemonError {
     #[error(transparent)]
     Merk(#[from] cw_store::MerkError),
 
+    #[error(transparent)]
+    SerdeYaml(#[from] serde_yaml::Error),
+
+    #[error(transparent)]
+    SignDoc(#[from] cw_sdk::tx::SignDocError),
+
+    #[error(transparent)]
+    StateMachine(#[from] cw_state_machine::error::Error),
+
+    #[error(transparent)]
+    Std(#[from] cosmwasm_std::StdError),
+
     #[error(transparent)]
     Tendermint(#[from] tendermint::Error),
 
@@ -57,9 +72,26 @@ pub enum DaemonError {
     #[error("failed to stringify path")]
     PathFailed,
 
+    #[error("instantiate message of the `bank` contract not found in genesis state")]
+    BankNotFound,
+
     #[error("password is incorrect")]
     IncorrectPassword,
 
+    #[error("passphrase must not be empty")]
+    EmptyPassphrase,
+
+    #[error("state at height {height} is unavailable; the last committed height is {last_height}")]
+    HeightUnavailable {
+        height: u64,
+        last_height: u64,
+    },
+
+    #[error("genesis file already has an app state: {filename}; use --overwrite to replace it")]
+    AppStateExists {
+        filename: String,
+    },
+
     #[error("file already exists: {filename}")]
     FileExists {
         filename: String,
@@ -70,6 +102,22 @@ pub enum DaemonError {
         filename: String,
     },
 
+    #[error("invalid batch file: {reason}")]
+    InvalidBatch {
+        reason: String,
+    },
+
+    #[error("failed to read password from file descriptor {fd}: {reason}")]
+    InvalidPasswordFd {
+        fd: i32,
+        reason: String,
+    },
+
+    #[error("malformed armored key: {reason}")]
+    MalformedArmor {
+        reason: String,
+    },
+
     #[error("failed to cast JWT payload to key: {reason}")]
     MalformedPayload {
         reason: String,
@@ -80,11 +128,33 @@ pub enum DaemonError {
         err: String,
     },
 
+    #[error("missing required argument: {arg}")]
+    MissingArgument {
+        arg: String,
+    },
+
+    #[error("tx sender {sender} does not match the signing key's address {address}")]
+    SenderMismatch {
+        sender: String,
+        address: String,
+    },
+
     #[error("tx sender {address} is a contract account")]
     SenderIsContract {
         address: String,
     },
 
+    #[error("tx failed with code {code}: {log}")]
+    TxFailed {
+        code: u32,
+        log: String,
+    },
+
+    #[error("tx {hash} was not included in a block in time")]
+    TxNotIncluded {
+        hash: String,
+    },
+
     #[error("feature is not supported yet: {feature}")]
     UnsupportedFeature {
         feature: String,
@@ -92,6 +162,12 @@ pub enum DaemonError {
 }
 
 impl DaemonError {
+    pub fn app_state_exists(filename: &Path) -> Result<Self, Self> {
+        Ok(Self::AppStateExists {
+            filename: path::stringify(filename)?,
+        })
+    }
+
     pub fn file_exists(filename: &Path) -> Result<Self, Self> {
         Ok(Self::FileExists {
             filename: path::stringify(filename)?,
@@ -104,12 +180,51 @@ impl DaemonError {
         })
     }
 
+    pub fn height_unavailable(height: u64, last_height: u64) -> Self {
+        Self::HeightUnavailable {
+            height,
+            last_height,
+        }
+    }
+
+    pub fn invalid_batch(reason: impl Into<String>) -> Self {
+        Self::InvalidBatch {
+            reason: reason.into(),
+        }
+    }
+
+    pub fn invalid_password_fd(fd: i32, reason: impl ToString) -> Self {
+        Self::InvalidPasswordFd {
+            fd,
+            reason: reason.to_string(),
+        }
+    }
+
+    pub fn malformed_armor(reason: impl Into<String>) -> Self {
+        Self::MalformedArmor {
+            reason: reason.into(),
+        }
+    }
+
     pub fn malformed_payload(reason: impl Into<String>) -> Self {
         Self::MalformedPayload {
             reason: reason.into(),
         }
     }
 
+    pub fn missing_argument(arg: impl Into<String>) -> Self {
+        Self::MissingArgument {
+            arg: arg.into(),
+        }
+    }
+
+    pub fn sender_mismatch(sender: impl Into<String>, address: impl Into<String>) -> Self {
+        Self::SenderMismatch {
+            sender: sender.into(),
+            address: address.into(),
+        }
+    }
+
     pub fn sender_is_contract(address: impl Into<String>) -> Self {
         Self::SenderIsContract {
             address: address.into(),
@@ -122,6 +237,19 @@ impl DaemonError {
         }
     }
 
+    pub fn tx_failed(code: u32, log: impl Into<String>) -> Self {
+        Self::TxFailed {
+            code,
+            log: log.into(),
+        }
+    }
+
+    pub fn tx_not_included(hash: impl Into<String>) -> Self {
+        Self::TxNotIncluded {
+            hash: hash.into(),
+        }
+    }
+
     pub fn unsupported_feature(feature: impl Into<String>) -> Self {
         Self::UnsupportedFeature {
             feature: feature.into(),
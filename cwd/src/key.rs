@@ -1,11 +1,56 @@
 use bip32::{Mnemonic, XPrv};
+use clap::ValueEnum;
 use cosmwasm_std::Addr;
 use josekit::jwt::JwtPayload;
-use k256::ecdsa::{signature::Signer, Signature, SigningKey, VerifyingKey};
 
-use cw_sdk::{address, Tx, TxBody};
+use cw_sdk::{address, OffchainSignDoc, PublicKey, SignDoc, Tx, TxBody};
 
-use crate::DaemonError;
+use crate::{slip10, DaemonError};
+
+/// The signature schemes supported by cw-sdk
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Algorithm {
+    Secp256k1,
+    Ed25519,
+    Secp256r1,
+}
+
+impl Algorithm {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Algorithm::Secp256k1 => "secp256k1",
+            Algorithm::Ed25519 => "ed25519",
+            Algorithm::Secp256r1 => "secp256r1",
+        }
+    }
+
+    /// Tag a raw public key with this signature scheme
+    pub fn pubkey(&self, bytes: Vec<u8>) -> PublicKey {
+        match self {
+            Algorithm::Secp256k1 => PublicKey::Secp256k1(bytes.into()),
+            Algorithm::Ed25519 => PublicKey::Ed25519(bytes.into()),
+            Algorithm::Secp256r1 => PublicKey::Secp256r1(bytes.into()),
+        }
+    }
+}
+
+impl TryFrom<&str> for Algorithm {
+    type Error = DaemonError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        <Self as ValueEnum>::from_str(s, false)
+            .map_err(|_| DaemonError::malformed_payload(format!("unknown algorithm `{s}`")))
+    }
+}
+
+/// A private key of one of the supported signature schemes.
+#[derive(Debug, Clone)]
+enum PrivateKey {
+    Secp256k1(k256::ecdsa::SigningKey),
+    /// ed25519-dalek's secret key type is not `Clone`, so we keep the raw bytes instead
+    Ed25519([u8; 32]),
+    Secp256r1(p256::ecdsa::SigningKey),
+}
 
 /// Represents a private key that is to be saved in the keyring.
 ///
@@ -17,81 +62,182 @@ pub struct Key {
     /// The key's name
     pub name: String,
     /// The private key
-    sk: SigningKey,
+    sk: PrivateKey,
 }
 
 impl Key {
-    /// Create a new key instance from a given name and BIP-32 mnemonic phrase
+    /// Create a new key instance from a given name and BIP-32 mnemonic phrase.
+    ///
+    /// secp256k1 keys are derived with BIP-32 at `m/44'/{coin_type}'/0'/0/0`. ed25519 and
+    /// secp256r1 keys are derived with SLIP-10; as ed25519 only supports hardened derivation, its
+    /// path is `m/44'/{coin_type}'/0'/0'/0'`.
     pub fn from_mnemonic(
         name: impl Into<String>,
         mnemonic: &Mnemonic,
         coin_type: u32,
+        algo: Algorithm,
     ) -> Result<Self, DaemonError> {
         // The `to_seed` function takes a password to generate salt. Here we just use an empty str.
         // For reference, both Terra Station and Keplr use an empty string as well:
         // - https://github.com/terra-money/terra.js/blob/v3.1.7/src/key/MnemonicKey.ts#L79
         // - https://github.com/chainapsis/keplr-wallet/blob/b6062a4d24f3dcb15dda063b1ece7d1fbffdbfc8/packages/crypto/src/mnemonic.ts#L63
         let seed = mnemonic.to_seed("");
-        let path = format!("m/44'/{coin_type}'/0'/0/0");
-        let xprv = XPrv::derive_from_path(&seed, &path.parse()?)?;
-        Ok(Self {
-            name: name.into(),
-            sk: xprv.into(),
-        })
+        if coin_type >= slip10::HARDENED {
+            return Err(DaemonError::malformed_payload(format!("invalid coin type {coin_type}")));
+        }
+        let sk_bytes = match algo {
+            Algorithm::Secp256k1 => {
+                let path = format!("m/44'/{coin_type}'/0'/0/0");
+                let xprv = XPrv::derive_from_path(&seed, &path.parse()?)?;
+                xprv.private_key().to_bytes().to_vec()
+            },
+            Algorithm::Ed25519 => {
+                let path = [44, coin_type, 0, 0, 0].map(|index| index + slip10::HARDENED);
+                slip10::derive_ed25519(seed.as_bytes(), &path).to_vec()
+            },
+            Algorithm::Secp256r1 => {
+                let hardened = |index: u32| index + slip10::HARDENED;
+                let path = [hardened(44), hardened(coin_type), hardened(0), 0, 0];
+                slip10::derive_nist256p1(seed.as_bytes(), &path).to_vec()
+            },
+        };
+        Self::from_privkey_bytes(name, algo, &sk_bytes)
     }
 
-    /// Create a new key instance from a given name and private key bytes
+    /// Create a new key instance from a given name, signature scheme, and private key bytes
     pub fn from_privkey_bytes(
         name: impl Into<String>,
+        algo: Algorithm,
         sk_bytes: &[u8],
     ) -> Result<Self, DaemonError> {
-        let sk = SigningKey::from_bytes(sk_bytes)?;
+        let sk = match algo {
+            Algorithm::Secp256k1 => {
+                PrivateKey::Secp256k1(k256::ecdsa::SigningKey::from_bytes(sk_bytes)?)
+            },
+            Algorithm::Ed25519 => {
+                // make sure the bytes are a valid ed25519 secret key
+                ed25519_dalek::SecretKey::from_bytes(sk_bytes)?;
+                let mut bytes = [0u8; 32];
+                bytes.copy_from_slice(sk_bytes);
+                PrivateKey::Ed25519(bytes)
+            },
+            Algorithm::Secp256r1 => {
+                PrivateKey::Secp256r1(p256::ecdsa::SigningKey::from_bytes(sk_bytes)?)
+            },
+        };
         Ok(Self {
             name: name.into(),
             sk,
         })
     }
 
-    /// Return a reference to the private key
-    pub fn privkey(&self) -> &SigningKey {
-        &self.sk
+    /// Return the signature scheme of the key
+    pub fn algo(&self) -> Algorithm {
+        match &self.sk {
+            PrivateKey::Secp256k1(_) => Algorithm::Secp256k1,
+            PrivateKey::Ed25519(_) => Algorithm::Ed25519,
+            PrivateKey::Secp256r1(_) => Algorithm::Secp256r1,
+        }
     }
 
-    /// Return the pubkey
-    pub fn pubkey(&self) -> VerifyingKey {
-        self.sk.verifying_key()
+    /// Return the raw private key bytes
+    pub fn privkey_bytes(&self) -> Vec<u8> {
+        match &self.sk {
+            PrivateKey::Secp256k1(sk) => sk.to_bytes().to_vec(),
+            PrivateKey::Ed25519(bytes) => bytes.to_vec(),
+            PrivateKey::Secp256r1(sk) => sk.to_bytes().to_vec(),
+        }
+    }
+
+    /// Return the pubkey, tagged by its signature scheme.
+    /// The secp256k1 and secp256r1 pubkeys are in the compressed SEC1 encoding
+    pub fn pubkey(&self) -> PublicKey {
+        let bytes = match &self.sk {
+            PrivateKey::Secp256k1(sk) => sk.verifying_key().to_bytes().to_vec(),
+            PrivateKey::Ed25519(bytes) => ed25519_keypair(bytes).public.to_bytes().to_vec(),
+            PrivateKey::Secp256r1(sk) => {
+                sk.verifying_key().to_encoded_point(true).as_bytes().to_vec()
+            },
+        };
+        self.algo().pubkey(bytes)
     }
 
     /// Return the key's address bytes, generated according to
     /// [ADR-028](https://docs.cosmos.network/v0.45/architecture/adr-028-public-key-addresses.html)
     pub fn address(&self) -> Result<Addr, address::AddressError> {
-        address::derive_from_pubkey(self.pubkey().to_bytes().as_slice())
+        address::derive_from_pubkey(&self.pubkey())
     }
 
-    /// Sign an arbitrary byte array. The bytes are SHA-256 hashed before signing
-    pub fn sign_bytes(&self, bytes: &[u8]) -> Signature {
-        self.sk.sign(bytes)
+    /// Sign an arbitrary byte array. For secp256k1 and secp256r1, the bytes are SHA-256 hashed
+    /// before signing; for ed25519, the raw bytes are signed.
+    pub fn sign_bytes(&self, bytes: &[u8]) -> Vec<u8> {
+        match &self.sk {
+            PrivateKey::Secp256k1(sk) => {
+                use k256::ecdsa::{signature::Signer, Signature};
+                let signature: Signature = sk.sign(bytes);
+                signature.to_vec()
+            },
+            PrivateKey::Ed25519(sk_bytes) => {
+                use ed25519_dalek::Signer;
+                ed25519_keypair(sk_bytes).sign(bytes).to_bytes().to_vec()
+            },
+            PrivateKey::Secp256r1(sk) => {
+                use p256::ecdsa::{signature::Signer, Signature};
+                let signature: Signature = sk.sign(bytes);
+                signature.to_vec()
+            },
+        }
     }
 
     /// Sign a tx body, returns the full tx.
     pub fn sign_tx(&self, body: &TxBody) -> Result<Tx, DaemonError> {
-        let body_bytes = serde_json::to_vec(body)?;
-        let signature = self.sign_bytes(&body_bytes);
+        let sign_bytes = SignDoc::new(body.clone()).sign_bytes()?;
+        let signature = self.sign_bytes(&sign_bytes);
         Ok(Tx {
             body: body.clone(),
-            pubkey: Some(self.pubkey().to_bytes().to_vec().into()),
-            signature: signature.to_vec().into(),
+            pubkey: Some(self.pubkey()),
+            signature: signature.into(),
+            fee_payer_pubkey: None,
+            fee_payer_signature: None,
         })
     }
+
+    /// Sign arbitrary data off-chain, to prove the ownership of the key's address. The data is
+    /// wrapped in an ADR-036 sign doc, so that the signature can't be used as that of a tx.
+    pub fn sign_offchain(&self, data: &[u8]) -> Result<Vec<u8>, DaemonError> {
+        let sign_bytes = OffchainSignDoc::new(self.address()?, data.to_vec()).sign_bytes()?;
+        Ok(self.sign_bytes(&sign_bytes))
+    }
+
+    /// Co-sign a tx that has been signed by the sender, as the fee payer.
+    pub fn co_sign_tx_as_fee_payer(&self, tx: &mut Tx) -> Result<(), DaemonError> {
+        let sign_bytes = SignDoc::new(tx.body.clone()).sign_bytes()?;
+        tx.fee_payer_pubkey = Some(self.pubkey());
+        tx.fee_payer_signature = Some(self.sign_bytes(&sign_bytes).into());
+        Ok(())
+    }
+}
+
+/// Expand the ed25519 secret key bytes into a key pair.
+/// The bytes have already been validated upon creation of the key, so this never fails.
+fn ed25519_keypair(sk_bytes: &[u8; 32]) -> ed25519_dalek::Keypair {
+    let secret = ed25519_dalek::SecretKey::from_bytes(sk_bytes)
+        .expect("[cwd]: invalid ed25519 secret key");
+    let public = ed25519_dalek::PublicKey::from(&secret);
+    ed25519_dalek::Keypair {
+        secret,
+        public,
+    }
 }
 
 impl TryFrom<Key> for JwtPayload {
     type Error = josekit::JoseError;
 
     fn try_from(key: Key) -> Result<Self, Self::Error> {
-        let sk_str = hex::encode(key.sk.to_bytes().as_slice());
+        let sk_str = hex::encode(key.privkey_bytes());
         let mut payload = JwtPayload::new();
         payload.set_claim("name", Some(key.name.into()))?;
+        payload.set_claim("algo", Some(key.algo().as_str().into()))?;
         payload.set_claim("sk", Some(sk_str.into()))?;
         Ok(payload)
     }
@@ -112,7 +258,17 @@ impl TryFrom<JwtPayload> for Key {
             .as_str()
             .ok_or_else(|| DaemonError::malformed_payload("incorrect JSON value type for `sk`"))?;
 
+        // keys created before multiple signature schemes were supported don't have the `algo`
+        // claim; they are all secp256k1
+        let algo = match payload.claim("algo") {
+            Some(value) => value
+                .as_str()
+                .ok_or_else(|| DaemonError::malformed_payload("incorrect JSON value type for `algo`"))?
+                .try_into()?,
+            None => Algorithm::Secp256k1,
+        };
+
         let sk_bytes = hex::decode(sk_str)?;
-        Key::from_privkey_bytes(name, &sk_bytes)
+        Key::from_privkey_bytes(name, algo, &sk_bytes)
     }
 }
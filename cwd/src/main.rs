@@ -1,3 +1,4 @@
+mod batch;
 mod client;
 mod commands;
 mod config;
@@ -7,21 +8,23 @@ mod keyring;
 mod path;
 mod print;
 mod prompt;
+mod slip10;
 
-use std::path::PathBuf;
+use std::{os::unix::io::RawFd, path::PathBuf};
 
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand};
 use tracing::error;
 use tracing_subscriber::filter::LevelFilter;
 
 use crate::{
     commands::{
-        DebugCmd, GenesisCmd, InitCmd, KeysCmd, QueryCmd, ResetCmd, StartCmd, TendermintCmd, TxCmd,
+        DebugCmd, ExportCmd, GenesisCmd, InitCmd, KeysCmd, QueryCmd, ResetCmd, StartCmd,
+        TendermintCmd, TxCmd,
     },
     config::{AppConfig, ClientConfig},
     error::DaemonError,
     key::Key,
-    keyring::Keyring,
+    keyring::{Keyring, KeyringBackend},
 };
 
 #[derive(Parser)]
@@ -37,6 +40,27 @@ pub struct Cli {
     /// Increase output logging verbosity to DEBUG level
     #[arg(long, default_value_t = false, action = clap::ArgAction::SetTrue)]
     pub debug: bool,
+
+    #[command(flatten)]
+    pub opts: GlobalOpts,
+}
+
+/// Options for running commands non-interactively, e.g. in scripts
+#[derive(Args)]
+pub struct GlobalOpts {
+    /// Skip confirmation prompts, e.g. before broadcasting a transaction
+    #[arg(long, short, global = true)]
+    pub yes: bool,
+
+    /// Where and how the keys are stored
+    #[arg(long, global = true, value_enum, default_value_t = KeyringBackend::File)]
+    pub keyring_backend: KeyringBackend,
+
+    /// Read the keyring password from this file descriptor instead of prompting for it. It's also
+    /// used as the passphrase of keys being exported or imported. The password can also be
+    /// provided by the CWD_KEYRING_PASSWORD environment variable
+    #[arg(long, global = true)]
+    pub password_fd: Option<RawFd>,
 }
 
 #[derive(Subcommand)]
@@ -44,6 +68,9 @@ pub enum Command {
     /// Helper command useful for developers
     Debug(DebugCmd),
 
+    /// Export the application state as a genesis state
+    Export(ExportCmd),
+
     /// Utilities for preparing the genesis state
     Genesis(GenesisCmd),
 
@@ -85,17 +112,19 @@ async fn run() -> Result<(), DaemonError> {
     } else {
         LevelFilter::INFO
     };
-    tracing_subscriber::fmt().with_max_level(log_level).init();
+    // logs go to stderr, so that the output of commands can be piped
+    tracing_subscriber::fmt().with_max_level(log_level).with_writer(std::io::stderr).init();
 
     match cli.command {
         Command::Debug(cmd) => cmd.run(),
+        Command::Export(cmd) => cmd.run(&home_dir),
         Command::Genesis(cmd) => cmd.run(),
         Command::Init(cmd) => cmd.run(&home_dir),
-        Command::Keys(cmd) => cmd.run(&home_dir),
+        Command::Keys(cmd) => cmd.run(&home_dir, &cli.opts),
         Command::Query(cmd) => cmd.run(&home_dir).await,
         Command::Start(cmd) => cmd.run(&home_dir),
         Command::Tendermint(cmd) => cmd.run(&home_dir).await,
-        Command::Tx(cmd) => cmd.run(&home_dir).await,
+        Command::Tx(cmd) => cmd.run(&home_dir, &cli.opts).await,
         Command::UnsafeResetAll(cmd) => cmd.run(&home_dir),
     }
 }
@@ -104,5 +133,6 @@ async fn run() -> Result<(), DaemonError> {
 async fn main() {
     if let Err(err) = run().await {
         error!("command failed with error: {}", err);
+        std::process::exit(1);
     }
 }
@@ -1,8 +1,27 @@
-use cosmwasm_std::Addr;
+use std::fmt;
+
+use clap::ValueEnum;
+use cosmwasm_std::{Addr, Binary};
 use serde::Serialize;
 
 use crate::{DaemonError, Key};
 
+/// How commands print their results
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Results along with human readable messages
+    Text,
+    /// Only the results, in JSON format, for consumption by scripts
+    Json,
+}
+
+/// Print a human readable message, in the text output format only
+pub fn banner(output: OutputFormat, msg: impl fmt::Display) {
+    if output == OutputFormat::Text {
+        println!("{msg}");
+    }
+}
+
 /// Print a BIP-38 mnemonic phrase
 pub fn mnemonic(phrase: &str) {
     let words = phrase.split(' ').collect::<Vec<_>>();
@@ -32,6 +51,22 @@ pub fn key(key: &Key) -> Result<(), DaemonError> {
     json(PrintableKey::try_from(key)?)
 }
 
+/// Print a newly created signing key along with its mnemonic phrase, as JSON
+pub fn key_with_mnemonic(key: &Key, phrase: &str) -> Result<(), DaemonError> {
+    json(PrintableKeyWithMnemonic {
+        key: PrintableKey::try_from(key)?,
+        mnemonic: phrase,
+    })
+}
+
+/// Print a signature over an off-chain message, along with what's needed to verify it
+pub fn offchain_signature(key: &Key, signature: &[u8]) -> Result<(), DaemonError> {
+    json(PrintableOffchainSignature {
+        key: PrintableKey::try_from(key)?,
+        signature: Binary::from(signature).to_base64(),
+    })
+}
+
 /// Print multiple signing keys, sorted alphabetically by name
 pub fn keys(keys: &[Key]) -> Result<(), DaemonError> {
     json(keys
@@ -44,10 +79,27 @@ pub fn keys(keys: &[Key]) -> Result<(), DaemonError> {
 struct PrintableKey<'a> {
     pub name: &'a str,
     pub address: Addr,
+    /// Signature scheme of the key
+    pub algo: &'static str,
     /// Hex-encoded bytearray
     pub pubkey: String,
 }
 
+#[derive(Serialize)]
+struct PrintableKeyWithMnemonic<'a> {
+    #[serde(flatten)]
+    pub key: PrintableKey<'a>,
+    pub mnemonic: &'a str,
+}
+
+#[derive(Serialize)]
+struct PrintableOffchainSignature<'a> {
+    #[serde(flatten)]
+    pub key: PrintableKey<'a>,
+    /// Base64-encoded bytearray
+    pub signature: String,
+}
+
 impl<'a> TryFrom<&'a Key> for PrintableKey<'a> {
     type Error = DaemonError;
 
@@ -55,7 +107,8 @@ impl<'a> TryFrom<&'a Key> for PrintableKey<'a> {
         Ok(Self {
             name: &key.name,
             address: key.address()?,
-            pubkey: hex::encode(key.pubkey().to_bytes().as_slice()),
+            algo: key.algo().as_str(),
+            pubkey: hex::encode(key.pubkey().as_slice()),
         })
     }
 }
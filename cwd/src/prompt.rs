@@ -33,3 +33,12 @@ pub fn password(prompt: impl Into<String>) -> io::Result<String> {
         .report(true)
         .interact()
 }
+
+/// Prompt for a new password, which the user is asked to repeat.
+pub fn new_password(prompt: impl Into<String>) -> io::Result<String> {
+    dialoguer::Password::new()
+        .with_prompt(prompt)
+        .with_confirmation("Repeat to confirm", "The inputs don't match, try again")
+        .report(true)
+        .interact()
+}
@@ -0,0 +1,142 @@
+//! Hierarchical key derivation for ed25519 and secp256r1 (a.k.a. NIST P-256), following SLIP-10:
+//! https://github.com/satoshilabs/slips/blob/master/slip-0010.md
+//!
+//! BIP-32 only defines derivation over secp256k1. Reusing the secp256k1 child key as the private
+//! key of another curve would produce keys that no other SLIP-10 wallet can reproduce.
+
+use hmac::{Hmac, Mac};
+use p256::elliptic_curve::{Field, PrimeField};
+use sha2::Sha512;
+
+/// Indexes equal to or greater than this value denote hardened derivation
+pub const HARDENED: u32 = 0x8000_0000;
+
+fn hmac_sha512(key: &[u8], data: &[u8]) -> ([u8; 32], [u8; 32]) {
+    let mut mac = Hmac::<Sha512>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(data);
+    let bytes = mac.finalize().into_bytes();
+
+    let mut left = [0u8; 32];
+    let mut right = [0u8; 32];
+    left.copy_from_slice(&bytes[..32]);
+    right.copy_from_slice(&bytes[32..]);
+    (left, right)
+}
+
+/// Derive an ed25519 private key from a seed along the given path.
+///
+/// ed25519 only supports hardened derivation, so every index in the path must be hardened.
+pub fn derive_ed25519(seed: &[u8], path: &[u32]) -> [u8; 32] {
+    let (mut key, mut chain_code) = hmac_sha512(b"ed25519 seed", seed);
+
+    for index in path {
+        assert!(*index >= HARDENED, "ed25519 only supports hardened derivation");
+
+        let mut data = Vec::with_capacity(37);
+        data.push(0);
+        data.extend_from_slice(&key);
+        data.extend_from_slice(&index.to_be_bytes());
+        (key, chain_code) = hmac_sha512(&chain_code, &data);
+    }
+
+    key
+}
+
+/// Derive a secp256r1 private key from a seed along the given path.
+pub fn derive_nist256p1(seed: &[u8], path: &[u32]) -> [u8; 32] {
+    const CURVE_KEY: &[u8] = b"Nist256p1 seed";
+
+    let (mut key, mut chain_code) = hmac_sha512(CURVE_KEY, seed);
+    // the master key must be a non-zero scalar less than the curve order; if not, hash again
+    while parse_scalar(&key).map_or(true, |scalar| bool::from(scalar.is_zero())) {
+        (key, chain_code) = hmac_sha512(CURVE_KEY, &[key, chain_code].concat());
+    }
+
+    for index in path {
+        let mut data = Vec::with_capacity(37);
+        if *index >= HARDENED {
+            data.push(0);
+            data.extend_from_slice(&key);
+        } else {
+            let sk = p256::ecdsa::SigningKey::from_bytes(&key).expect("key is a valid scalar");
+            data.extend_from_slice(sk.verifying_key().to_encoded_point(true).as_bytes());
+        }
+        data.extend_from_slice(&index.to_be_bytes());
+
+        let parent = parse_scalar(&key).expect("key is a valid scalar");
+        loop {
+            let (left, right) = hmac_sha512(&chain_code, &data);
+            // if IL is not less than the curve order, or the resulting key is zero, proceed with
+            // 0x01 || IR || ser32(i) as the HMAC data
+            if let Some(tweak) = parse_scalar(&left) {
+                let child = tweak + parent;
+                if !bool::from(child.is_zero()) {
+                    key = child.to_repr().into();
+                    chain_code = right;
+                    break;
+                }
+            }
+            data.clear();
+            data.push(1);
+            data.extend_from_slice(&right);
+            data.extend_from_slice(&index.to_be_bytes());
+        }
+    }
+
+    key
+}
+
+fn parse_scalar(bytes: &[u8; 32]) -> Option<p256::Scalar> {
+    p256::Scalar::from_repr((*bytes).into()).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // test vector 1 of SLIP-10
+    const SEED: &str = "000102030405060708090a0b0c0d0e0f";
+
+    #[test]
+    fn deriving_ed25519() {
+        let seed = hex::decode(SEED).unwrap();
+        let cases: [(&[u32], &str); 4] = [
+            (&[], "2b4be7f19ee27bbf30c667b642d5f4aa69fd169872f8fc3059c08ebae2eb19e7"),
+            (&[HARDENED], "68e0fe46dfb67e368c75379acec591dad19df3cde26e63b93a8e704f1dade7a3"),
+            (
+                &[HARDENED, HARDENED + 1],
+                "b1d0bad404bf35da785a64ca1ac54b2617211d2777696fbffaf208f746ae84f2",
+            ),
+            (
+                &[HARDENED, HARDENED + 1, HARDENED + 2],
+                "92a5b23c0b8a99e37d07df3fb9966917f5d06e02ddbd909c7e184371463e9fc9",
+            ),
+        ];
+        for (path, expected) in cases {
+            assert_eq!(hex::encode(derive_ed25519(&seed, path)), expected);
+        }
+    }
+
+    #[test]
+    #[should_panic = "ed25519 only supports hardened derivation"]
+    fn rejecting_non_hardened_ed25519() {
+        derive_ed25519(&hex::decode(SEED).unwrap(), &[HARDENED, 1]);
+    }
+
+    #[test]
+    fn deriving_nist256p1() {
+        let seed = hex::decode(SEED).unwrap();
+        let cases: [(&[u32], &str); 4] = [
+            (&[], "612091aaa12e22dd2abef664f8a01a82cae99ad7441b7ef8110424915c268bc2"),
+            (&[HARDENED], "6939694369114c67917a182c59ddb8cafc3004e63ca5d3b84403ba8613debc0c"),
+            (&[HARDENED, 1], "284e9d38d07d21e4e281b645089a94f4cf5a5a81369acf151a1c3a57f18b2129"),
+            (
+                &[HARDENED, 1, HARDENED + 2],
+                "694596e8a54f252c960eb771a3c41e7e32496d03b954aeb90f61635b8e092aa7",
+            ),
+        ];
+        for (path, expected) in cases {
+            assert_eq!(hex::encode(derive_nist256p1(&seed, path)), expected);
+        }
+    }
+}
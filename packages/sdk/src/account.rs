@@ -2,14 +2,16 @@ use cosmwasm_schema::cw_serde;
 use cosmwasm_std::{Addr, Binary};
 use cw_address_like::AddressLike;
 
+use crate::{PublicKey, TxBody};
+
 /// The account type to be stored on-chain.
 #[cw_serde]
 pub enum Account<T: AddressLike> {
     /// An account that is controlled by a single public/private key pair.
     /// Roughly synonymous to "externally-owned account" (EoA) in Ethereum.
     Base {
-        /// The account's secp256k1 public key
-        pubkey: Binary,
+        /// The account's public key, tagged by its signature scheme
+        pubkey: PublicKey,
 
         /// The account's sequence number, used to prevent replay attacks.
         /// The first tx ever to be submitted by the account should come with the sequence of 1.
@@ -17,6 +19,10 @@ pub enum Account<T: AddressLike> {
     },
 
     /// An account that is controlled by wasm code.
+    ///
+    /// A contract may also send transactions, in which case the state machine
+    /// invokes its `sudo` entry point with `AccountSudoMsg::Authenticate` in
+    /// place of signature verification.
     Contract {
         /// Identifier of the wasm byte code associated with this contract.
         code_id: u64,
@@ -51,3 +57,21 @@ impl From<Account<Addr>> for Account<String> {
         }
     }
 }
+
+/// Message that the state machine sends to a contract account's `sudo` entry
+/// point when the contract is the sender of a transaction.
+///
+/// The contract is expected to return an error if the transaction is not
+/// authorized. This allows accounts with custom authentication logics, such as
+/// session keys, passkeys, or social recovery wallets, to be built as contracts.
+#[cw_serde]
+pub enum AccountSudoMsg {
+    Authenticate {
+        /// The body of the transaction to be authenticated
+        tx_body: TxBody,
+
+        /// Arbitrary data to be interpreted by the contract, e.g. a signature.
+        /// This is the `signature` field of the transaction.
+        credential: Binary,
+    },
+}
@@ -2,7 +2,7 @@ use bech32::{FromBase32, ToBase32, Variant};
 use cosmwasm_std::{Addr, CanonicalAddr};
 use thiserror::Error;
 
-use crate::hash::sha256;
+use crate::{hash::sha256, pubkey::PublicKey};
 
 /// Currently we simply hardcode the prefix in the state machine's binary.
 ///
@@ -21,13 +21,6 @@ pub const ADDRESS_PREFIX: &str = "cw";
 ///   https://ethereum-magicians.org/t/increasing-address-size-from-20-to-32-bytes/5485/43
 pub const ADDRESS_LENGTH: usize = 32;
 
-/// According to ADR-028, each basic address (one that is represented by a single key pair), needs
-/// to have a "type" string denoting the public key scheme used.
-///
-/// For now, cw-sdk only supports the secp256k1 scheme. The type string is defined by:
-/// https://github.com/cosmos/cosmos-sdk/blob/main/proto/cosmos/crypto/secp256k1/keys.proto
-pub const PUBKEY_TYPE: &str = "cosmos.crypto.secp256k1.PubKey";
-
 /// Takes a human readable address and returns a canonical binary representation of it.
 pub fn canonicalize(human: &str) -> Result<CanonicalAddr, AddressError> {
     let (prefix, addr_bytes_base32, variant) = bech32::decode(human)?;
@@ -71,13 +64,15 @@ pub fn validate(input: &str) -> Result<Addr, AddressError> {
 /// The address bytes are computed as:
 ///
 /// ```plain
-/// address_bytes := sha256(PUBKEY_TYPE | sha256(pubkey_bytes))[:ADDRESS_LENGTH]
+/// address_bytes := sha256(pubkey_type | sha256(pubkey_bytes))[:ADDRESS_LENGTH]
 /// ```
 ///
-/// Where `|` means bytes concatenation without using any separator.
-pub fn derive_from_pubkey(pubkey_bytes: &[u8]) -> Result<Addr, AddressError> {
-    let mut bytes = PUBKEY_TYPE.to_string().into_bytes();
-    bytes.extend(sha256(pubkey_bytes));
+/// Where `|` means bytes concatenation without using any separator, and `pubkey_type` is the
+/// ADR-028 "type" string denoting the public key scheme used, e.g.
+/// `cosmos.crypto.secp256k1.PubKey`.
+pub fn derive_from_pubkey(pubkey: &PublicKey) -> Result<Addr, AddressError> {
+    let mut bytes = pubkey.type_str().to_string().into_bytes();
+    bytes.extend(sha256(pubkey.as_slice()));
     humanize_prehash(&bytes)
 }
 
@@ -1,6 +1,10 @@
 use cosmwasm_schema::cw_serde;
+use cosmwasm_std::Binary;
 
-use crate::SdkMsg;
+use crate::{
+    AccountResponse, ContractHistoryEntry, FeeAllowanceResponse, GrantResponse, SdkMsg,
+    UpgradePlan, WasmRawRecord,
+};
 
 /// This should be included inside `~/.tendermint/genesis.json`, under the
 /// `app_state` field.
@@ -19,6 +23,88 @@ pub struct GenesisState {
     /// The application developers must provide a trusted deployer account.
     pub deployer: String,
 
+    /// Address or label of the account allowed to schedule chain upgrades,
+    /// e.g. a governance contract. If not provided, upgrades can't be scheduled.
+    ///
+    /// Labels are resolved when an upgrade is scheduled, so the account doesn't
+    /// need to exist until then.
+    pub upgrade_authority: Option<String>,
+
+    /// State exported from an existing chain, to be imported during the
+    /// InitChain call before the messages are executed.
+    pub snapshot: Option<GenesisSnapshot>,
+
     /// Messages to be executed in order during the InitChain call.
     pub msgs: Vec<SdkMsg>,
 }
+
+/// The state of a chain at a given height, which can be used to start a new
+/// chain, e.g. to fork mainnet state into a local test network.
+///
+/// The upgrade authority is not included here, but in `GenesisState`, so that
+/// it can be changed when starting the new chain.
+#[cw_serde]
+pub struct GenesisSnapshot {
+    /// Height of the last committed block at the time the state was exported
+    pub height: u64,
+
+    /// Wasm byte codes, ordered by code id, starting from 1
+    pub codes: Vec<Binary>,
+
+    /// Base and contract accounts
+    pub accounts: Vec<AccountResponse>,
+
+    /// The raw storage of each contract
+    pub contracts: Vec<ContractStorage>,
+
+    pub fee_allowances: Vec<FeeAllowanceResponse>,
+
+    pub grants: Vec<GrantResponse>,
+
+    /// Sequence numbers of the contract accounts that have sent txs
+    #[serde(default)]
+    pub contract_sequences: Vec<ContractSequence>,
+
+    /// Code histories of contracts, ordered by contract addresses
+    #[serde(default)]
+    pub contract_histories: Vec<ContractHistory>,
+
+    /// The chain upgrade that is scheduled but not yet applied, if any
+    pub upgrade_plan: Option<UpgradePlan>,
+
+    /// Chain upgrades that have been applied, so that they are not applied
+    /// again on the new chain
+    #[serde(default)]
+    pub done_upgrades: Vec<DoneUpgrade>,
+
+    /// Version of the application. None if no upgrade has been applied
+    pub app_version: Option<u64>,
+}
+
+/// All key-value pairs in a contract's storage
+#[cw_serde]
+pub struct ContractStorage {
+    pub address: String,
+    pub records: Vec<WasmRawRecord>,
+}
+
+/// The sequence number of a contract account
+#[cw_serde]
+pub struct ContractSequence {
+    pub address: String,
+    pub sequence: u64,
+}
+
+/// All operations that changed the code a contract runs
+#[cw_serde]
+pub struct ContractHistory {
+    pub address: String,
+    pub entries: Vec<ContractHistoryEntry>,
+}
+
+/// A chain upgrade, and the height at which it was applied
+#[cw_serde]
+pub struct DoneUpgrade {
+    pub name: String,
+    pub height: u64,
+}
@@ -1,9 +1,35 @@
-use cosmwasm_std::{Addr, Api, Coin, StdResult};
+use std::str::FromStr;
+
+use cosmwasm_std::{Addr, Api, Coin, StdError, StdResult, Uint128};
 
 pub fn stringify_coins(coins: &[Coin]) -> String {
     coins.iter().map(|coin| coin.to_string()).collect::<Vec<_>>().join(",")
 }
 
+/// Parse a string in the format of `100uatom,20ufoo` into coins, i.e. the
+/// reverse of `stringify_coins`. An empty string is parsed into an empty vec.
+pub fn parse_coins(s: &str) -> StdResult<Vec<Coin>> {
+    if s.is_empty() {
+        return Ok(vec![]);
+    }
+
+    s.split(',')
+        .map(|coin_str| {
+            let denom_start = coin_str
+                .find(|c: char| !c.is_ascii_digit())
+                .ok_or_else(|| StdError::generic_err(format!("coin `{coin_str}` has no denom")))?;
+            let (amount, denom) = coin_str.split_at(denom_start);
+            if amount.is_empty() {
+                return Err(StdError::generic_err(format!("coin `{coin_str}` has no amount")));
+            }
+            Ok(Coin {
+                denom: denom.into(),
+                amount: Uint128::from_str(amount)?,
+            })
+        })
+        .collect()
+}
+
 pub fn stringify_option(opt: Option<impl ToString>) -> String {
     opt.map_or_else(|| "null".to_string(), |value| value.to_string())
 }
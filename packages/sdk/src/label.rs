@@ -34,6 +34,16 @@ use cosmwasm_std::Addr;
 
 use crate::address::{derive_from_label, validate, AddressError, ADDRESS_PREFIX};
 
+/// Label of the bank contract, which the state machine invokes to process fee
+/// payments. It must implement the `sudo` method defined by `BankSudoMsg`.
+pub const BANK: &str = "bank";
+
+/// Label from which the address of the account collecting tx fees is derived.
+///
+/// No contract is expected to be instantiated with this label; the account
+/// simply holds the fees in the bank contract.
+pub const FEE_COLLECTOR: &str = "fee_collector";
+
 /// Resolve and validate a raw address string, which may either be a contract's
 /// actual address or its label.
 ///
@@ -5,12 +5,18 @@ pub mod hash;
 pub mod helpers;
 pub mod label;
 mod msg;
-mod tx;
+pub mod pubkey;
+pub mod tx;
 
-pub use account::Account;
-pub use genesis::GenesisState;
+pub use account::{Account, AccountSudoMsg};
+pub use genesis::{
+    ContractHistory, ContractSequence, ContractStorage, DoneUpgrade, GenesisSnapshot, GenesisState,
+};
 pub use msg::{
-    AccountResponse, CodeResponse, ContractResponse, InfoResponse, SdkMsg, SdkQuery,
+    AccountResponse, Authorization, BankSudoMsg, CodeResponse, ContractCodeOperation,
+    ContractHistoryEntry, ContractResponse, FeeAllowance, FeeAllowanceResponse, Grant,
+    GrantResponse, InfoResponse, SdkMsg, SdkQuery, SimulateResponse, UpgradePlan, WasmRawRecord,
     WasmRawResponse, WasmSmartResponse,
 };
-pub use tx::{Tx, TxBody};
+pub use pubkey::PublicKey;
+pub use tx::{OffchainSignDoc, SignDoc, Tx, TxBody};
@@ -1,8 +1,8 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{Binary, BlockInfo, Coin, ContractResult};
+use cosmwasm_std::{Binary, BlockInfo, Coin, ContractResult, Event, Order, Timestamp};
 use serde_json::Value;
 
-use crate::account::Account;
+use crate::{account::Account, tx::Tx};
 
 #[cw_serde]
 pub enum SdkMsg {
@@ -56,6 +56,189 @@ pub enum SdkMsg {
         code_id: u64,
         msg: Value,
     },
+
+    /// Allow the grantee to send txs whose fees are paid by the sender of this
+    /// message, without the sender having to co-sign each tx.
+    /// Overwrites the existing allowance, if any.
+    GrantFeeAllowance {
+        grantee: String,
+        allowance: FeeAllowance,
+    },
+
+    /// Revoke the fee allowance previously granted to the grantee
+    RevokeFeeAllowance {
+        grantee: String,
+    },
+
+    /// Allow the grantee to execute messages on behalf of the sender of this
+    /// message, through `Exec`, as long as the messages are permitted by the
+    /// authorization. Overwrites the existing grant, if any.
+    Grant {
+        grantee: String,
+        authorization: Authorization,
+        expiration: Option<Timestamp>,
+    },
+
+    /// Revoke the grant previously given to the grantee
+    Revoke {
+        grantee: String,
+    },
+
+    /// Execute messages on behalf of the granter, who must have given the
+    /// sender a grant that permits each of the messages. The messages are
+    /// executed with the granter as `info.sender`.
+    ///
+    /// `Exec` messages can't be nested.
+    Exec {
+        granter: String,
+        msgs: Vec<SdkMsg>,
+    },
+
+    /// Schedule a chain upgrade at the given height. Only the upgrade authority
+    /// specified in the genesis state can send this message.
+    ///
+    /// At that height, nodes running a binary that doesn't have a handler for
+    /// the upgrade's name halt, so that operators can switch to the new binary.
+    /// Scheduling an upgrade replaces the one previously scheduled, if any.
+    ScheduleUpgrade {
+        name: String,
+        height: u64,
+        /// Additional info for node operators, e.g. where to find the binary
+        info: Option<String>,
+    },
+}
+
+impl SdkMsg {
+    /// All message types, i.e. every value `msg_type` may return.
+    pub const MSG_TYPES: &'static [&'static str] = &[
+        "store_code",
+        "instantiate",
+        "execute",
+        "migrate",
+        "grant_fee_allowance",
+        "revoke_fee_allowance",
+        "grant",
+        "revoke",
+        "exec",
+        "schedule_upgrade",
+    ];
+
+    /// The message's type, i.e. the name of the enum variant in snake_case,
+    /// which is also the key of the message's JSON representation.
+    pub fn msg_type(&self) -> &'static str {
+        match self {
+            SdkMsg::StoreCode {
+                ..
+            } => "store_code",
+            SdkMsg::Instantiate {
+                ..
+            } => "instantiate",
+            SdkMsg::Execute {
+                ..
+            } => "execute",
+            SdkMsg::Migrate {
+                ..
+            } => "migrate",
+            SdkMsg::GrantFeeAllowance {
+                ..
+            } => "grant_fee_allowance",
+            SdkMsg::RevokeFeeAllowance {
+                ..
+            } => "revoke_fee_allowance",
+            SdkMsg::Grant {
+                ..
+            } => "grant",
+            SdkMsg::Revoke {
+                ..
+            } => "revoke",
+            SdkMsg::Exec {
+                ..
+            } => "exec",
+            SdkMsg::ScheduleUpgrade {
+                ..
+            } => "schedule_upgrade",
+        }
+    }
+}
+
+/// The message that the state machine sends to the "bank" contract's `sudo`
+/// entry point. The bank contract must implement it.
+#[cw_serde]
+pub enum BankSudoMsg {
+    /// Forcibly transfer coins between two accounts.
+    /// Callable by the state machine when handling gas fee payments and funds
+    /// attached to messages.
+    Transfer {
+        from: String,
+        to: String,
+        coins: Vec<Coin>,
+    },
+}
+
+/// An allowance for a grantee to spend the granter's coins on tx fees.
+#[cw_serde]
+pub struct FeeAllowance {
+    /// The maximum amount of coins that can be spent on fees.
+    /// Decreases as fees are paid. None means unlimited.
+    pub spend_limit: Option<Vec<Coin>>,
+
+    /// The allowance can't be used after this time. None means never expires.
+    pub expiration: Option<Timestamp>,
+}
+
+/// An operation that changes the code a contract runs.
+#[cw_serde]
+pub enum ContractCodeOperation {
+    Instantiate,
+    Migrate,
+}
+
+/// A record of an operation that changed the code a contract runs.
+#[cw_serde]
+pub struct ContractHistoryEntry {
+    pub operation: ContractCodeOperation,
+    /// The code id the contract runs after the operation
+    pub code_id: u64,
+    /// Height of the block in which the operation took place
+    pub height: u64,
+    /// The account who instantiated or migrated the contract
+    pub sender: String,
+    /// SHA-256 hash of the instantiate or migrate message, in hex encoding
+    pub msg_hash: String,
+}
+
+/// Specifies which messages a grantee may execute on behalf of the granter.
+#[cw_serde]
+pub enum Authorization {
+    /// Any message of the given type, e.g. "execute"; see `SdkMsg::msg_type`.
+    ///
+    /// Messages that manage grants and fee allowances, migrate contracts, or
+    /// schedule chain upgrades can't be granted this way.
+    MsgType {
+        msg_type: String,
+    },
+
+    /// `Execute` messages on the given contract, and no other message
+    Contract {
+        contract: String,
+    },
+}
+
+/// Permission for a grantee to execute messages on behalf of the granter.
+#[cw_serde]
+pub struct Grant {
+    pub authorization: Authorization,
+
+    /// The grant can't be used after this time. None means never expires.
+    pub expiration: Option<Timestamp>,
+}
+
+/// A chain upgrade scheduled at a given height
+#[cw_serde]
+pub struct UpgradePlan {
+    pub name: String,
+    pub height: u64,
+    pub info: Option<String>,
 }
 
 #[cw_serde]
@@ -91,6 +274,29 @@ pub enum SdkQuery {
         limit: Option<u32>,
     },
 
+    /// Enumerate all contracts running the given code id, by address
+    #[returns(Vec<ContractResponse>)]
+    ContractsByCode {
+        code_id: u64,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    /// Enumerate all contracts administered by the given account, by address
+    #[returns(Vec<ContractResponse>)]
+    ContractsByAdmin {
+        admin: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    /// Query the code history of a contract, i.e. its instantiation and all of
+    /// its migrations, from the oldest to the newest
+    #[returns(Vec<ContractHistoryEntry>)]
+    ContractHistory {
+        contract: String,
+    },
+
     /// Query a single wasm byte code by id
     #[returns(CodeResponse)]
     Code {
@@ -104,6 +310,29 @@ pub enum SdkQuery {
         limit: Option<u32>,
     },
 
+    /// Query the fee allowance granted by a granter to a grantee
+    #[returns(FeeAllowanceResponse)]
+    FeeAllowance {
+        granter: String,
+        grantee: String,
+    },
+
+    /// Enumerate all fee allowances granted by a granter
+    #[returns(Vec<FeeAllowanceResponse>)]
+    FeeAllowances {
+        granter: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    /// Enumerate all grants given by a granter
+    #[returns(Vec<GrantResponse>)]
+    Grants {
+        granter: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
     /// Perform raw query on a wasm contract
     #[returns(WasmRawResponse)]
     WasmRaw {
@@ -111,18 +340,54 @@ pub enum SdkQuery {
         key: Binary,
     },
 
+    /// Enumerate the raw key-value pairs in a wasm contract's storage.
+    /// Both bounds are exclusive; the iteration starts after `start_after` and
+    /// stops before `end`, in the given order (ascending by default).
+    #[returns(Vec<WasmRawRecord>)]
+    WasmRawRange {
+        contract: String,
+        start_after: Option<Binary>,
+        end: Option<Binary>,
+        limit: Option<u32>,
+        order: Option<Order>,
+    },
+
     /// Perform smart query on a wasm contract
     #[returns(WasmSmartResponse)]
     WasmSmart {
         contract: String,
         msg: Value,
     },
+
+    /// Execute a tx against the last committed state without committing the
+    /// state changes. Used to find out whether a tx will succeed, and to
+    /// estimate how much gas it consumes.
+    #[returns(SimulateResponse)]
+    Simulate {
+        tx: Tx,
+        /// Do not verify the signatures of the sender and the fee payer, so that
+        /// an unsigned tx can be simulated.
+        skip_signature: bool,
+    },
+
+    /// Query the chain upgrade that is scheduled but not yet applied, if any
+    #[returns(Option<UpgradePlan>)]
+    UpgradePlan {},
+
+    /// Query the height at which the upgrade of the given name was applied.
+    /// Return None if the upgrade hasn't been applied.
+    #[returns(Option<u64>)]
+    AppliedUpgrade {
+        name: String,
+    },
 }
 
 #[cw_serde]
 pub struct InfoResponse {
     pub last_committed_block: BlockInfo,
     pub code_count: u64,
+    /// Incremented each time a chain upgrade is applied, starting from 1
+    pub app_version: u64,
 }
 
 #[cw_serde]
@@ -145,6 +410,20 @@ pub struct CodeResponse {
     pub wasm_byte_code: Binary,
 }
 
+#[cw_serde]
+pub struct FeeAllowanceResponse {
+    pub granter: String,
+    pub grantee: String,
+    pub allowance: FeeAllowance,
+}
+
+#[cw_serde]
+pub struct GrantResponse {
+    pub granter: String,
+    pub grantee: String,
+    pub grant: Grant,
+}
+
 #[cw_serde]
 pub struct WasmRawResponse {
     /// Raw value in the contract storage under the given key.
@@ -152,6 +431,12 @@ pub struct WasmRawResponse {
     pub value: Option<Binary>,
 }
 
+#[cw_serde]
+pub struct WasmRawRecord {
+    pub key: Binary,
+    pub value: Binary,
+}
+
 #[cw_serde]
 pub struct WasmSmartResponse {
     /// Smart query result.
@@ -159,3 +444,15 @@ pub struct WasmSmartResponse {
     /// into the correct type.
     pub result: ContractResult<Binary>,
 }
+
+#[cw_serde]
+pub struct SimulateResponse {
+    /// Events emitted during the tx's execution
+    pub events: Vec<Event>,
+    /// The data returned by each message in the tx, in order
+    pub data: Vec<Option<Binary>>,
+    /// The total amount of gas consumed by the contract calls
+    pub gas_used: u64,
+    /// The storage keys written (or deleted) by the tx, in ascending order
+    pub writes: Vec<Binary>,
+}
@@ -0,0 +1,235 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::Binary;
+use thiserror::Error;
+
+/// ADR-028 type string of secp256k1 public keys, defined by:
+/// https://github.com/cosmos/cosmos-sdk/blob/main/proto/cosmos/crypto/secp256k1/keys.proto
+pub const SECP256K1_PUBKEY_TYPE: &str = "cosmos.crypto.secp256k1.PubKey";
+
+/// ADR-028 type string of ed25519 public keys, defined by:
+/// https://github.com/cosmos/cosmos-sdk/blob/main/proto/cosmos/crypto/ed25519/keys.proto
+pub const ED25519_PUBKEY_TYPE: &str = "cosmos.crypto.ed25519.PubKey";
+
+/// ADR-028 type string of secp256r1 public keys, defined by:
+/// https://github.com/cosmos/cosmos-sdk/blob/main/proto/cosmos/crypto/secp256r1/keys.proto
+pub const SECP256R1_PUBKEY_TYPE: &str = "cosmos.crypto.secp256r1.PubKey";
+
+/// A public key, tagged by the signature scheme it is to be used with.
+#[cw_serde]
+pub enum PublicKey {
+    /// A compressed, SEC1-encoded secp256k1 public key (33 bytes).
+    /// The signature is ECDSA over the SHA-256 hash of the message, in the 64-byte `r | s` format.
+    Secp256k1(Binary),
+
+    /// An ed25519 public key (32 bytes).
+    /// The signature is EdDSA over the raw message, in the 64-byte format.
+    Ed25519(Binary),
+
+    /// A compressed, SEC1-encoded secp256r1 (a.k.a. NIST P-256) public key (33 bytes).
+    /// The signature is ECDSA over the SHA-256 hash of the message, in the 64-byte `r | s` format.
+    Secp256r1(Binary),
+}
+
+impl PublicKey {
+    /// Name of the signature scheme
+    pub fn algo(&self) -> &'static str {
+        match self {
+            PublicKey::Secp256k1(_) => "secp256k1",
+            PublicKey::Ed25519(_) => "ed25519",
+            PublicKey::Secp256r1(_) => "secp256r1",
+        }
+    }
+
+    /// The "type" string used in address derivation according to ADR-028
+    pub fn type_str(&self) -> &'static str {
+        match self {
+            PublicKey::Secp256k1(_) => SECP256K1_PUBKEY_TYPE,
+            PublicKey::Ed25519(_) => ED25519_PUBKEY_TYPE,
+            PublicKey::Secp256r1(_) => SECP256R1_PUBKEY_TYPE,
+        }
+    }
+
+    /// The raw public key bytes
+    pub fn as_slice(&self) -> &[u8] {
+        match self {
+            PublicKey::Secp256k1(bytes) => bytes.as_slice(),
+            PublicKey::Ed25519(bytes) => bytes.as_slice(),
+            PublicKey::Secp256r1(bytes) => bytes.as_slice(),
+        }
+    }
+
+    /// Verify a signature over the given message, using the scheme that
+    /// corresponds to the public key's type.
+    pub fn verify(&self, msg: &[u8], sig: &[u8]) -> Result<(), PublicKeyError> {
+        match self {
+            PublicKey::Secp256k1(bytes) => {
+                use k256::ecdsa::{signature::Verifier, Signature, VerifyingKey};
+
+                let vk = VerifyingKey::from_sec1_bytes(bytes)
+                    .map_err(|err| PublicKeyError::invalid_pubkey(self.algo(), err))?;
+                let sig = Signature::try_from(sig)
+                    .map_err(|err| PublicKeyError::invalid_signature(self.algo(), err))?;
+                vk.verify(msg, &sig)
+                    .map_err(|_| PublicKeyError::verification_failed(self.algo()))
+            },
+
+            PublicKey::Ed25519(bytes) => {
+                use ed25519_dalek::{PublicKey as VerifyingKey, Signature, Verifier};
+
+                let vk = VerifyingKey::from_bytes(bytes)
+                    .map_err(|err| PublicKeyError::invalid_pubkey(self.algo(), err))?;
+                let sig = Signature::try_from(sig)
+                    .map_err(|err| PublicKeyError::invalid_signature(self.algo(), err))?;
+                vk.verify(msg, &sig)
+                    .map_err(|_| PublicKeyError::verification_failed(self.algo()))
+            },
+
+            PublicKey::Secp256r1(bytes) => {
+                use p256::ecdsa::{signature::Verifier, Signature, VerifyingKey};
+
+                let vk = VerifyingKey::from_sec1_bytes(bytes)
+                    .map_err(|err| PublicKeyError::invalid_pubkey(self.algo(), err))?;
+                let sig = Signature::try_from(sig)
+                    .map_err(|err| PublicKeyError::invalid_signature(self.algo(), err))?;
+                vk.verify(msg, &sig)
+                    .map_err(|_| PublicKeyError::verification_failed(self.algo()))
+            },
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum PublicKeyError {
+    #[error("invalid {algo} public key: {reason}")]
+    InvalidPubkey {
+        algo: String,
+        reason: String,
+    },
+
+    #[error("invalid {algo} signature: {reason}")]
+    InvalidSignature {
+        algo: String,
+        reason: String,
+    },
+
+    #[error("{algo} signature verification failed")]
+    VerificationFailed {
+        algo: String,
+    },
+}
+
+impl PublicKeyError {
+    pub fn invalid_pubkey(algo: impl Into<String>, reason: impl ToString) -> Self {
+        Self::InvalidPubkey {
+            algo: algo.into(),
+            reason: reason.to_string(),
+        }
+    }
+
+    pub fn invalid_signature(algo: impl Into<String>, reason: impl ToString) -> Self {
+        Self::InvalidSignature {
+            algo: algo.into(),
+            reason: reason.to_string(),
+        }
+    }
+
+    pub fn verification_failed(algo: impl Into<String>) -> Self {
+        Self::VerificationFailed {
+            algo: algo.into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MSG: &[u8] = b"hello world";
+
+    /// Generate a key pair of each scheme deterministically, and sign the message with it
+    fn sign_with_each_scheme(msg: &[u8]) -> Vec<(PublicKey, Vec<u8>)> {
+        let k256_sk = k256::ecdsa::SigningKey::from_bytes(&[1u8; 32]).unwrap();
+        let k256_sig: k256::ecdsa::Signature = k256::ecdsa::signature::Signer::sign(&k256_sk, msg);
+
+        let secret = ed25519_dalek::SecretKey::from_bytes(&[1u8; 32]).unwrap();
+        let keypair = ed25519_dalek::Keypair {
+            public: (&secret).into(),
+            secret,
+        };
+        let ed25519_sig = ed25519_dalek::Signer::sign(&keypair, msg);
+
+        let p256_sk = p256::ecdsa::SigningKey::from_bytes(&[1u8; 32]).unwrap();
+        let p256_sig: p256::ecdsa::Signature = p256::ecdsa::signature::Signer::sign(&p256_sk, msg);
+
+        vec![
+            (
+                PublicKey::Secp256k1(k256_sk.verifying_key().to_bytes().to_vec().into()),
+                k256_sig.as_ref().to_vec(),
+            ),
+            (
+                PublicKey::Ed25519(keypair.public.to_bytes().to_vec().into()),
+                ed25519_sig.to_bytes().to_vec(),
+            ),
+            (
+                PublicKey::Secp256r1(
+                    p256_sk.verifying_key().to_encoded_point(true).as_bytes().to_vec().into(),
+                ),
+                p256_sig.as_ref().to_vec(),
+            ),
+        ]
+    }
+
+    #[test]
+    fn verifying_valid_signatures() {
+        for (pubkey, sig) in sign_with_each_scheme(MSG) {
+            pubkey.verify(MSG, &sig).unwrap();
+        }
+    }
+
+    #[test]
+    fn rejecting_tampered_messages() {
+        for (pubkey, sig) in sign_with_each_scheme(MSG) {
+            let err = pubkey.verify(b"hello world!", &sig).unwrap_err();
+            assert!(matches!(err, PublicKeyError::VerificationFailed {
+                ..
+            }));
+        }
+    }
+
+    #[test]
+    fn rejecting_signatures_by_another_key() {
+        let signed = sign_with_each_scheme(MSG);
+        // check each scheme's pubkey against the signature produced by the next scheme
+        for (i, (pubkey, _)) in signed.iter().enumerate() {
+            let (_, sig) = &signed[(i + 1) % signed.len()];
+            assert!(pubkey.verify(MSG, sig).is_err());
+        }
+    }
+
+    #[test]
+    fn rejecting_wrong_length_pubkeys() {
+        for (pubkey, sig) in sign_with_each_scheme(MSG) {
+            // drop the last byte of the pubkey
+            let bytes = pubkey.as_slice()[..pubkey.as_slice().len() - 1].to_vec();
+            let truncated = match pubkey {
+                PublicKey::Secp256k1(_) => PublicKey::Secp256k1(bytes.into()),
+                PublicKey::Ed25519(_) => PublicKey::Ed25519(bytes.into()),
+                PublicKey::Secp256r1(_) => PublicKey::Secp256r1(bytes.into()),
+            };
+            let err = truncated.verify(MSG, &sig).unwrap_err();
+            assert!(matches!(err, PublicKeyError::InvalidPubkey {
+                ..
+            }));
+        }
+    }
+
+    #[test]
+    fn rejecting_wrong_length_signatures() {
+        for (pubkey, sig) in sign_with_each_scheme(MSG) {
+            let err = pubkey.verify(MSG, &sig[..63]).unwrap_err();
+            assert!(matches!(err, PublicKeyError::InvalidSignature {
+                ..
+            }));
+        }
+    }
+}
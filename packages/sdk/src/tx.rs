@@ -1,7 +1,14 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::Binary;
+use cosmwasm_std::{Binary, Coin, Timestamp};
+use serde::Serialize;
+use serde_json::Value;
+use thiserror::Error;
 
-use crate::SdkMsg;
+use crate::{
+    address::{self, AddressError},
+    pubkey::PublicKeyError,
+    PublicKey, SdkMsg,
+};
 
 /// Tendermint will provide this as JSON bytes by in the CheckTx and DeliverTx
 /// requests. The state machine should deserialize the bytes upon receipt.
@@ -11,17 +18,31 @@ pub struct Tx {
     /// executed in order, and some parameters for prevention of replay attacks.
     pub body: TxBody,
 
-    /// The sender's secp256k1 public key.
+    /// The sender's public key, tagged by its signature scheme.
     /// Optional if the accounts already exists in the state.
-    pub pubkey: Option<Binary>,
+    pub pubkey: Option<PublicKey>,
 
-    /// Secp256k1 signature.
-    /// The content is `sha256(JSON.stringify(txbody))`, signed by the
-    /// corresponding private key.
+    /// Signature over the canonical sign bytes of `SignDoc { body }`, signed by
+    /// the corresponding private key, using the scheme indicated by the public
+    /// key. See `SignDoc::sign_bytes` for the encoding.
+    ///
+    /// If the sender is a contract account, this is instead an arbitrary
+    /// credential to be passed to the contract for authentication.
     pub signature: Binary,
+
+    /// The fee payer's public key.
+    /// Optional if the fee payer's account already exists in the state.
+    pub fee_payer_pubkey: Option<PublicKey>,
+
+    /// The fee payer's signature over the same sign doc as the sender's.
+    ///
+    /// If a fee payer is specified in the tx body but this is not provided, the
+    /// fee payer must have granted a fee allowance to the sender.
+    pub fee_payer_signature: Option<Binary>,
 }
 
-/// Body of the transaction. This is what the sender needs to sign.
+/// Body of the transaction. This is what the sender needs to sign, wrapped in a
+/// `SignDoc`.
 #[cw_serde]
 pub struct TxBody {
     /// The sender's address
@@ -37,4 +58,319 @@ pub struct TxBody {
 
     /// Wasm messages to be executed in order
     pub msgs: Vec<SdkMsg>,
+
+    /// The tx is rejected if included in a block with height greater than this.
+    /// Used to prevent pre-signed txs from staying valid indefinitely.
+    pub timeout_height: Option<u64>,
+
+    /// The tx is rejected if included in a block with time later than this.
+    /// Used to prevent pre-signed txs from staying valid indefinitely.
+    pub timeout_timestamp: Option<Timestamp>,
+
+    /// Fee to be paid for this tx. Transferred to the fee collector account
+    /// through the "bank" contract.
+    pub fee: Vec<Coin>,
+
+    /// The account that pays the fee. If not provided, the sender pays.
+    pub fee_payer: Option<String>,
+
+    /// The fee payer's sequence number. Required if the fee payer co-signs
+    /// the tx, in which case the fee payer's sequence is incremented as well.
+    pub fee_payer_sequence: Option<u64>,
+
+    /// The maximum amount of gas the tx may consume in total. The tx fails if
+    /// it runs out of gas. None means the chain's default limit applies.
+    pub gas_limit: Option<u64>,
+}
+
+/// The document that the sender of a tx signs.
+#[cw_serde]
+pub struct SignDoc {
+    pub body: TxBody,
+}
+
+impl SignDoc {
+    pub fn new(body: TxBody) -> Self {
+        Self {
+            body,
+        }
+    }
+
+    /// The bytes to be signed, which is the canonical JSON encoding of the
+    /// sign doc. See `to_canonical_json` for the rules of the encoding.
+    pub fn sign_bytes(&self) -> Result<Vec<u8>, SignDocError> {
+        to_canonical_json(self).map_err(SignDocError::from)
+    }
+
+    /// Verify a signature over the sign bytes, using the scheme indicated by
+    /// the public key.
+    pub fn verify(&self, pubkey: &PublicKey, signature: &[u8]) -> Result<(), SignDocError> {
+        pubkey.verify(&self.sign_bytes()?, signature).map_err(SignDocError::from)
+    }
+}
+
+/// The type of the message in an off-chain sign doc, as defined by ADR-036:
+/// https://github.com/cosmos/cosmos-sdk/blob/main/docs/architecture/adr-036-arbitrary-signature.md
+pub const OFFCHAIN_MSG_TYPE: &str = "sign/MsgSignData";
+
+/// The document signed to prove the ownership of an address off-chain, i.e.
+/// without sending a tx, in the shape defined by ADR-036: an Amino sign doc
+/// with empty chain id, zero account number and sequence, no fee, and a single
+/// `sign/MsgSignData` message.
+///
+/// Its sign bytes are the canonical JSON encoding as well, which is an object
+/// without a `body` key, so a signature over an off-chain sign doc can never be
+/// replayed as a signature over a `SignDoc`, and vice versa.
+#[cw_serde]
+pub struct OffchainSignDoc {
+    pub account_number: String,
+    pub chain_id: String,
+    pub fee: OffchainFee,
+    pub memo: String,
+    pub msgs: Vec<OffchainMsg>,
+    pub sequence: String,
+}
+
+#[cw_serde]
+pub struct OffchainFee {
+    pub amount: Vec<Coin>,
+    pub gas: String,
+}
+
+#[cw_serde]
+pub struct OffchainMsg {
+    #[serde(rename = "type")]
+    pub ty: String,
+    pub value: MsgSignData,
+}
+
+#[cw_serde]
+pub struct MsgSignData {
+    /// Address of the account whose ownership is proven
+    pub signer: String,
+    /// The arbitrary data that is signed
+    pub data: Binary,
+}
+
+impl OffchainSignDoc {
+    pub fn new(signer: impl Into<String>, data: impl Into<Binary>) -> Self {
+        Self {
+            account_number: "0".into(),
+            chain_id: "".into(),
+            fee: OffchainFee {
+                amount: vec![],
+                gas: "0".into(),
+            },
+            memo: "".into(),
+            msgs: vec![OffchainMsg {
+                ty: OFFCHAIN_MSG_TYPE.into(),
+                value: MsgSignData {
+                    signer: signer.into(),
+                    data: data.into(),
+                },
+            }],
+            sequence: "0".into(),
+        }
+    }
+
+    /// The bytes to be signed, which is the canonical JSON encoding of the
+    /// sign doc. See `to_canonical_json` for the rules of the encoding.
+    pub fn sign_bytes(&self) -> Result<Vec<u8>, SignDocError> {
+        to_canonical_json(self).map_err(SignDocError::from)
+    }
+
+    /// Verify a signature over the sign bytes, using the scheme indicated by
+    /// the public key, and that the public key belongs to the signer.
+    pub fn verify(&self, pubkey: &PublicKey, signature: &[u8]) -> Result<(), SignDocError> {
+        let address = address::derive_from_pubkey(pubkey)?;
+        for msg in &self.msgs {
+            if address.as_str() != msg.value.signer {
+                return Err(SignDocError::signer_mismatch(&msg.value.signer, address));
+            }
+        }
+
+        pubkey.verify(&self.sign_bytes()?, signature).map_err(SignDocError::from)
+    }
+}
+
+/// Serialize a data into canonical JSON bytes, with the following rules:
+///
+/// - no whitespace outside of strings;
+/// - object keys are sorted by their UTF-8 bytes in ascending order, at all
+///   levels of nesting;
+/// - strings are escaped in the minimal form, i.e. only `"`, `\`, and control
+///   characters are escaped; `\b`, `\f`, `\n`, `\r`, `\t` use their short
+///   forms, other control characters use lowercase `\u00xx`. Non-ASCII
+///   characters are not escaped;
+/// - integers are written in decimal without exponent or leading zeros.
+///
+/// Note that the key ordering differs from JavaScript's default string ordering,
+/// which compares UTF-16 code units, for keys containing characters outside of
+/// the Basic Multilingual Plane. Apart from that, this is equivalent to
+/// `JSON.stringify` after recursively sorting the object keys.
+pub fn to_canonical_json(data: &impl Serialize) -> serde_json::Result<Vec<u8>> {
+    let value = serde_json::to_value(data)?;
+    let mut bytes = vec![];
+    write_canonical_json(&value, &mut bytes)?;
+    Ok(bytes)
+}
+
+fn write_canonical_json(value: &Value, bytes: &mut Vec<u8>) -> serde_json::Result<()> {
+    match value {
+        Value::Array(values) => {
+            bytes.push(b'[');
+            for (i, value) in values.iter().enumerate() {
+                if i > 0 {
+                    bytes.push(b',');
+                }
+                write_canonical_json(value, bytes)?;
+            }
+            bytes.push(b']');
+        },
+        Value::Object(map) => {
+            let mut entries = map.iter().collect::<Vec<_>>();
+            entries.sort_by(|(a, _), (b, _)| a.as_bytes().cmp(b.as_bytes()));
+            bytes.push(b'{');
+            for (i, (key, value)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    bytes.push(b',');
+                }
+                serde_json::to_writer(&mut *bytes, key)?;
+                bytes.push(b':');
+                write_canonical_json(value, bytes)?;
+            }
+            bytes.push(b'}');
+        },
+        // null, booleans, numbers, and strings are written in the compact form
+        _ => serde_json::to_writer(bytes, value)?,
+    }
+    Ok(())
+}
+
+#[derive(Debug, Error)]
+pub enum SignDocError {
+    #[error(transparent)]
+    Serde(#[from] serde_json::Error),
+
+    #[error(transparent)]
+    PublicKey(#[from] PublicKeyError),
+
+    #[error(transparent)]
+    Address(#[from] AddressError),
+
+    #[error("signer {signer} does not match the public key's address {address}")]
+    SignerMismatch {
+        signer: String,
+        address: String,
+    },
+}
+
+impl SignDocError {
+    pub fn signer_mismatch(signer: impl Into<String>, address: impl Into<String>) -> Self {
+        Self::SignerMismatch {
+            signer: signer.into(),
+            address: address.into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+    use crate::address;
+
+    /// Test vectors generated by `testdata/generate_sign_doc.js`, which uses an
+    /// independent implementation of the canonical JSON encoding
+    #[derive(Deserialize)]
+    struct TestVector {
+        description: String,
+        pubkey: PublicKey,
+        body: TxBody,
+        sign_bytes: String,
+        signature: Binary,
+    }
+
+    fn test_vectors() -> Vec<TestVector> {
+        serde_json::from_str(include_str!("../testdata/sign_doc.json")).unwrap()
+    }
+
+    #[test]
+    fn sign_bytes() {
+        for vector in test_vectors() {
+            let sign_bytes = SignDoc::new(vector.body).sign_bytes().unwrap();
+            assert_eq!(
+                String::from_utf8(sign_bytes).unwrap(),
+                vector.sign_bytes,
+                "{}",
+                vector.description,
+            );
+        }
+    }
+
+    #[test]
+    fn verifying_signatures() {
+        for vector in test_vectors() {
+            let sender = address::derive_from_pubkey(&vector.pubkey).unwrap();
+            assert_eq!(sender, vector.body.sender, "{}", vector.description);
+
+            let mut sign_doc = SignDoc::new(vector.body);
+            assert!(
+                sign_doc.verify(&vector.pubkey, &vector.signature).is_ok(),
+                "{}",
+                vector.description,
+            );
+
+            // tampering with the body should invalidate the signature
+            sign_doc.body.sequence = sign_doc.body.sequence.wrapping_add(1);
+            assert!(
+                sign_doc.verify(&vector.pubkey, &vector.signature).is_err(),
+                "{}",
+                vector.description,
+            );
+        }
+    }
+
+    #[test]
+    fn offchain_sign_bytes() {
+        let sign_doc = OffchainSignDoc::new("cw1signer", b"hello".to_vec());
+        let sign_bytes = sign_doc.sign_bytes().unwrap();
+        assert_eq!(
+            String::from_utf8(sign_bytes.clone()).unwrap(),
+            r#"{"account_number":"0","chain_id":"","fee":{"amount":[],"gas":"0"},"memo":"","msgs":[{"type":"sign/MsgSignData","value":{"data":"aGVsbG8=","signer":"cw1signer"}}],"sequence":"0"}"#,
+        );
+
+        // the sign bytes can't be mistaken for those of a tx
+        assert!(serde_json::from_slice::<SignDoc>(&sign_bytes).is_err());
+    }
+
+    #[test]
+    fn verifying_offchain_signatures() {
+        use k256::ecdsa::{signature::Signer, Signature, SigningKey};
+
+        let sk = SigningKey::from_bytes(&[1u8; 32]).unwrap();
+        let pubkey = PublicKey::Secp256k1(sk.verifying_key().to_bytes().to_vec().into());
+        let signer = address::derive_from_pubkey(&pubkey).unwrap();
+
+        let sign_doc = OffchainSignDoc::new(&signer, b"hello".to_vec());
+        let signature: Signature = sk.sign(&sign_doc.sign_bytes().unwrap());
+        assert!(sign_doc.verify(&pubkey, signature.as_ref()).is_ok());
+
+        // tampering with the data should invalidate the signature
+        let tampered = OffchainSignDoc::new(&signer, b"world".to_vec());
+        assert!(matches!(
+            tampered.verify(&pubkey, signature.as_ref()),
+            Err(SignDocError::PublicKey(_)),
+        ));
+
+        // the public key must belong to the signer
+        let other = OffchainSignDoc::new("cw1other", b"hello".to_vec());
+        assert!(matches!(
+            other.verify(&pubkey, signature.as_ref()),
+            Err(SignDocError::SignerMismatch {
+                ..
+            }),
+        ));
+    }
 }
@@ -0,0 +1,244 @@
+// Generate the SignDoc test vectors in `sign_doc.json`, independently of the Rust implementation.
+//
+// Usage: node generate_sign_doc.js > sign_doc.json
+//
+// Only uses Node.js built-in modules. ECDSA signatures are randomized, so re-running this script
+// produces different (but equally valid) signatures. They are normalized to low-S form, as
+// required by the `k256` library.
+//
+// Integers are represented as `BigInt`s, so that values beyond 2^53 (e.g. the sequence in the third
+// case) are serialized exactly.
+
+const crypto = require("crypto");
+
+// ---------------------------------- canonical JSON ----------------------------------
+
+// Object keys are sorted by their UTF-8 bytes. This differs from JavaScript's default string
+// ordering, which compares UTF-16 code units, for keys containing characters outside of the Basic
+// Multilingual Plane.
+function compareUtf8(a, b) {
+  return Buffer.compare(Buffer.from(a, "utf8"), Buffer.from(b, "utf8"));
+}
+
+function canonical(value) {
+  if (typeof value === "bigint") {
+    return value.toString();
+  }
+  if (Array.isArray(value)) {
+    return "[" + value.map(canonical).join(",") + "]";
+  }
+  if (value !== null && typeof value === "object") {
+    const keys = Object.keys(value).sort(compareUtf8);
+    return "{" + keys.map((key) => JSON.stringify(key) + ":" + canonical(value[key])).join(",") + "}";
+  }
+  // null, booleans, and strings
+  return JSON.stringify(value);
+}
+
+// Like `JSON.stringify(value, null, 2)`, but supports `BigInt`s and preserves key order.
+function pretty(value, indent = "") {
+  const inner = indent + "  ";
+  if (typeof value === "bigint") {
+    return value.toString();
+  }
+  if (Array.isArray(value)) {
+    if (value.length === 0) return "[]";
+    return "[\n" + value.map((v) => inner + pretty(v, inner)).join(",\n") + "\n" + indent + "]";
+  }
+  if (value !== null && typeof value === "object") {
+    const keys = Object.keys(value);
+    if (keys.length === 0) return "{}";
+    const entries = keys.map((key) => inner + JSON.stringify(key) + ": " + pretty(value[key], inner));
+    return "{\n" + entries.join(",\n") + "\n" + indent + "}";
+  }
+  return JSON.stringify(value);
+}
+
+// ---------------------------------- addresses ----------------------------------
+
+const CHARSET = "qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+
+function polymod(values) {
+  const gen = [0x3b6a57b2, 0x26508e6d, 0x1ea119fa, 0x3d4233dd, 0x2a1462b3];
+  let chk = 1;
+  for (const v of values) {
+    const b = chk >>> 25;
+    chk = (((chk & 0x1ffffff) << 5) ^ v) >>> 0;
+    for (let i = 0; i < 5; i++) {
+      if ((b >>> i) & 1) chk = (chk ^ gen[i]) >>> 0;
+    }
+  }
+  return chk;
+}
+
+function hrpExpand(hrp) {
+  const chars = [...hrp].map((c) => c.charCodeAt(0));
+  return [...chars.map((c) => c >> 5), 0, ...chars.map((c) => c & 31)];
+}
+
+function convertBits(data, fromBits, toBits) {
+  let acc = 0;
+  let bits = 0;
+  const ret = [];
+  const maxv = (1 << toBits) - 1;
+  for (const value of data) {
+    acc = ((acc << fromBits) | value) & 0xffffff;
+    bits += fromBits;
+    while (bits >= toBits) {
+      bits -= toBits;
+      ret.push((acc >> bits) & maxv);
+    }
+  }
+  if (bits > 0) ret.push((acc << (toBits - bits)) & maxv);
+  return ret;
+}
+
+function bech32Encode(hrp, data) {
+  const words = convertBits(data, 8, 5);
+  const poly = polymod([...hrpExpand(hrp), ...words, 0, 0, 0, 0, 0, 0]) ^ 1;
+  const checksum = [0, 1, 2, 3, 4, 5].map((i) => (poly >>> (5 * (5 - i))) & 31);
+  return hrp + "1" + [...words, ...checksum].map((x) => CHARSET[x]).join("");
+}
+
+const sha256 = (data) => crypto.createHash("sha256").update(data).digest();
+
+const TYPES = {
+  secp256k1: "cosmos.crypto.secp256k1.PubKey",
+  ed25519: "cosmos.crypto.ed25519.PubKey",
+  secp256r1: "cosmos.crypto.secp256r1.PubKey",
+};
+
+// ADR-028 address of a public key
+function address(algo, pk) {
+  const pre = Buffer.concat([Buffer.from(TYPES[algo]), sha256(pk)]);
+  return bech32Encode("cw", sha256(pre));
+}
+
+// ---------------------------------- keys and signatures ----------------------------------
+
+const N = {
+  secp256k1: 0xfffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141n,
+  secp256r1: 0xffffffff00000000ffffffffffffffffbce6faada7179e84f3b9cac2fc632551n,
+};
+
+const CURVES = {
+  secp256k1: { ecdh: "secp256k1", jwk: "secp256k1" },
+  secp256r1: { ecdh: "prime256v1", jwk: "P-256" },
+};
+
+// PKCS#8 DER prefix of an ed25519 private key; the 32-byte seed follows
+const ED25519_PKCS8_PREFIX = Buffer.from("302e020100300506032b657004220420", "hex");
+
+const b64url = (bytes) => Buffer.from(bytes).toString("base64url");
+
+function keypair(algo, seed) {
+  const digest = sha256(seed);
+  if (algo === "ed25519") {
+    const sk = crypto.createPrivateKey({
+      key: Buffer.concat([ED25519_PKCS8_PREFIX, digest]),
+      format: "der",
+      type: "pkcs8",
+    });
+    const spki = crypto.createPublicKey(sk).export({ format: "der", type: "spki" });
+    return { sk, pk: spki.subarray(spki.length - 32) };
+  }
+
+  const d = BigInt("0x" + digest.toString("hex")) % N[algo];
+  const dBytes = Buffer.from(d.toString(16).padStart(64, "0"), "hex");
+  const ecdh = crypto.createECDH(CURVES[algo].ecdh);
+  ecdh.setPrivateKey(dBytes);
+  const uncompressed = ecdh.getPublicKey();
+  const sk = crypto.createPrivateKey({
+    key: {
+      kty: "EC",
+      crv: CURVES[algo].jwk,
+      d: b64url(dBytes),
+      x: b64url(uncompressed.subarray(1, 33)),
+      y: b64url(uncompressed.subarray(33, 65)),
+    },
+    format: "jwk",
+  });
+  return { sk, pk: ecdh.getPublicKey(null, "compressed") };
+}
+
+function sign(algo, sk, msg) {
+  if (algo === "ed25519") {
+    return crypto.sign(null, msg, sk);
+  }
+  const sig = crypto.sign("sha256", msg, { key: sk, dsaEncoding: "ieee-p1363" });
+  const r = sig.subarray(0, 32);
+  let s = BigInt("0x" + sig.subarray(32).toString("hex"));
+  if (s > N[algo] / 2n) {
+    s = N[algo] - s;
+  }
+  return Buffer.concat([r, Buffer.from(s.toString(16).padStart(64, "0"), "hex")]);
+}
+
+// ---------------------------------- test cases ----------------------------------
+
+const b64 = (bytes) => Buffer.from(bytes).toString("base64");
+
+const cases = [
+  ["secp256k1", "execute a contract; nested keys out of order", (sender) => ({
+    sender, chain_id: "cw-dev-1", sequence: 1n,
+    timeout_height: null, timeout_timestamp: null,
+    fee: [], fee_payer: null, fee_payer_sequence: null, gas_limit: null,
+    msgs: [{ execute: {
+      contract: address("secp256k1", Buffer.from("contract")),
+      msg: { transfer: { recipient: "alice", amount: "12345" }, memo: null },
+      funds: [{ denom: "uatom", amount: "100" }],
+    } }],
+  })],
+  ["ed25519", "instantiate a contract; non-ASCII and escaped characters in strings", (sender) => ({
+    sender, chain_id: "cw-dev-1", sequence: 7n,
+    timeout_height: 1000n, timeout_timestamp: null,
+    fee: [{ denom: "uatom", amount: "2500" }], fee_payer: null, fee_payer_sequence: null,
+    gas_limit: 250000n,
+    msgs: [{ instantiate: {
+      code_id: 2n, label: "ラベル-🚀",
+      msg: { zeta: [1n, 2n, { b: true, a: false }], Alpha: "line1\nline2\t\"quoted\"\\\u0001", _: {} },
+      funds: [], admin: null,
+    } }],
+  })],
+  ["secp256r1", "store code and migrate; multiple messages", (sender) => ({
+    sender, chain_id: "cw-test-2", sequence: 18446744073709551615n,
+    timeout_height: null, timeout_timestamp: "1700000000000000000",
+    fee: [
+      { denom: "uatom", amount: "1" },
+      { denom: "factory/cw1xyz/ufoo", amount: "340282366920938463463374607431768211455" },
+    ],
+    fee_payer: address("ed25519", Buffer.from("fee payer")), fee_payer_sequence: 42n, gas_limit: null,
+    msgs: [
+      { store_code: { wasm_byte_code: b64(Buffer.from("0061736d01000000", "hex")) } },
+      { migrate: { contract: address("secp256k1", Buffer.from("contract")), code_id: 3n, msg: {} } },
+    ],
+  })],
+  // UTF-16 ordering would put the keys starting with characters outside of the BMP (encoded as
+  // surrogates, 0xd800-0xdfff) before "ｱ" (U+FF71); UTF-8 ordering puts them after
+  ["secp256k1", "keys outside of the Basic Multilingual Plane", (sender) => ({
+    sender, chain_id: "cw-dev-1", sequence: 2n,
+    timeout_height: null, timeout_timestamp: null,
+    fee: [], fee_payer: null, fee_payer_sequence: null, gas_limit: null,
+    msgs: [{ execute: {
+      contract: address("secp256k1", Buffer.from("contract")),
+      msg: { "🚀": 1n, "ｱ": 2n, "𝔸": 3n, "é": 4n, "z": 5n, "\uffff": 6n },
+      funds: [],
+    } }],
+  })],
+];
+
+const vectors = cases.map(([algo, description, makeBody]) => {
+  const { sk, pk } = keypair(algo, "cw-sdk test vector " + algo);
+  const body = makeBody(address(algo, pk));
+  const signBytes = canonical({ body });
+  const signature = sign(algo, sk, Buffer.from(signBytes, "utf8"));
+  return {
+    description,
+    pubkey: { [algo]: b64(pk) },
+    body,
+    sign_bytes: signBytes,
+    signature: b64(signature),
+  };
+});
+
+console.log(pretty(vectors));
@@ -1,14 +1,25 @@
-use std::sync::mpsc::{channel, Receiver, Sender};
+use std::{
+    collections::HashSet,
+    sync::mpsc::{channel, Receiver, Sender},
+};
 
 use cosmwasm_std::{Attribute as WasmAttribute, BlockInfo, Event as WasmEvent, Timestamp};
 use cw_sdk::{GenesisState, SdkQuery, Tx};
-use tendermint_proto::abci::{self, Event, EventAttribute};
+use cw_store::SnapshotInfo;
+use tendermint_proto::abci::{
+    self, response_apply_snapshot_chunk, response_offer_snapshot, Event, EventAttribute,
+};
 
 use crate::AppCommand;
 
 #[derive(Clone, Debug)]
 pub struct App {
     pub cmd_tx: Sender<AppCommand>,
+
+    /// Event attributes to be indexed by Tendermint, in the format
+    /// `{event_type}.{attribute_key}`, e.g. `wasm._contract_address`.
+    /// If empty, all attributes are indexed.
+    pub index_events: HashSet<String>,
 }
 
 impl App {
@@ -41,14 +52,14 @@ impl tendermint_abci::Application for App {
             &result_rx,
         );
 
-        let (height, app_hash) = result.unwrap_or_else(|err| {
+        let (height, app_hash, app_version) = result.unwrap_or_else(|err| {
             panic!("ABCI Info request failed with error: {err}");
         });
 
         abci::ResponseInfo {
             data: env!("CARGO_PKG_NAME").into(),
             version: env!("CARGO_PKG_VERSION").into(),
-            app_version: 1,
+            app_version,
             last_block_height: height,
             last_block_app_hash: app_hash.to_vec().into(),
         }
@@ -116,9 +127,7 @@ impl tendermint_abci::Application for App {
                         ..Default::default()
                     },
                     Err(error) => abci::ResponseQuery {
-                        // TODO: we need to define error codes instead of using
-                        // `1` for all errors
-                        code: 1,
+                        code: error.code(),
                         log: error.to_string(),
                         ..Default::default()
                     },
@@ -152,8 +161,41 @@ impl tendermint_abci::Application for App {
     }
 
     /// Check the given transaction before putting it into the local mempool.
-    fn check_tx(&self, _request: abci::RequestCheckTx) -> abci::ResponseCheckTx {
-        Default::default()
+    fn check_tx(&self, request: abci::RequestCheckTx) -> abci::ResponseCheckTx {
+        let (result_tx, result_rx) = channel();
+
+        // unlike in DeliverTx, a malformed tx here is simply rejected from the
+        // mempool, instead of halting the node
+        let tx: Tx = match serde_json::from_slice(&request.tx) {
+            Ok(tx) => tx,
+            Err(err) => {
+                return abci::ResponseCheckTx {
+                    code: 1,
+                    log: format!("failed to deserialize tx: {err}"),
+                    ..Default::default()
+                };
+            },
+        };
+
+        let result = self.execute_command(
+            AppCommand::CheckTx {
+                tx,
+                result_tx,
+            },
+            &result_rx,
+        );
+
+        match result {
+            Ok(()) => abci::ResponseCheckTx {
+                code: 0,
+                ..Default::default()
+            },
+            Err(error) => abci::ResponseCheckTx {
+                code: error.code(),
+                log: error.to_string(),
+                ..Default::default()
+            },
+        }
     }
 
     /// Signals the beginning of a new block, prior to any `DeliverTx` calls.
@@ -189,7 +231,7 @@ impl tendermint_abci::Application for App {
         });
 
         abci::ResponseBeginBlock {
-            events: wasm_event_to_abci(events),
+            events: wasm_event_to_abci(events, &self.index_events),
         }
     }
 
@@ -215,11 +257,11 @@ impl tendermint_abci::Application for App {
             Ok(events) => abci::ResponseDeliverTx {
                 code: 0,
                 log: serde_json::to_string(&events).unwrap(),
-                events: wasm_event_to_abci(events),
+                events: wasm_event_to_abci(events, &self.index_events),
                 ..Default::default()
             },
             Err(error) => abci::ResponseDeliverTx {
-                code: 1,
+                code: error.code(),
                 log: error.to_string(),
                 ..Default::default()
             },
@@ -253,29 +295,152 @@ impl tendermint_abci::Application for App {
             retain_height: height,
         }
     }
+
+    /// Used during state sync to discover available snapshots on peers.
+    fn list_snapshots(&self) -> abci::ResponseListSnapshots {
+        let (result_tx, result_rx) = channel();
+
+        let result = self.execute_command(
+            AppCommand::ListSnapshots {
+                result_tx,
+            },
+            &result_rx,
+        );
+
+        // failing to list snapshots only means this node can't serve them to
+        // peers, so we respond with an empty list instead of panicking
+        let snapshots = result.unwrap_or_default();
+
+        abci::ResponseListSnapshots {
+            snapshots: snapshots
+                .into_iter()
+                .map(|snapshot| abci::Snapshot {
+                    height: snapshot.height,
+                    format: snapshot.format,
+                    chunks: snapshot.chunks,
+                    hash: snapshot.hash.into(),
+                    metadata: Default::default(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Called when bootstrapping the node using state sync.
+    fn offer_snapshot(&self, request: abci::RequestOfferSnapshot) -> abci::ResponseOfferSnapshot {
+        let (result_tx, result_rx) = channel();
+
+        let Some(snapshot) = request.snapshot else {
+            return abci::ResponseOfferSnapshot {
+                result: response_offer_snapshot::Result::Reject as i32,
+            };
+        };
+
+        let result = self.execute_command(
+            AppCommand::OfferSnapshot {
+                snapshot: SnapshotInfo {
+                    height: snapshot.height,
+                    format: snapshot.format,
+                    chunks: snapshot.chunks,
+                    hash: snapshot.hash.to_vec(),
+                },
+                app_hash: request.app_hash.to_vec(),
+                result_tx,
+            },
+            &result_rx,
+        );
+
+        let result = match result {
+            Ok(()) => response_offer_snapshot::Result::Accept,
+            Err(cw_state_machine::error::Error::UnsupportedSnapshotFormat {
+                ..
+            }) => response_offer_snapshot::Result::RejectFormat,
+            Err(_) => response_offer_snapshot::Result::Reject,
+        };
+
+        abci::ResponseOfferSnapshot {
+            result: result as i32,
+        }
+    }
+
+    /// Used during state sync to retrieve snapshot chunks from peers.
+    fn load_snapshot_chunk(
+        &self,
+        request: abci::RequestLoadSnapshotChunk,
+    ) -> abci::ResponseLoadSnapshotChunk {
+        let (result_tx, result_rx) = channel();
+
+        let result = self.execute_command(
+            AppCommand::LoadSnapshotChunk {
+                height: request.height,
+                format: request.format,
+                chunk: request.chunk,
+                result_tx,
+            },
+            &result_rx,
+        );
+
+        // an empty chunk signals to the peer that the chunk is not available
+        abci::ResponseLoadSnapshotChunk {
+            chunk: result.ok().flatten().unwrap_or_default().into(),
+        }
+    }
+
+    /// Apply the given snapshot chunk to the application's state.
+    fn apply_snapshot_chunk(
+        &self,
+        request: abci::RequestApplySnapshotChunk,
+    ) -> abci::ResponseApplySnapshotChunk {
+        let (result_tx, result_rx) = channel();
+
+        let result = self.execute_command(
+            AppCommand::ApplySnapshotChunk {
+                chunk: request.chunk.to_vec(),
+                result_tx,
+            },
+            &result_rx,
+        );
+
+        match result {
+            Ok(_) => abci::ResponseApplySnapshotChunk {
+                result: response_apply_snapshot_chunk::Result::Accept as i32,
+                ..Default::default()
+            },
+            // the chunk failed verification against the app hash. the snapshot
+            // is discarded, and the peer that sent the chunk is banned
+            Err(_) => abci::ResponseApplySnapshotChunk {
+                result: response_apply_snapshot_chunk::Result::RejectSnapshot as i32,
+                reject_senders: vec![request.sender],
+                ..Default::default()
+            },
+        }
+    }
 }
 
 /// Casting CosmWasm event attributes into ABCI event attributes
-fn wasm_attrs_to_abci(wasm_attrs: Vec<WasmAttribute>) -> Vec<EventAttribute> {
+fn wasm_attrs_to_abci(
+    ty: &str,
+    wasm_attrs: Vec<WasmAttribute>,
+    index_events: &HashSet<String>,
+) -> Vec<EventAttribute> {
     wasm_attrs
         .into_iter()
         .map(|attr| EventAttribute {
+            // Tendermint's tx indexer only indexes attributes marked as such,
+            // which can then be used in tx search queries
+            index: index_events.is_empty() || index_events.contains(&format!("{ty}.{}", attr.key)),
             key: attr.key.into_bytes().into(),
             value: attr.value.into_bytes().into(),
-            // Not sure what "index" means, but Go SDK returns `true` for all attributes,
-            // so I'll do the same here =)
-            index: true,
         })
         .collect()
 }
 
 /// Casting CosmWasm events into ABCI events
-fn wasm_event_to_abci(wasm_events: Vec<WasmEvent>) -> Vec<Event> {
+fn wasm_event_to_abci(wasm_events: Vec<WasmEvent>, index_events: &HashSet<String>) -> Vec<Event> {
     wasm_events
         .into_iter()
         .map(|event| Event {
+            attributes: wasm_attrs_to_abci(&event.ty, event.attributes, index_events),
             r#type: event.ty,
-            attributes: wasm_attrs_to_abci(event.attributes),
         })
         .collect()
 }
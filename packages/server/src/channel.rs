@@ -4,15 +4,16 @@ use cosmwasm_std::{Binary, BlockInfo, Event};
 
 use cw_sdk::{hash::HASH_LENGTH, GenesisState, SdkQuery, Tx};
 use cw_state_machine::error::Result as StateMachineResult;
+use cw_store::SnapshotInfo;
 
 /// The ABCI server and the driver maintains a channel between them, and
 /// communicate by sending commands.
 /// This enum defines the commands allowed to be transmitted through the channel.
 #[derive(Debug, Clone)]
 pub enum AppCommand {
-    /// Returns the last committed block height and app hash
+    /// Returns the last committed block height, app hash, and app version
     Info {
-        result_tx: Sender<StateMachineResult<(i64, [u8; HASH_LENGTH])>>,
+        result_tx: Sender<StateMachineResult<(i64, [u8; HASH_LENGTH], u64)>>,
     },
 
     /// Provide the genesis state, returns the app hash.
@@ -35,6 +36,12 @@ pub enum AppCommand {
         result_tx: Sender<StateMachineResult<Vec<Event>>>,
     },
 
+    /// Provide a tx, returns whether the tx can be admitted into the mempool.
+    CheckTx {
+        tx: Tx,
+        result_tx: Sender<StateMachineResult<()>>,
+    },
+
     /// Provide a tx, returns the events emitted during tx execution.
     DeliverTx {
         tx: Tx,
@@ -45,4 +52,33 @@ pub enum AppCommand {
     Commit {
         result_tx: Sender<StateMachineResult<(i64, [u8; HASH_LENGTH])>>,
     },
+
+    /// Returns the snapshots available to be served to state syncing nodes.
+    ListSnapshots {
+        result_tx: Sender<StateMachineResult<Vec<SnapshotInfo>>>,
+    },
+
+    /// Provide a snapshot offered by a peer and the trusted app hash at the
+    /// snapshot's height, returns whether the snapshot is accepted.
+    OfferSnapshot {
+        snapshot: SnapshotInfo,
+        app_hash: Vec<u8>,
+        result_tx: Sender<StateMachineResult<()>>,
+    },
+
+    /// Provide the snapshot height, format, and chunk index, returns the chunk
+    /// if found.
+    LoadSnapshotChunk {
+        height: u64,
+        format: u32,
+        chunk: u32,
+        result_tx: Sender<StateMachineResult<Option<Vec<u8>>>>,
+    },
+
+    /// Provide a chunk of the snapshot being restored, returns whether the
+    /// restoration is complete.
+    ApplySnapshotChunk {
+        chunk: Vec<u8>,
+        result_tx: Sender<StateMachineResult<bool>>,
+    },
 }
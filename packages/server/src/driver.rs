@@ -1,8 +1,10 @@
 use std::sync::mpsc::Receiver;
 
-use cw_state_machine::StateMachine;
+use tracing::error;
 
-use crate::AppCommand;
+use cw_state_machine::{error::Result as StateMachineResult, StateMachine};
+
+use crate::{AppCommand, ViewPublisher};
 
 /// The driver is a wrapper around the actual state machine.
 /// It maintains a channel with the ABCI server, and performs actions or queries
@@ -10,10 +12,14 @@ use crate::AppCommand;
 pub struct AppDriver {
     pub state_machine: StateMachine,
     pub cmd_rx: Receiver<AppCommand>,
+    /// Publishes views of the committed state to the REST server, if enabled
+    pub views: Option<ViewPublisher>,
 }
 
 impl AppDriver {
     pub fn run(&mut self) {
+        self.publish_view(ViewPublisher::publish);
+
         loop {
             match self.cmd_rx.recv().unwrap() {
                 AppCommand::Info {
@@ -32,14 +38,74 @@ impl AppDriver {
                     block,
                     result_tx,
                 } => result_tx.send(self.state_machine.begin_block(block)).unwrap(),
+                AppCommand::CheckTx {
+                    tx,
+                    result_tx,
+                } => result_tx.send(self.state_machine.check_tx(&tx)).unwrap(),
                 AppCommand::DeliverTx {
                     tx,
                     result_tx,
                 } => result_tx.send(self.state_machine.deliver_tx(tx)).unwrap(),
                 AppCommand::Commit {
                     result_tx,
-                } => result_tx.send(self.state_machine.commit()).unwrap(),
+                } => {
+                    let result = self.state_machine.commit();
+                    if result.is_ok() {
+                        self.publish_view(ViewPublisher::maybe_publish);
+                    }
+                    result_tx.send(result).unwrap();
+                },
+                AppCommand::ListSnapshots {
+                    result_tx,
+                } => result_tx.send(self.state_machine.list_snapshots()).unwrap(),
+                AppCommand::OfferSnapshot {
+                    snapshot,
+                    app_hash,
+                    result_tx,
+                } => result_tx
+                    .send(self.state_machine.offer_snapshot(snapshot, &app_hash))
+                    .unwrap(),
+                AppCommand::LoadSnapshotChunk {
+                    height,
+                    format,
+                    chunk,
+                    result_tx,
+                } => result_tx
+                    .send(self.state_machine.load_snapshot_chunk(height, format, chunk))
+                    .unwrap(),
+                AppCommand::ApplySnapshotChunk {
+                    chunk,
+                    result_tx,
+                } => {
+                    let result = self.state_machine.apply_snapshot_chunk(&chunk);
+                    // the store has been replaced once the last chunk is applied
+                    if let Ok(true) = result {
+                        self.publish_view(ViewPublisher::publish);
+                    }
+                    result_tx.send(result).unwrap();
+                },
             }
         }
     }
+
+    /// Publish a view of the committed state, if enabled, using the given
+    /// method of the publisher. Failing to do so only means queries are served
+    /// from a stale view, so the error is logged instead of halting the chain.
+    fn publish_view<T>(
+        &self,
+        publish: impl FnOnce(&ViewPublisher, &StateMachine, i64) -> StateMachineResult<T>,
+    ) {
+        let Some(views) = &self.views else {
+            return;
+        };
+
+        let result = self
+            .state_machine
+            .info()
+            .and_then(|(height, _, _)| publish(views, &self.state_machine, height));
+
+        if let Err(err) = result {
+            error!(target: "Failed to publish query view", reason = err.to_string());
+        }
+    }
 }
@@ -1,7 +1,13 @@
 mod app;
 mod channel;
 mod driver;
+mod metrics;
+mod rest;
+mod view;
 
 pub use app::*;
 pub use channel::*;
 pub use driver::*;
+pub use metrics::*;
+pub use rest::*;
+pub use view::*;
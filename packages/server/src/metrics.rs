@@ -0,0 +1,31 @@
+use tiny_http::{Header, Response, Server};
+use tracing::error;
+
+use cw_state_machine::metrics::METRICS;
+
+/// Serve the state machine's metrics in the Prometheus text format over HTTP.
+/// Requests to any path are answered with the metrics.
+///
+/// This blocks the current thread, so it should be spawned in a thread of its
+/// own. The metrics can be read from any thread, unlike the state machine.
+pub fn serve_metrics(listen_addr: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let server = Server::http(listen_addr)?;
+
+    for request in server.incoming_requests() {
+        let response = match METRICS.encode() {
+            Ok(body) => Response::from_data(body).with_header(
+                Header::from_bytes("Content-Type", "text/plain; version=0.0.4").unwrap(),
+            ),
+            Err(err) => {
+                error!(target: "Failed to encode metrics", reason = err.to_string());
+                Response::from_string(err.to_string()).with_status_code(500)
+            },
+        };
+
+        if let Err(err) = request.respond(response) {
+            error!(target: "Failed to respond to metrics request", reason = err.to_string());
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,452 @@
+use std::{collections::HashMap, io::Read, str::FromStr};
+
+use cosmwasm_std::{Binary, Order, StdError};
+use percent_encoding::percent_decode_str;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tiny_http::{Header, Method, Request, Response, Server};
+use tracing::error;
+use url::Url;
+
+use cw_sdk::{SdkQuery, Tx};
+use cw_state_machine::error::Error as StateMachineError;
+
+use crate::QueryView;
+
+/// The largest request body accepted, in bytes. This is large enough for
+/// simulating txs that upload wasm byte codes.
+pub const MAX_BODY_SIZE: usize = 4 * 1024 * 1024;
+
+/// Serve the queries of the state machine as a JSON REST API over HTTP. The
+/// queries are served from views of the committed state published by the
+/// AppDriver, so they don't hold up the processing of blocks.
+///
+/// Routes:
+///
+/// - `GET /info`
+/// - `GET /accounts`, `GET /accounts/{address}`
+/// - `GET /contracts`, optionally filtered by `code_id` or `admin`
+/// - `GET /contracts/{label}`, `GET /contracts/{contract}/history`
+/// - `GET /codes`, `GET /codes/{code_id}`
+/// - `GET /fee_allowances/{granter}`, `GET /fee_allowances/{granter}/{grantee}`
+/// - `GET /grants/{granter}`
+/// - `GET /wasm/{contract}/raw`, `GET /wasm/{contract}/raw/{hex-encoded key}`
+/// - `GET /wasm/{contract}/smart/{base64-encoded query msg}`
+/// - `POST /wasm/{contract}/smart`, with the query msg as the body
+/// - `POST /simulate`, with `{"tx": .., "skip_signature": ..}` as the body
+/// - `GET /upgrade/plan`, `GET /upgrade/applied/{name}`
+///
+/// Routes that enumerate items are paginated with the `start_after` and `limit`
+/// query parameters. Request bodies are limited to `MAX_BODY_SIZE` bytes.
+/// Failed requests are answered with an appropriate status code and a body of
+/// the form `{"code": .., "error": ..}`, where `code` is the state machine's
+/// error code, or 1 if the request itself is invalid.
+///
+/// This blocks the current thread, so it should be spawned in a thread of its
+/// own.
+pub fn serve_rest(
+    listen_addr: &str,
+    mut view: QueryView,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let server = Server::http(listen_addr)?;
+
+    for mut request in server.incoming_requests() {
+        let (status, body) = match handle_request(&mut request, &mut view) {
+            Ok(body) => (200, body),
+            Err(err) => (err.status, serde_json::to_vec(&err.body)?),
+        };
+
+        let response = Response::from_data(body)
+            .with_status_code(status)
+            .with_header(Header::from_bytes("Content-Type", "application/json").unwrap());
+
+        if let Err(err) = request.respond(response) {
+            error!(target: "Failed to respond to REST request", reason = err.to_string());
+        }
+    }
+
+    Ok(())
+}
+
+/// The body of the POST /simulate request
+#[derive(Deserialize)]
+struct SimulateRequest {
+    tx: Tx,
+    #[serde(default)]
+    skip_signature: bool,
+}
+
+#[derive(Debug)]
+struct RestError {
+    status: u16,
+    body: RestErrorBody,
+}
+
+#[derive(Debug, Serialize)]
+struct RestErrorBody {
+    code: u32,
+    error: String,
+}
+
+impl RestError {
+    fn bad_request(error: impl Into<String>) -> Self {
+        Self {
+            status: 400,
+            body: RestErrorBody {
+                code: 1,
+                error: error.into(),
+            },
+        }
+    }
+
+    fn not_found(error: impl Into<String>) -> Self {
+        Self {
+            status: 404,
+            body: RestErrorBody {
+                code: 1,
+                error: error.into(),
+            },
+        }
+    }
+
+    fn payload_too_large() -> Self {
+        Self {
+            status: 413,
+            body: RestErrorBody {
+                code: 1,
+                error: format!("request body must not be larger than {MAX_BODY_SIZE} bytes"),
+            },
+        }
+    }
+}
+
+impl From<StateMachineError> for RestError {
+    fn from(err: StateMachineError) -> Self {
+        let status = match &err {
+            StateMachineError::Std(StdError::NotFound {
+                ..
+            })
+            | StateMachineError::AccountNotFound {
+                ..
+            }
+            | StateMachineError::CodeNotFound {
+                ..
+            } => 404,
+            StateMachineError::QueryUnsupported => 501,
+            // failures of the node itself, rather than of the request
+            StateMachineError::Backend(_)
+            | StateMachineError::Vm(_)
+            | StateMachineError::Merk(_)
+            | StateMachineError::Snapshot(_) => 500,
+            // the request is invalid, e.g. a malformed address, or the tx to
+            // be simulated fails
+            _ => 400,
+        };
+
+        Self {
+            status,
+            body: RestErrorBody {
+                code: err.code(),
+                error: err.to_string(),
+            },
+        }
+    }
+}
+
+fn handle_request(request: &mut Request, view: &mut QueryView) -> Result<Vec<u8>, RestError> {
+    // reject bodies declared too large without reading them
+    if request.body_length().map_or(false, |length| length > MAX_BODY_SIZE) {
+        return Err(RestError::payload_too_large());
+    }
+
+    let method = request.method().clone();
+    let url = request.url().to_owned();
+    let query = route(&method, &url, request.as_reader())?;
+
+    Ok(view.query(query)?.into())
+}
+
+/// Find the query for the given method and url. The body is only read for POST
+/// routes.
+fn route(method: &Method, url: &str, body: impl Read) -> Result<SdkQuery, RestError> {
+    let url = Url::parse(&format!("http://localhost{url}"))
+        .map_err(|err| RestError::bad_request(format!("invalid url: {err}")))?;
+
+    let segments = url
+        .path_segments()
+        .into_iter()
+        .flatten()
+        .filter(|segment| !segment.is_empty())
+        .map(|segment| percent_decode_str(segment).decode_utf8_lossy().into_owned())
+        .collect::<Vec<_>>();
+    let segments = segments.iter().map(String::as_str).collect::<Vec<_>>();
+
+    let params: HashMap<String, String> = url.query_pairs().into_owned().collect();
+
+    let query = match (method, segments.as_slice()) {
+        (Method::Get, ["info"]) => SdkQuery::Info {},
+        (Method::Get, ["accounts"]) => SdkQuery::Accounts {
+            start_after: params.get("start_after").cloned(),
+            limit: parse_param(&params, "limit")?,
+        },
+        (Method::Get, ["accounts", address]) => SdkQuery::Account {
+            address: address.to_string(),
+        },
+        (Method::Get, ["contracts"]) => {
+            let start_after = params.get("start_after").cloned();
+            let limit = parse_param(&params, "limit")?;
+            match (parse_param(&params, "code_id")?, params.get("admin")) {
+                (Some(_), Some(_)) => {
+                    return Err(RestError::bad_request("code_id and admin can't both be provided"));
+                },
+                (Some(code_id), None) => SdkQuery::ContractsByCode {
+                    code_id,
+                    start_after,
+                    limit,
+                },
+                (None, Some(admin)) => SdkQuery::ContractsByAdmin {
+                    admin: admin.clone(),
+                    start_after,
+                    limit,
+                },
+                (None, None) => SdkQuery::Contracts {
+                    start_after,
+                    limit,
+                },
+            }
+        },
+        (Method::Get, ["contracts", label]) => SdkQuery::Contract {
+            label: label.to_string(),
+        },
+        (Method::Get, ["contracts", contract, "history"]) => SdkQuery::ContractHistory {
+            contract: contract.to_string(),
+        },
+        (Method::Get, ["codes"]) => SdkQuery::Codes {
+            start_after: parse_param(&params, "start_after")?,
+            limit: parse_param(&params, "limit")?,
+        },
+        (Method::Get, ["codes", code_id]) => SdkQuery::Code {
+            code_id: parse("code_id", code_id)?,
+        },
+        (Method::Get, ["fee_allowances", granter]) => SdkQuery::FeeAllowances {
+            granter: granter.to_string(),
+            start_after: params.get("start_after").cloned(),
+            limit: parse_param(&params, "limit")?,
+        },
+        (Method::Get, ["fee_allowances", granter, grantee]) => SdkQuery::FeeAllowance {
+            granter: granter.to_string(),
+            grantee: grantee.to_string(),
+        },
+        (Method::Get, ["grants", granter]) => SdkQuery::Grants {
+            granter: granter.to_string(),
+            start_after: params.get("start_after").cloned(),
+            limit: parse_param(&params, "limit")?,
+        },
+        (Method::Get, ["wasm", contract, "raw"]) => SdkQuery::WasmRawRange {
+            contract: contract.to_string(),
+            start_after: params.get("start_after").map(|key| parse_hex(key)).transpose()?,
+            end: params.get("end").map(|key| parse_hex(key)).transpose()?,
+            limit: parse_param(&params, "limit")?,
+            order: params.get("order").map(|order| parse_order(order)).transpose()?,
+        },
+        (Method::Get, ["wasm", contract, "raw", key]) => SdkQuery::WasmRaw {
+            contract: contract.to_string(),
+            key: parse_hex(key)?,
+        },
+        (Method::Get, ["wasm", contract, "smart", msg]) => {
+            let msg = Binary::from_base64(msg)
+                .map_err(|err| RestError::bad_request(format!("invalid query msg: {err}")))?;
+            SdkQuery::WasmSmart {
+                contract: contract.to_string(),
+                msg: parse_json(&msg)?,
+            }
+        },
+        (Method::Post, ["wasm", contract, "smart"]) => SdkQuery::WasmSmart {
+            contract: contract.to_string(),
+            msg: parse_json::<Value>(&read_body(body)?)?,
+        },
+        (Method::Post, ["simulate"]) => {
+            let req: SimulateRequest = parse_json(&read_body(body)?)?;
+            SdkQuery::Simulate {
+                tx: req.tx,
+                skip_signature: req.skip_signature,
+            }
+        },
+        (Method::Get, ["upgrade", "plan"]) => SdkQuery::UpgradePlan {},
+        (Method::Get, ["upgrade", "applied", name]) => SdkQuery::AppliedUpgrade {
+            name: name.to_string(),
+        },
+        (method, _) => {
+            return Err(RestError::not_found(format!("no route for {method} {}", url.path())));
+        },
+    };
+
+    Ok(query)
+}
+
+/// Read the request body, up to `MAX_BODY_SIZE` bytes. The declared length
+/// can't be trusted, e.g. with chunked encoding, so the size is checked while
+/// reading.
+fn read_body(body: impl Read) -> Result<Vec<u8>, RestError> {
+    let mut bytes = vec![];
+    body.take(MAX_BODY_SIZE as u64 + 1)
+        .read_to_end(&mut bytes)
+        .map_err(|err| RestError::bad_request(format!("failed to read request body: {err}")))?;
+
+    if bytes.len() > MAX_BODY_SIZE {
+        return Err(RestError::payload_too_large());
+    }
+
+    Ok(bytes)
+}
+
+fn parse<T: FromStr>(name: &str, value: &str) -> Result<T, RestError> {
+    value.parse().map_err(|_| RestError::bad_request(format!("invalid {name}: {value}")))
+}
+
+fn parse_param<T: FromStr>(
+    params: &HashMap<String, String>,
+    name: &str,
+) -> Result<Option<T>, RestError> {
+    params.get(name).map(|value| parse(name, value)).transpose()
+}
+
+fn parse_hex(key: &str) -> Result<Binary, RestError> {
+    hex::decode(key)
+        .map(Binary::from)
+        .map_err(|err| RestError::bad_request(format!("invalid hex-encoded key {key}: {err}")))
+}
+
+fn parse_order(order: &str) -> Result<Order, RestError> {
+    match order {
+        "ascending" => Ok(Order::Ascending),
+        "descending" => Ok(Order::Descending),
+        _ => Err(RestError::bad_request(format!("invalid order: {order}"))),
+    }
+}
+
+fn parse_json<T: for<'de> Deserialize<'de>>(bytes: &[u8]) -> Result<T, RestError> {
+    serde_json::from_slice(bytes)
+        .map_err(|err| RestError::bad_request(format!("invalid json: {err}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use serde_json::json;
+
+    use super::*;
+
+    fn get(url: &str) -> Result<SdkQuery, RestError> {
+        route(&Method::Get, url, io::empty())
+    }
+
+    fn post(url: &str, body: &[u8]) -> Result<SdkQuery, RestError> {
+        route(&Method::Post, url, body)
+    }
+
+    #[test]
+    fn routing_get_requests() {
+        assert_eq!(get("/info").unwrap(), SdkQuery::Info {});
+        assert_eq!(get("/accounts?start_after=alice&limit=10").unwrap(), SdkQuery::Accounts {
+            start_after: Some("alice".into()),
+            limit: Some(10),
+        });
+        assert_eq!(get("/contracts?code_id=2").unwrap(), SdkQuery::ContractsByCode {
+            code_id: 2,
+            start_after: None,
+            limit: None,
+        });
+        assert_eq!(get("/contracts?admin=alice").unwrap(), SdkQuery::ContractsByAdmin {
+            admin: "alice".into(),
+            start_after: None,
+            limit: None,
+        });
+        assert_eq!(get("/contracts/bank/history").unwrap(), SdkQuery::ContractHistory {
+            contract: "bank".into(),
+        });
+        assert_eq!(get("/codes/3").unwrap(), SdkQuery::Code {
+            code_id: 3,
+        });
+        assert_eq!(get("/fee_allowances/alice/bob").unwrap(), SdkQuery::FeeAllowance {
+            granter: "alice".into(),
+            grantee: "bob".into(),
+        });
+        assert_eq!(get("/upgrade/applied/v2").unwrap(), SdkQuery::AppliedUpgrade {
+            name: "v2".into(),
+        });
+    }
+
+    #[test]
+    fn decoding_path_segments() {
+        // path segments are percent-decoded, and empty ones are ignored
+        assert_eq!(get("//contracts/%E3%83%A9%E3%83%99%E3%83%AB/").unwrap(), SdkQuery::Contract {
+            label: "ラベル".into(),
+        });
+
+        assert_eq!(
+            get("/wasm/bank/raw?start_after=6b6579&order=descending").unwrap(),
+            SdkQuery::WasmRawRange {
+                contract: "bank".into(),
+                start_after: Some(b"key".to_vec().into()),
+                end: None,
+                limit: None,
+                order: Some(Order::Descending),
+            },
+        );
+        assert_eq!(get("/wasm/bank/raw/6b6579").unwrap(), SdkQuery::WasmRaw {
+            contract: "bank".into(),
+            key: b"key".to_vec().into(),
+        });
+
+        let msg = Binary::from(br#"{"config":{}}"#.to_vec()).to_base64();
+        assert_eq!(get(&format!("/wasm/bank/smart/{msg}")).unwrap(), SdkQuery::WasmSmart {
+            contract: "bank".into(),
+            msg: json!({ "config": {} }),
+        });
+    }
+
+    #[test]
+    fn routing_post_requests() {
+        assert_eq!(post("/wasm/bank/smart", br#"{"config":{}}"#).unwrap(), SdkQuery::WasmSmart {
+            contract: "bank".into(),
+            msg: json!({ "config": {} }),
+        });
+
+        // the same route only accepts the query msg in the path with GET
+        let err = post("/wasm/bank/smart/e30=", b"").unwrap_err();
+        assert_eq!(err.status, 404);
+    }
+
+    #[test]
+    fn rejecting_invalid_requests() {
+        for (url, status) in [
+            ("/unknown", 404),
+            ("/accounts?limit=ten", 400),
+            ("/contracts?code_id=1&admin=alice", 400),
+            ("/codes/one", 400),
+            ("/wasm/bank/raw/not-hex", 400),
+            ("/wasm/bank/raw?order=sideways", 400),
+            ("/wasm/bank/smart/not-base64", 400),
+        ] {
+            assert_eq!(get(url).unwrap_err().status, status, "{url}");
+        }
+
+        let err = post("/simulate", b"not json").unwrap_err();
+        assert_eq!(err.status, 400);
+
+        let err = route(&Method::Delete, "/info", io::empty()).unwrap_err();
+        assert_eq!(err.status, 404);
+    }
+
+    #[test]
+    fn limiting_body_size() {
+        let msg = format!(r#"{{"key":"{}"}}"#, "a".repeat(MAX_BODY_SIZE));
+        let err = post("/wasm/bank/smart", msg.as_bytes()).unwrap_err();
+        assert_eq!(err.status, 413);
+
+        // a body of exactly the maximum size is read in full
+        assert_eq!(read_body(&vec![0u8; MAX_BODY_SIZE][..]).unwrap().len(), MAX_BODY_SIZE);
+    }
+}
@@ -0,0 +1,210 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    sync::{Arc, Condvar, Mutex},
+};
+
+use cosmwasm_std::Binary;
+use tracing::error;
+
+use cw_sdk::SdkQuery;
+use cw_state_machine::{error::Result as StateMachineResult, StateMachine};
+use cw_store::Store;
+
+/// Create a publisher through which the AppDriver publishes read-only views of
+/// the committed state, from which queries can be served on another thread.
+///
+/// Each view is a checkpoint of the store, saved in the given directory under
+/// the block height. After each commit, a view is published every `interval`
+/// blocks. Checkpoints left over from previous runs are deleted.
+pub fn query_views(
+    dir: impl Into<PathBuf>,
+    interval: u64,
+) -> io::Result<(ViewPublisher, QueryView)> {
+    let dir = dir.into();
+    if dir.exists() {
+        fs::remove_dir_all(&dir)?;
+    }
+    fs::create_dir_all(&dir)?;
+
+    let latest = Arc::new(LatestView::default());
+
+    Ok((
+        ViewPublisher {
+            dir,
+            interval,
+            latest: latest.clone(),
+        },
+        QueryView {
+            latest,
+            current: None,
+        },
+    ))
+}
+
+/// The most recently published checkpoint that the `QueryView` has yet to open.
+/// Only the most recent one is kept: when a checkpoint is published before the
+/// previous one is opened, the previous one is deleted.
+#[derive(Default)]
+struct LatestView {
+    path: Mutex<Option<PathBuf>>,
+    published: Condvar,
+}
+
+/// Publishes checkpoints of the committed state. Owned by the AppDriver.
+pub struct ViewPublisher {
+    dir: PathBuf,
+    interval: u64,
+    latest: Arc<LatestView>,
+}
+
+impl ViewPublisher {
+    /// Publish a view if the block height is a multiple of the interval.
+    /// Return whether a view was published.
+    pub fn maybe_publish(
+        &self,
+        state_machine: &StateMachine,
+        height: i64,
+    ) -> StateMachineResult<bool> {
+        if self.interval == 0 || height as u64 % self.interval != 0 {
+            return Ok(false);
+        }
+
+        self.publish(state_machine, height)?;
+
+        Ok(true)
+    }
+
+    pub fn publish(&self, state_machine: &StateMachine, height: i64) -> StateMachineResult<()> {
+        // if the QueryView has been dropped, no one is going to use the
+        // checkpoint, or delete it
+        if Arc::strong_count(&self.latest) == 1 {
+            return Ok(());
+        }
+
+        // a checkpoint may already exist at this height, e.g. if the state was
+        // restored from a snapshot
+        let path = self.dir.join(height.to_string());
+        remove_checkpoint(&path);
+
+        state_machine.checkpoint(&path)?;
+
+        let mut latest = self.latest.path.lock().unwrap();
+        if let Some(unopened) = latest.replace(path.clone()) {
+            if unopened != path {
+                remove_checkpoint(&unopened);
+            }
+        }
+        self.latest.published.notify_all();
+
+        Ok(())
+    }
+}
+
+/// Serves queries from the most recent checkpoint published by the
+/// `ViewPublisher`. Owned by the thread that serves the queries.
+///
+/// Checkpoints are opened lazily, when a query is served, and are deleted once
+/// superseded by a more recent one.
+pub struct QueryView {
+    latest: Arc<LatestView>,
+    /// The path of the checkpoint that is open, and a state machine wrapping it
+    current: Option<(PathBuf, StateMachine)>,
+}
+
+impl QueryView {
+    pub fn query(&mut self, query: SdkQuery) -> StateMachineResult<Binary> {
+        self.refresh()?;
+
+        let (_, state_machine) = self.current.as_ref().expect("a view has been opened");
+        state_machine.query(query)
+    }
+
+    /// Open the most recent checkpoint, if it's not open yet. If no checkpoint
+    /// has been published, block until one is.
+    fn refresh(&mut self) -> StateMachineResult<()> {
+        let latest = {
+            let mut latest = self.latest.path.lock().unwrap();
+            if self.current.is_none() {
+                while latest.is_none() {
+                    latest = self.latest.published.wait(latest).unwrap();
+                }
+            }
+            latest.take()
+        };
+
+        let Some(path) = latest else {
+            return Ok(());
+        };
+
+        let state_machine = StateMachine::new(Store::open(&path)?);
+        if let Some((superseded, state_machine)) = self.current.replace((path, state_machine)) {
+            // close the checkpoint's database before deleting its files
+            drop(state_machine);
+            remove_checkpoint(&superseded);
+        }
+
+        Ok(())
+    }
+}
+
+/// Failing to delete a checkpoint only wastes disk space, so the error is
+/// logged instead of failing the request.
+fn remove_checkpoint(path: &Path) {
+    if !path.exists() {
+        return;
+    }
+
+    if let Err(err) = fs::remove_dir_all(path) {
+        error!(
+            target: "Failed to delete query view",
+            path = path.display().to_string(),
+            reason = err.to_string(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn list_views(dir: &Path) -> Vec<String> {
+        let mut views = fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect::<Vec<_>>();
+        views.sort();
+        views
+    }
+
+    #[test]
+    fn publishing_views() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let views_dir = temp_dir.path().join("views");
+        let state_machine = StateMachine::new(Store::open(temp_dir.path().join("data")).unwrap());
+
+        let (publisher, mut view) = query_views(&views_dir, 2).unwrap();
+
+        // a view that is never opened is deleted once superseded
+        publisher.publish(&state_machine, 1).unwrap();
+        publisher.publish(&state_machine, 2).unwrap();
+        assert_eq!(list_views(&views_dir), ["2"]);
+
+        // after commits, views are only published every interval
+        assert!(!publisher.maybe_publish(&state_machine, 3).unwrap());
+        assert!(publisher.maybe_publish(&state_machine, 4).unwrap());
+        assert_eq!(list_views(&views_dir), ["4"]);
+
+        // the view that is open is kept until a more recent one is opened
+        view.refresh().unwrap();
+        publisher.publish(&state_machine, 6).unwrap();
+        assert_eq!(list_views(&views_dir), ["4", "6"]);
+        view.refresh().unwrap();
+        assert_eq!(list_views(&views_dir), ["6"]);
+
+        // nothing is published once the query view is dropped
+        drop(view);
+        publisher.publish(&state_machine, 8).unwrap();
+        assert_eq!(list_views(&views_dir), ["6"]);
+    }
+}
@@ -1,95 +1,462 @@
-use cosmwasm_std::{Addr, BlockInfo, Storage};
-use k256::ecdsa::{signature::Verifier, Signature, VerifyingKey};
+use cosmwasm_std::{Addr, BlockInfo, ContractInfo, ContractResult, Env, Event, Storage};
 
-use cw_sdk::{address, Account, Tx};
+use cw_sdk::{address, Account, AccountSudoMsg, PublicKey, SignDoc, Tx};
 
 use crate::{
     error::{Error, Result},
-    state::ACCOUNTS,
+    events, execute,
+    gas::GasMeter,
+    state::{ACCOUNTS, CONTRACT_SEQUENCES},
 };
 
-/// The response type of `authenticate_tx` function.
-pub struct Sender {
-    pub address: Addr,
-    pub account: Account<Addr>,
-}
-
-/// Authenticate the signer's address, pubkey, signature, sequence, and chain id.
+/// Authenticate the signer's address, pubkey, signature, sequence, chain id,
+/// and timeouts.
 /// Return error if any one fails.
-/// Returns the sender address and account info if succeeds.
-pub fn authenticate_tx(store: &dyn Storage, pending_block: &BlockInfo, tx: &Tx) -> Result<Sender> {
-    let sender = &tx.body.sender;
-    let sender_addr = address::validate(sender)?;
+///
+/// Signatures of any of the schemes supported by `cw_sdk::PublicKey` are
+/// accepted, as long as the scheme matches the account's pubkey.
+///
+/// If succeeds, the sender's incremented sequence number is saved to the store,
+/// and the sender address is returned, along with the events emitted by the
+/// sender if it is a contract account.
+///
+/// If `skip_signature` is true, everything but the signature is checked. This
+/// is used in tx simulations.
+///
+/// NOTE: the fee payer, if any, is authenticated separately in `fee::pay_fee`.
+pub fn authenticate_tx(
+    mut store: impl Storage + 'static,
+    pending_block: &BlockInfo,
+    tx: &Tx,
+    skip_signature: bool,
+    gas: &mut GasMeter,
+) -> Result<(Addr, Vec<Event>)> {
+    let sender_addr = address::validate(&tx.body.sender)?;
+
+    // the chain id must match, and the tx must not have expired
+    validate_tx(pending_block, tx)?;
+
+    // If the sender account is a contract, it can't sign txs. Instead, we let
+    // the contract authenticate the tx itself.
+    if let Some(Account::Contract {
+        ..
+    }) = ACCOUNTS.may_load(&store, &sender_addr)?
+    {
+        return authenticate_contract(store, pending_block, tx, sender_addr, skip_signature, gas);
+    }
+
+    authenticate_base_account(
+        &mut store,
+        &sender_addr,
+        tx.pubkey.as_ref(),
+        tx.body.sequence,
+        &SignDoc::new(tx.body.clone()),
+        &tx.signature,
+        skip_signature,
+    )?;
 
+    Ok((sender_addr, vec![]))
+}
+
+/// Authenticate a signer of the tx who owns a base account, i.e. the sender, or
+/// the fee payer if it co-signs the tx.
+///
+/// If succeeds, the account's incremented sequence number (and its pubkey, if
+/// this is the first tx the account ever signs) is saved to the store.
+pub fn authenticate_base_account(
+    store: &mut dyn Storage,
+    addr: &Addr,
+    pubkey: Option<&PublicKey>,
+    sequence: u64,
+    sign_doc: &SignDoc,
+    signature: &[u8],
+    skip_signature: bool,
+) -> Result<()> {
     // find the user's account
-    let (pubkey, mut sequence) = match ACCOUNTS.may_load(store, &sender_addr)? {
-        // If the sender account is a contract, throw error because contracts
-        // can't sign txs.
+    let (account_pubkey, mut account_sequence) = match ACCOUNTS.may_load(store, addr)? {
+        // Contracts can't sign txs
         Some(Account::Contract {
             ..
         }) => {
-            return Err(Error::account_is_contract(sender));
-        }
+            return Err(Error::account_is_contract(addr));
+        },
 
         // If the account is found on chain, meaning the account has already
-        // sent at least one tx before, its pubkey must match the one included
+        // signed at least one tx before, its pubkey must match the one included
         // in the tx.
         Some(Account::Base {
-            pubkey,
+            pubkey: account_pubkey,
             sequence,
         }) => {
-            if let Some(sender_pubkey) = &tx.pubkey {
-                if pubkey != *sender_pubkey {
-                    return Err(Error::pubkey_mismatch(sender, &pubkey, sender_pubkey));
+            if let Some(pubkey) = pubkey {
+                if account_pubkey != *pubkey {
+                    return Err(Error::pubkey_mismatch(addr, &account_pubkey, pubkey));
                 }
             }
 
-            (pubkey, sequence)
+            (account_pubkey, sequence)
         },
 
-        // If not found, meaning it's the first time the account every sends a
+        // If not found, meaning it's the first time the account every signs a
         // tx, use the pubkey provided by the tx and initialize sequence to be 0.
-        // Note, the pubkey must match the sender address.
+        // Note, the pubkey must match the account address.
         None => {
-            let Some(pubkey) = &tx.pubkey else {
-                return Err(Error::account_not_found(sender));
+            let Some(pubkey) = pubkey else {
+                return Err(Error::account_not_found(addr));
             };
 
-            let address = address::derive_from_pubkey(pubkey.as_slice())?;
-            if *sender != address {
-                return Err(Error::address_mismatch(address, sender));
+            let derived_addr = address::derive_from_pubkey(pubkey)?;
+            if *addr != derived_addr {
+                return Err(Error::address_mismatch(derived_addr, addr));
             }
 
             (pubkey.clone(), 0)
         },
     };
 
-    // the chain id must match
-    if pending_block.chain_id != tx.body.chain_id {
-        return Err(Error::chain_id_mismatch(&pending_block.chain_id, &tx.body.chain_id));
+    // the account sequence mush match
+    account_sequence += 1;
+    if account_sequence != sequence {
+        return Err(Error::sequence_mismatch(addr, account_sequence, sequence));
     }
 
-    // the account sequence mush match
-    sequence += 1;
+    // verify the signature, using the scheme indicated by the pubkey
+    // the content to be signed is the canonical JSON encoding of the sign doc
+    if !skip_signature {
+        sign_doc.verify(&account_pubkey, signature)?;
+    }
+
+    // signature is valid; update the account info
+    ACCOUNTS.save(
+        store,
+        addr,
+        &Account::Base {
+            pubkey: account_pubkey,
+            sequence: account_sequence,
+        },
+    )?;
+
+    Ok(())
+}
+
+/// Perform the checks that don't require loading the sender's account: the
+/// chain id must match, the fee payer fields must be consistent, and the tx must
+/// not have expired.
+///
+/// A tx is expired if the block it is included in is higher than its
+/// `timeout_height`, or later than its `timeout_timestamp`.
+pub fn validate_tx(block: &BlockInfo, tx: &Tx) -> Result<()> {
+    if block.chain_id != tx.body.chain_id {
+        return Err(Error::chain_id_mismatch(&block.chain_id, &tx.body.chain_id));
+    }
+
+    // the fee payer's signature and sequence are meaningless without a fee payer
+    if tx.body.fee_payer.is_none()
+        && (tx.fee_payer_signature.is_some() || tx.body.fee_payer_sequence.is_some())
+    {
+        return Err(Error::FeePayerNotSpecified);
+    }
+
+    if let Some(timeout_height) = tx.body.timeout_height {
+        if block.height > timeout_height {
+            return Err(Error::tx_expired_height(timeout_height, block.height));
+        }
+    }
+
+    if let Some(timeout_timestamp) = tx.body.timeout_timestamp {
+        if block.time > timeout_timestamp {
+            return Err(Error::tx_expired_timestamp(timeout_timestamp, block.time));
+        }
+    }
+
+    Ok(())
+}
+
+/// Authenticate a tx whose sender is a contract account.
+///
+/// The sequence number is checked and incremented the same way as for base
+/// accounts, except that it is kept in `CONTRACT_SEQUENCES`. Then, in place of
+/// signature verification, the contract's `sudo` entry point is invoked with
+/// the tx body and the credential, i.e. the tx's `signature` field. The tx is
+/// rejected if the contract returns an error. Otherwise, the events emitted by
+/// the contract are returned, subject to the same rules as those of any other
+/// contract call.
+///
+/// If `skip_signature` is true, the contract is not invoked.
+fn authenticate_contract(
+    mut store: impl Storage + 'static,
+    pending_block: &BlockInfo,
+    tx: &Tx,
+    sender_addr: Addr,
+    skip_signature: bool,
+    gas: &mut GasMeter,
+) -> Result<(Addr, Vec<Event>)> {
+    // the contract's sequence must match
+    let sequence = CONTRACT_SEQUENCES.may_load(&store, &sender_addr)?.unwrap_or(0) + 1;
     if sequence != tx.body.sequence {
-        return Err(Error::sequence_mismatch(sender, sequence, tx.body.sequence));
-    }
-
-    // verify the signature
-    // the content to be signed is (the sha256 hash of) the tx body
-    let body_bytes = serde_json::to_vec(&tx.body)?;
-    let signature = Signature::try_from(tx.signature.as_slice())?;
-
-    // if signature is valid, return the sender address and updated account info
-    // otherwise, return error
-    VerifyingKey::from_sec1_bytes(pubkey.as_slice())?
-        .verify(&body_bytes, &signature)
-        .map(|_| Sender {
-            address: sender_addr,
-            account: Account::Base {
-                pubkey,
-                sequence,
+        return Err(Error::sequence_mismatch(&sender_addr, sequence, tx.body.sequence));
+    }
+
+    CONTRACT_SEQUENCES.save(&mut store, &sender_addr, &sequence)?;
+
+    if skip_signature {
+        return Ok((sender_addr, vec![]));
+    }
+
+    let env = Env {
+        block: pending_block.clone(),
+        transaction: None,
+        contract: ContractInfo {
+            address: sender_addr.clone(),
+        },
+    };
+
+    let msg = AccountSudoMsg::Authenticate {
+        tx_body: tx.body.clone(),
+        credential: tx.signature.clone(),
+    };
+
+    match execute::sudo_contract(store, &env, &serde_json::to_vec(&msg)?, gas)? {
+        ContractResult::Ok(res) => {
+            if !res.messages.is_empty() {
+                return Err(Error::SubmessagesUnsupported);
+            }
+            let events = events::contract_events(
+                Event::new("sudo"),
+                &sender_addr,
+                res.attributes,
+                res.events,
+            )?;
+            Ok((sender_addr, events))
+        },
+        ContractResult::Err(err) => Err(Error::contract_auth_failed(&sender_addr, err)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::testing::MockStorage;
+    use cw_store::Shared;
+
+    use super::*;
+    use crate::testing::{mock_block, TestAccount};
+
+    fn authenticate(
+        store: &Shared<MockStorage>,
+        tx: &Tx,
+        skip_signature: bool,
+    ) -> Result<(Addr, Vec<Event>)> {
+        let mut gas = GasMeter::unlimited();
+        authenticate_tx(store.share(), &mock_block(1, 0), tx, skip_signature, &mut gas)
+    }
+
+    #[test]
+    fn authenticating_base_account() {
+        let store = Shared::new(MockStorage::new());
+        let alice = TestAccount::new(1);
+
+        // the first tx creates the account, and must include the pubkey
+        let tx = alice.sign_tx(alice.tx_body(1, vec![]));
+        let (sender_addr, events) = authenticate(&store, &tx, false).unwrap();
+        assert_eq!(sender_addr, alice.addr);
+        assert!(events.is_empty());
+        assert_eq!(
+            ACCOUNTS.load(&store, &alice.addr).unwrap(),
+            Account::Base {
+                pubkey: alice.pubkey.clone(),
+                sequence: 1,
+            },
+        );
+
+        // subsequent txs may omit the pubkey
+        let mut tx = alice.sign_tx(alice.tx_body(2, vec![]));
+        tx.pubkey = None;
+        authenticate(&store, &tx, false).unwrap();
+        assert_eq!(
+            ACCOUNTS.load(&store, &alice.addr).unwrap(),
+            Account::Base {
+                pubkey: alice.pubkey,
+                sequence: 2,
+            },
+        );
+    }
+
+    #[test]
+    fn rejecting_missing_pubkey() {
+        let store = Shared::new(MockStorage::new());
+        let alice = TestAccount::new(1);
+
+        let mut tx = alice.sign_tx(alice.tx_body(1, vec![]));
+        tx.pubkey = None;
+        let err = authenticate(&store, &tx, false).unwrap_err();
+        assert!(matches!(err, Error::AccountNotFound {
+            ..
+        }));
+    }
+
+    #[test]
+    fn rejecting_sequence_mismatch() {
+        let store = Shared::new(MockStorage::new());
+        let alice = TestAccount::new(1);
+
+        // the first tx must have sequence 1
+        let tx = alice.sign_tx(alice.tx_body(2, vec![]));
+        let err = authenticate(&store, &tx, false).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::SequenceMismatch {
+                expect: 1,
+                found: 2,
+                ..
+            },
+        ));
+
+        // a tx can't be replayed
+        let tx = alice.sign_tx(alice.tx_body(1, vec![]));
+        authenticate(&store, &tx, false).unwrap();
+        let err = authenticate(&store, &tx, false).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::SequenceMismatch {
+                expect: 2,
+                found: 1,
+                ..
+            },
+        ));
+    }
+
+    #[test]
+    fn rejecting_pubkey_mismatch() {
+        let store = Shared::new(MockStorage::new());
+        let alice = TestAccount::new(1);
+        let bob = TestAccount::new(2);
+
+        // a new account's pubkey must match its address
+        let body = alice.tx_body(1, vec![]);
+        let tx = Tx {
+            pubkey: Some(bob.pubkey.clone()),
+            signature: bob.sign(&body),
+            ..alice.sign_tx(body)
+        };
+        let err = authenticate(&store, &tx, false).unwrap_err();
+        assert!(matches!(err, Error::AddressMismatch {
+            ..
+        }));
+
+        // an existing account's pubkey must match the one on chain
+        authenticate(&store, &alice.sign_tx(alice.tx_body(1, vec![])), false).unwrap();
+        let body = alice.tx_body(2, vec![]);
+        let tx = Tx {
+            pubkey: Some(bob.pubkey.clone()),
+            signature: bob.sign(&body),
+            ..alice.sign_tx(body)
+        };
+        let err = authenticate(&store, &tx, false).unwrap_err();
+        assert!(matches!(err, Error::PubkeyMismatch {
+            ..
+        }));
+    }
+
+    #[test]
+    fn rejecting_invalid_signature() {
+        let store = Shared::new(MockStorage::new());
+        let alice = TestAccount::new(1);
+        let bob = TestAccount::new(2);
+
+        let body = alice.tx_body(1, vec![]);
+        let tx = Tx {
+            signature: bob.sign(&body),
+            ..alice.sign_tx(body)
+        };
+        let err = authenticate(&store, &tx, false).unwrap_err();
+        assert!(matches!(err, Error::SignDoc(_)));
+
+        // the sequence is not incremented
+        assert!(ACCOUNTS.may_load(&store, &alice.addr).unwrap().is_none());
+
+        // the signature is not verified in simulations
+        authenticate(&store, &tx, true).unwrap();
+    }
+
+    #[test]
+    fn authenticating_contract_account() {
+        let mut store = Shared::new(MockStorage::new());
+        let alice = TestAccount::new(1);
+
+        let contract_addr = address::derive_from_label("wallet").unwrap();
+        ACCOUNTS
+            .save(
+                &mut store,
+                &contract_addr,
+                &Account::Contract {
+                    code_id: 1,
+                    label: "wallet".into(),
+                    admin: None,
+                },
+            )
+            .unwrap();
+
+        let mut body = alice.tx_body(1, vec![]);
+        body.sender = contract_addr.to_string();
+        let tx = Tx {
+            pubkey: None,
+            signature: b"credential".to_vec().into(),
+            fee_payer_pubkey: None,
+            fee_payer_signature: None,
+            body,
+        };
+
+        // the contract isn't invoked when simulating, but its sequence is
+        // checked and incremented all the same
+        let (sender_addr, events) = authenticate(&store, &tx, true).unwrap();
+        assert_eq!(sender_addr, contract_addr);
+        assert!(events.is_empty());
+        assert_eq!(CONTRACT_SEQUENCES.load(&store, &contract_addr).unwrap(), 1);
+
+        let err = authenticate(&store, &tx, true).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::SequenceMismatch {
+                expect: 2,
+                found: 1,
+                ..
             },
-        })
-        .map_err(Error::from)
+        ));
+
+        // contracts can't sign txs, e.g. as fee payers
+        let err = authenticate_base_account(
+            &mut store,
+            &contract_addr,
+            Some(&alice.pubkey),
+            1,
+            &SignDoc::new(tx.body.clone()),
+            &tx.signature,
+            false,
+        )
+        .unwrap_err();
+        assert!(matches!(err, Error::AccountIsContract {
+            ..
+        }));
+    }
+
+    #[test]
+    fn rejecting_wrong_chain_or_expired_tx() {
+        let store = Shared::new(MockStorage::new());
+        let alice = TestAccount::new(1);
+
+        let mut body = alice.tx_body(1, vec![]);
+        body.chain_id = "other-chain".into();
+        let err = authenticate(&store, &alice.sign_tx(body), false).unwrap_err();
+        assert!(matches!(err, Error::ChainIdMismatch {
+            ..
+        }));
+
+        // the mock block is at height 1 and time 0
+        let mut body = alice.tx_body(1, vec![]);
+        body.timeout_height = Some(0);
+        let err = authenticate(&store, &alice.sign_tx(body), false).unwrap_err();
+        assert!(matches!(err, Error::TxExpiredHeight {
+            ..
+        }));
+    }
 }
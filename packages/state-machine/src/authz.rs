@@ -0,0 +1,332 @@
+use cosmwasm_std::{Addr, BlockInfo, Event, Storage, Timestamp};
+
+use cw_sdk::{address, label::resolve_raw_address, Authorization, Grant, SdkMsg};
+
+use crate::{
+    error::{Error, Result},
+    state::GRANTS,
+};
+
+/// Message types that can't be granted through `Authorization::MsgType`, as
+/// they would let the grantee take over the granter's account or contracts, or
+/// act as the upgrade authority. `exec` is included because `Exec` messages
+/// can't be nested.
+pub const NON_GRANTABLE_MSG_TYPES: &[&str] = &[
+    "grant",
+    "revoke",
+    "grant_fee_allowance",
+    "revoke_fee_allowance",
+    "migrate",
+    "schedule_upgrade",
+    "exec",
+];
+
+pub fn grant(
+    store: &mut dyn Storage,
+    granter_addr: &Addr,
+    grantee: &str,
+    authorization: Authorization,
+    expiration: Option<Timestamp>,
+) -> Result<Event> {
+    let grantee_addr = address::validate(grantee)?;
+
+    if grantee_addr == *granter_addr {
+        return Err(Error::self_grant(granter_addr));
+    }
+
+    let authorization = match authorization {
+        Authorization::MsgType {
+            msg_type,
+        } => {
+            if !SdkMsg::MSG_TYPES.contains(&msg_type.as_str()) {
+                return Err(Error::unknown_msg_type(msg_type));
+            }
+            if NON_GRANTABLE_MSG_TYPES.contains(&msg_type.as_str()) {
+                return Err(Error::msg_type_not_grantable(msg_type));
+            }
+            Authorization::MsgType {
+                msg_type,
+            }
+        },
+        // the contract may be provided as either an address or a label. save
+        // the address, so that it can be compared against that of the messages.
+        Authorization::Contract {
+            contract,
+        } => Authorization::Contract {
+            contract: resolve_raw_address(&contract)?.into(),
+        },
+    };
+
+    GRANTS.save(
+        store,
+        (granter_addr, &grantee_addr),
+        &Grant {
+            authorization,
+            expiration,
+        },
+    )?;
+
+    Ok(Event::new("grant")
+        .add_attribute("granter", granter_addr)
+        .add_attribute("grantee", grantee_addr))
+}
+
+pub fn revoke(store: &mut dyn Storage, granter_addr: &Addr, grantee: &str) -> Result<Event> {
+    let grantee_addr = address::validate(grantee)?;
+
+    if !GRANTS.has(store, (granter_addr, &grantee_addr)) {
+        return Err(Error::grant_not_found(granter_addr, grantee_addr));
+    }
+
+    GRANTS.remove(store, (granter_addr, &grantee_addr));
+
+    Ok(Event::new("revoke")
+        .add_attribute("granter", granter_addr)
+        .add_attribute("grantee", grantee_addr))
+}
+
+/// Check whether the granter has given the grantee a grant, which has not
+/// expired and permits the message.
+///
+/// `Exec` messages are never permitted, as they can't be nested.
+pub fn check_grant(
+    store: &dyn Storage,
+    block: &BlockInfo,
+    granter_addr: &Addr,
+    grantee_addr: &Addr,
+    msg: &SdkMsg,
+) -> Result<()> {
+    if let SdkMsg::Exec {
+        ..
+    } = msg
+    {
+        return Err(Error::NestedExec);
+    }
+
+    let Some(grant) = GRANTS.may_load(store, (granter_addr, grantee_addr))? else {
+        return Err(Error::grant_not_found(granter_addr, grantee_addr));
+    };
+
+    if let Some(expiration) = grant.expiration {
+        if block.time > expiration {
+            return Err(Error::grant_expired(granter_addr, grantee_addr));
+        }
+    }
+
+    let permitted = match (&grant.authorization, msg) {
+        (
+            Authorization::MsgType {
+                msg_type,
+            },
+            _,
+        ) => msg_type == msg.msg_type() && !NON_GRANTABLE_MSG_TYPES.contains(&msg_type.as_str()),
+        (
+            Authorization::Contract {
+                contract,
+            },
+            SdkMsg::Execute {
+                contract: msg_contract,
+                ..
+            },
+        ) => *contract == resolve_raw_address(msg_contract)?,
+        _ => false,
+    };
+
+    if !permitted {
+        return Err(Error::unauthorized(granter_addr, grantee_addr, msg.msg_type()));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::testing::MockStorage;
+    use serde_json::json;
+
+    use super::*;
+    use crate::testing::{mock_block, TestAccount};
+
+    fn execute_msg(contract: &str) -> SdkMsg {
+        SdkMsg::Execute {
+            contract: contract.into(),
+            msg: json!({}),
+            funds: vec![],
+        }
+    }
+
+    fn msg_type_grant(msg_type: &str) -> Authorization {
+        Authorization::MsgType {
+            msg_type: msg_type.into(),
+        }
+    }
+
+    #[test]
+    fn granting_and_revoking() {
+        let mut store = MockStorage::new();
+        let alice = TestAccount::new(1);
+        let bob = TestAccount::new(2);
+        let block = mock_block(1, 0);
+        let msg = execute_msg("wallet");
+
+        let err = check_grant(&store, &block, &alice.addr, &bob.addr, &msg).unwrap_err();
+        assert!(matches!(err, Error::GrantNotFound {
+            ..
+        }));
+
+        grant(&mut store, &alice.addr, bob.addr.as_str(), msg_type_grant("execute"), None).unwrap();
+        check_grant(&store, &block, &alice.addr, &bob.addr, &msg).unwrap();
+
+        // the grant is one-directional
+        let err = check_grant(&store, &block, &bob.addr, &alice.addr, &msg).unwrap_err();
+        assert!(matches!(err, Error::GrantNotFound {
+            ..
+        }));
+
+        revoke(&mut store, &alice.addr, bob.addr.as_str()).unwrap();
+        let err = check_grant(&store, &block, &alice.addr, &bob.addr, &msg).unwrap_err();
+        assert!(matches!(err, Error::GrantNotFound {
+            ..
+        }));
+
+        // a grant can't be revoked twice
+        let err = revoke(&mut store, &alice.addr, bob.addr.as_str()).unwrap_err();
+        assert!(matches!(err, Error::GrantNotFound {
+            ..
+        }));
+    }
+
+    #[test]
+    fn rejecting_self_grant() {
+        let mut store = MockStorage::new();
+        let alice = TestAccount::new(1);
+
+        let authorization = msg_type_grant("execute");
+        let err = grant(&mut store, &alice.addr, alice.addr.as_str(), authorization, None)
+            .unwrap_err();
+        assert!(matches!(err, Error::SelfGrant {
+            ..
+        }));
+        assert!(!GRANTS.has(&store, (&alice.addr, &alice.addr)));
+    }
+
+    #[test]
+    fn rejecting_expired_grant() {
+        let mut store = MockStorage::new();
+        let alice = TestAccount::new(1);
+        let bob = TestAccount::new(2);
+        let msg = execute_msg("wallet");
+
+        let expiration = Some(Timestamp::from_seconds(100));
+        grant(&mut store, &alice.addr, bob.addr.as_str(), msg_type_grant("execute"), expiration)
+            .unwrap();
+
+        // the grant can still be used at the expiration time, but not after
+        check_grant(&store, &mock_block(1, 100), &alice.addr, &bob.addr, &msg).unwrap();
+        let err = check_grant(&store, &mock_block(2, 101), &alice.addr, &bob.addr, &msg)
+            .unwrap_err();
+        assert!(matches!(err, Error::GrantExpired {
+            ..
+        }));
+    }
+
+    #[test]
+    fn rejecting_wrong_msg_type() {
+        let mut store = MockStorage::new();
+        let alice = TestAccount::new(1);
+        let bob = TestAccount::new(2);
+        let block = mock_block(1, 0);
+
+        grant(&mut store, &alice.addr, bob.addr.as_str(), msg_type_grant("instantiate"), None)
+            .unwrap();
+
+        let err = check_grant(&store, &block, &alice.addr, &bob.addr, &execute_msg("wallet"))
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Unauthorized {
+                msg_type,
+                ..
+            } if msg_type == "execute",
+        ));
+    }
+
+    #[test]
+    fn rejecting_invalid_msg_types() {
+        let mut store = MockStorage::new();
+        let alice = TestAccount::new(1);
+        let bob = TestAccount::new(2);
+
+        let err = grant(&mut store, &alice.addr, bob.addr.as_str(), msg_type_grant("exectue"), None)
+            .unwrap_err();
+        assert!(matches!(err, Error::UnknownMsgType {
+            ..
+        }));
+
+        for msg_type in NON_GRANTABLE_MSG_TYPES {
+            let authorization = msg_type_grant(msg_type);
+            let err = grant(&mut store, &alice.addr, bob.addr.as_str(), authorization, None)
+                .unwrap_err();
+            assert!(matches!(err, Error::MsgTypeNotGrantable {
+                ..
+            }));
+        }
+
+        assert!(!GRANTS.has(&store, (&alice.addr, &bob.addr)));
+    }
+
+    #[test]
+    fn checking_contract_grant() {
+        let mut store = MockStorage::new();
+        let alice = TestAccount::new(1);
+        let bob = TestAccount::new(2);
+        let block = mock_block(1, 0);
+
+        // the contract is granted by its label, and the messages may refer to
+        // it by either its label or address
+        let authorization = Authorization::Contract {
+            contract: "wallet".into(),
+        };
+        grant(&mut store, &alice.addr, bob.addr.as_str(), authorization, None).unwrap();
+
+        let contract_addr = address::derive_from_label("wallet").unwrap();
+        check_grant(&store, &block, &alice.addr, &bob.addr, &execute_msg("wallet")).unwrap();
+        check_grant(&store, &block, &alice.addr, &bob.addr, &execute_msg(contract_addr.as_str()))
+            .unwrap();
+
+        // another contract isn't permitted
+        let err = check_grant(&store, &block, &alice.addr, &bob.addr, &execute_msg("vault"))
+            .unwrap_err();
+        assert!(matches!(err, Error::Unauthorized {
+            ..
+        }));
+
+        // neither is any other message on the same contract
+        let msg = SdkMsg::Migrate {
+            contract: "wallet".into(),
+            code_id: 2,
+            msg: json!({}),
+        };
+        let err = check_grant(&store, &block, &alice.addr, &bob.addr, &msg).unwrap_err();
+        assert!(matches!(err, Error::Unauthorized {
+            ..
+        }));
+    }
+
+    #[test]
+    fn rejecting_nested_exec() {
+        let mut store = MockStorage::new();
+        let alice = TestAccount::new(1);
+        let bob = TestAccount::new(2);
+
+        grant(&mut store, &alice.addr, bob.addr.as_str(), msg_type_grant("execute"), None).unwrap();
+
+        let msg = SdkMsg::Exec {
+            granter: alice.addr.to_string(),
+            msgs: vec![execute_msg("wallet")],
+        };
+        let err = check_grant(&store, &mock_block(1, 0), &alice.addr, &bob.addr, &msg)
+            .unwrap_err();
+        assert!(matches!(err, Error::NestedExec));
+    }
+}
@@ -8,6 +8,8 @@ use cw_store::{
     prefix::{concat, namespace_upper_bound, trim},
 };
 
+use crate::gas::GAS_PER_BYTE_WRITTEN;
+
 /// NOTE: cosmwasm-vm requires the backend store to be of 'static lifetime.
 /// This requirement comes from wasmer so not something we can change.
 ///
@@ -47,7 +49,9 @@ impl<T: Storage> cosmwasm_vm::Storage for ContractSubstore<T> {
 
     fn set(&mut self, key: &[u8], value: &[u8]) -> BackendResult<()> {
         self.store.set(&self.key(key), value);
-        (Ok(()), GasInfo::free())
+        // charge gas by the number of bytes written
+        let size = (key.len() + value.len()) as u64;
+        (Ok(()), GasInfo::with_cost(size.saturating_mul(GAS_PER_BYTE_WRITTEN)))
     }
 
     fn remove(&mut self, key: &[u8]) -> BackendResult<()> {
@@ -1,3 +1,6 @@
+use cosmwasm_std::Timestamp;
+use cw_sdk::{hash::HASH_LENGTH, PublicKey};
+
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
     #[error(transparent)]
@@ -12,11 +15,17 @@ pub enum Error {
     #[error(transparent)]
     Merk(#[from] cw_store::MerkError),
 
+    #[error(transparent)]
+    Snapshot(#[from] cw_store::SnapshotError),
+
     #[error(transparent)]
     Address(#[from] cw_sdk::address::AddressError),
 
     #[error(transparent)]
-    Ecdsa(#[from] k256::ecdsa::Error),
+    PublicKey(#[from] cw_sdk::pubkey::PublicKeyError),
+
+    #[error(transparent)]
+    SignDoc(#[from] cw_sdk::tx::SignDocError),
 
     #[error(transparent)]
     Serde(#[from] serde_json::Error),
@@ -63,9 +72,9 @@ pub enum Error {
     #[error("pubkey for sender {sender} does not match: expecting {expect}, found {found}")]
     PubkeyMismatch {
         sender: String,
-        /// The pubkey stored on-chain; hex-encoded bytearray
+        /// The pubkey stored on-chain, in the format `{algo}:{hex-encoded bytes}`
         expect: String,
-        /// The pubkey included in the tx; hex-encoded bytearray
+        /// The pubkey included in the tx, in the format `{algo}:{hex-encoded bytes}`
         found: String,
     },
 
@@ -77,6 +86,18 @@ pub enum Error {
         found: String,
     },
 
+    #[error("tx expired: timeout height {timeout_height}, current height {height}")]
+    TxExpiredHeight {
+        timeout_height: u64,
+        height: u64,
+    },
+
+    #[error("tx expired: timeout timestamp {timeout_timestamp}, current time {time}")]
+    TxExpiredTimestamp {
+        timeout_timestamp: Timestamp,
+        time: Timestamp,
+    },
+
     #[error("incorrect sequence number for sender {sender}: expecting {expect}, found {found}")]
     SequenceMismatch {
         sender: String,
@@ -86,17 +107,178 @@ pub enum Error {
         found: u64,
     },
 
+    #[error("contract account {address} failed to authenticate tx: {reason}")]
+    ContractAuthFailed {
+        address: String,
+        reason: String,
+    },
+
+    #[error("fee payer signature or sequence is provided, but fee payer is not specified")]
+    FeePayerNotSpecified,
+
+    #[error("fee payer {fee_payer} co-signed the tx but its sequence number is not provided")]
+    FeePayerSequenceMissing {
+        fee_payer: String,
+    },
+
+    #[error("no fee allowance found from granter {granter} to grantee {grantee}")]
+    FeeAllowanceNotFound {
+        granter: String,
+        grantee: String,
+    },
+
+    #[error("fee allowance from granter {granter} to grantee {grantee} has expired")]
+    FeeAllowanceExpired {
+        granter: String,
+        grantee: String,
+    },
+
+    #[error("fee exceeds the spend limit of allowance from granter {granter} to grantee {grantee}")]
+    FeeAllowanceExceeded {
+        granter: String,
+        grantee: String,
+    },
+
+    #[error("no grant found from granter {granter} to grantee {grantee}")]
+    GrantNotFound {
+        granter: String,
+        grantee: String,
+    },
+
+    #[error("grant from granter {granter} to grantee {grantee} has expired")]
+    GrantExpired {
+        granter: String,
+        grantee: String,
+    },
+
+    #[error("grant from granter {granter} to grantee {grantee} does not permit {msg_type} messages")]
+    Unauthorized {
+        granter: String,
+        grantee: String,
+        msg_type: String,
+    },
+
+    #[error("exec messages can't be nested")]
+    NestedExec,
+
+    #[error("out of gas: limit {limit}, used {used}")]
+    OutOfGas {
+        limit: u64,
+        used: u64,
+    },
+
+    #[error("event type `{ty}` is invalid: it must have at least 2 characters")]
+    InvalidEventType {
+        ty: String,
+    },
+
+    #[error("event attribute keys must not be empty")]
+    EmptyAttributeKey,
+
+    #[error("value of event attribute `{key}` must not be empty")]
+    EmptyAttributeValue {
+        key: String,
+    },
+
+    #[error("event attribute key `{key}` is reserved: keys must not start with an underscore")]
+    ReservedAttributeKey {
+        key: String,
+    },
+
     #[error("contract response includes submessages, which is not supported yet")]
     SubmessagesUnsupported,
 
     #[error("sending funds when instantiating or executing contracts is not supported yet")]
     FundsUnsupported,
 
-    #[error("migrating contracts is not supported yet")]
-    MigrationUnsupported,
+    #[error("sender {sender} is not the admin of contract {address}")]
+    NotContractAdmin {
+        address: String,
+        sender: String,
+    },
 
     #[error("this query is not supported yet")]
     QueryUnsupported,
+
+    #[error("chain upgrades are disabled: no upgrade authority is set")]
+    UpgradesDisabled,
+
+    #[error("sender {sender} is not the upgrade authority")]
+    NotUpgradeAuthority {
+        sender: String,
+    },
+
+    #[error("upgrade height {height} must be after the current height {current_height}")]
+    UpgradeHeightPassed {
+        height: u64,
+        current_height: u64,
+    },
+
+    #[error("upgrade {name} has already been applied")]
+    UpgradeAlreadyApplied {
+        name: String,
+    },
+
+    #[error(
+        "UPGRADE \"{name}\" NEEDED at height {height}{}: this binary has no handler for it",
+        .info.as_ref().map(|info| format!(" ({info})")).unwrap_or_default()
+    )]
+    UpgradeNeeded {
+        name: String,
+        height: u64,
+        info: Option<String>,
+    },
+
+    #[error("binary was switched too early: upgrade \"{name}\" is scheduled at height {height}")]
+    UpgradeTooEarly {
+        name: String,
+        height: u64,
+    },
+
+    #[error("unsupported snapshot format: {format}")]
+    UnsupportedSnapshotFormat {
+        format: u32,
+    },
+
+    #[error("app hash is not {HASH_LENGTH} bytes long")]
+    InvalidAppHash,
+
+    #[error("no snapshot has been offered to be restored")]
+    NoSnapshotOffered,
+
+    #[error("account {address} can't grant a fee allowance to itself")]
+    SelfFeeAllowance {
+        address: String,
+    },
+
+    #[error("tx gas limit {limit} exceeds the maximum of {max}")]
+    GasLimitTooHigh {
+        limit: u64,
+        max: u64,
+    },
+
+    #[error("unknown message type `{msg_type}`")]
+    UnknownMsgType {
+        msg_type: String,
+    },
+
+    #[error("{msg_type} messages can't be granted")]
+    MsgTypeNotGrantable {
+        msg_type: String,
+    },
+
+    #[error("fee payer sequence number is provided but the fee payer didn't co-sign the tx")]
+    FeePayerSequenceUnexpected,
+
+    #[error("denom {denom} is not accepted as fee, because its transfers trigger a hook")]
+    FeeDenomNotAccepted {
+        denom: String,
+    },
+
+    #[error("account {address} can't grant to itself")]
+    SelfGrant {
+        address: String,
+    },
 }
 
 impl Error {
@@ -137,11 +319,15 @@ impl Error {
         }
     }
 
-    pub fn pubkey_mismatch(sender: impl Into<String>, expect: &[u8], found: &[u8]) -> Self {
+    pub fn pubkey_mismatch(
+        sender: impl Into<String>,
+        expect: &PublicKey,
+        found: &PublicKey,
+    ) -> Self {
         Self::PubkeyMismatch {
             sender: sender.into(),
-            expect: hex::encode(expect),
-            found: hex::encode(found),
+            expect: format!("{}:{}", expect.algo(), hex::encode(expect.as_slice())),
+            found: format!("{}:{}", found.algo(), hex::encode(found.as_slice())),
         }
     }
 
@@ -159,6 +345,328 @@ impl Error {
             found,
         }
     }
+
+    pub fn tx_expired_height(timeout_height: u64, height: u64) -> Self {
+        Self::TxExpiredHeight {
+            timeout_height,
+            height,
+        }
+    }
+
+    pub fn tx_expired_timestamp(timeout_timestamp: Timestamp, time: Timestamp) -> Self {
+        Self::TxExpiredTimestamp {
+            timeout_timestamp,
+            time,
+        }
+    }
+
+    pub fn fee_payer_sequence_missing(fee_payer: impl Into<String>) -> Self {
+        Self::FeePayerSequenceMissing {
+            fee_payer: fee_payer.into(),
+        }
+    }
+
+    pub fn fee_allowance_not_found(granter: impl Into<String>, grantee: impl Into<String>) -> Self {
+        Self::FeeAllowanceNotFound {
+            granter: granter.into(),
+            grantee: grantee.into(),
+        }
+    }
+
+    pub fn fee_allowance_expired(granter: impl Into<String>, grantee: impl Into<String>) -> Self {
+        Self::FeeAllowanceExpired {
+            granter: granter.into(),
+            grantee: grantee.into(),
+        }
+    }
+
+    pub fn fee_allowance_exceeded(granter: impl Into<String>, grantee: impl Into<String>) -> Self {
+        Self::FeeAllowanceExceeded {
+            granter: granter.into(),
+            grantee: grantee.into(),
+        }
+    }
+
+    pub fn self_fee_allowance(address: impl Into<String>) -> Self {
+        Self::SelfFeeAllowance {
+            address: address.into(),
+        }
+    }
+
+    pub fn unknown_msg_type(msg_type: impl Into<String>) -> Self {
+        Self::UnknownMsgType {
+            msg_type: msg_type.into(),
+        }
+    }
+
+    pub fn msg_type_not_grantable(msg_type: impl Into<String>) -> Self {
+        Self::MsgTypeNotGrantable {
+            msg_type: msg_type.into(),
+        }
+    }
+
+    pub fn fee_denom_not_accepted(denom: impl Into<String>) -> Self {
+        Self::FeeDenomNotAccepted {
+            denom: denom.into(),
+        }
+    }
+
+    pub fn self_grant(address: impl Into<String>) -> Self {
+        Self::SelfGrant {
+            address: address.into(),
+        }
+    }
+
+    pub fn grant_not_found(granter: impl Into<String>, grantee: impl Into<String>) -> Self {
+        Self::GrantNotFound {
+            granter: granter.into(),
+            grantee: grantee.into(),
+        }
+    }
+
+    pub fn grant_expired(granter: impl Into<String>, grantee: impl Into<String>) -> Self {
+        Self::GrantExpired {
+            granter: granter.into(),
+            grantee: grantee.into(),
+        }
+    }
+
+    pub fn unauthorized(
+        granter: impl Into<String>,
+        grantee: impl Into<String>,
+        msg_type: impl Into<String>,
+    ) -> Self {
+        Self::Unauthorized {
+            granter: granter.into(),
+            grantee: grantee.into(),
+            msg_type: msg_type.into(),
+        }
+    }
+
+    pub fn not_contract_admin(address: impl Into<String>, sender: impl Into<String>) -> Self {
+        Self::NotContractAdmin {
+            address: address.into(),
+            sender: sender.into(),
+        }
+    }
+
+    pub fn out_of_gas(limit: u64, used: u64) -> Self {
+        Self::OutOfGas {
+            limit,
+            used,
+        }
+    }
+
+    pub fn gas_limit_too_high(limit: u64, max: u64) -> Self {
+        Self::GasLimitTooHigh {
+            limit,
+            max,
+        }
+    }
+
+    pub fn invalid_event_type(ty: impl Into<String>) -> Self {
+        Self::InvalidEventType {
+            ty: ty.into(),
+        }
+    }
+
+    pub fn empty_attribute_value(key: impl Into<String>) -> Self {
+        Self::EmptyAttributeValue {
+            key: key.into(),
+        }
+    }
+
+    pub fn reserved_attribute_key(key: impl Into<String>) -> Self {
+        Self::ReservedAttributeKey {
+            key: key.into(),
+        }
+    }
+
+    pub fn not_upgrade_authority(sender: impl Into<String>) -> Self {
+        Self::NotUpgradeAuthority {
+            sender: sender.into(),
+        }
+    }
+
+    pub fn upgrade_height_passed(height: u64, current_height: u64) -> Self {
+        Self::UpgradeHeightPassed {
+            height,
+            current_height,
+        }
+    }
+
+    pub fn upgrade_already_applied(name: impl Into<String>) -> Self {
+        Self::UpgradeAlreadyApplied {
+            name: name.into(),
+        }
+    }
+
+    pub fn upgrade_needed(name: impl Into<String>, height: u64, info: Option<String>) -> Self {
+        Self::UpgradeNeeded {
+            name: name.into(),
+            height,
+            info,
+        }
+    }
+
+    pub fn upgrade_too_early(name: impl Into<String>, height: u64) -> Self {
+        Self::UpgradeTooEarly {
+            name: name.into(),
+            height,
+        }
+    }
+
+    pub fn unsupported_snapshot_format(format: u32) -> Self {
+        Self::UnsupportedSnapshotFormat {
+            format,
+        }
+    }
+
+    pub fn contract_auth_failed(address: impl Into<String>, reason: impl Into<String>) -> Self {
+        Self::ContractAuthFailed {
+            address: address.into(),
+            reason: reason.into(),
+        }
+    }
+
+    /// The code of the error, which is returned to Tendermint in the responses
+    /// of failed txs and queries. Code 0 means success, and code 1 is reserved
+    /// for errors that don't come from the state machine, e.g. malformed txs.
+    ///
+    /// Codes must not be changed once released, as clients may rely on them.
+    pub fn code(&self) -> u32 {
+        match self {
+            Error::Std(_) => 2,
+            Error::Backend(_) => 3,
+            Error::Vm(_) => 4,
+            Error::Merk(_) => 5,
+            Error::Snapshot(_) => 6,
+            Error::Address(_) => 7,
+            Error::PublicKey(_) => 8,
+            Error::SignDoc(_) => 9,
+            Error::Serde(_) => 10,
+            Error::Contract(_) => 11,
+            Error::IllegalLabel => 12,
+            Error::AccountFound {
+                ..
+            } => 13,
+            Error::AccountNotFound {
+                ..
+            } => 14,
+            Error::AccountIsContract {
+                ..
+            } => 15,
+            Error::AccountIsNotContract {
+                ..
+            } => 16,
+            Error::CodeNotFound {
+                ..
+            } => 17,
+            Error::AddressMismatch {
+                ..
+            } => 18,
+            Error::PubkeyMismatch {
+                ..
+            } => 19,
+            Error::ChainIdMismatch {
+                ..
+            } => 20,
+            Error::TxExpiredHeight {
+                ..
+            } => 21,
+            Error::TxExpiredTimestamp {
+                ..
+            } => 22,
+            Error::SequenceMismatch {
+                ..
+            } => 23,
+            Error::ContractAuthFailed {
+                ..
+            } => 24,
+            Error::FeePayerNotSpecified => 25,
+            Error::FeePayerSequenceMissing {
+                ..
+            } => 26,
+            Error::FeeAllowanceNotFound {
+                ..
+            } => 27,
+            Error::FeeAllowanceExpired {
+                ..
+            } => 28,
+            Error::FeeAllowanceExceeded {
+                ..
+            } => 29,
+            Error::GrantNotFound {
+                ..
+            } => 30,
+            Error::GrantExpired {
+                ..
+            } => 31,
+            Error::Unauthorized {
+                ..
+            } => 32,
+            Error::NestedExec => 33,
+            Error::OutOfGas {
+                ..
+            } => 34,
+            Error::InvalidEventType {
+                ..
+            } => 35,
+            Error::EmptyAttributeKey => 36,
+            Error::EmptyAttributeValue {
+                ..
+            } => 37,
+            Error::ReservedAttributeKey {
+                ..
+            } => 38,
+            Error::SubmessagesUnsupported => 39,
+            Error::FundsUnsupported => 40,
+            Error::NotContractAdmin {
+                ..
+            } => 41,
+            Error::QueryUnsupported => 42,
+            Error::UpgradesDisabled => 43,
+            Error::NotUpgradeAuthority {
+                ..
+            } => 44,
+            Error::UpgradeHeightPassed {
+                ..
+            } => 45,
+            Error::UpgradeAlreadyApplied {
+                ..
+            } => 46,
+            Error::UpgradeNeeded {
+                ..
+            } => 47,
+            Error::UpgradeTooEarly {
+                ..
+            } => 48,
+            Error::UnsupportedSnapshotFormat {
+                ..
+            } => 49,
+            Error::InvalidAppHash => 50,
+            Error::NoSnapshotOffered => 51,
+            Error::SelfFeeAllowance {
+                ..
+            } => 52,
+            Error::GasLimitTooHigh {
+                ..
+            } => 53,
+            Error::UnknownMsgType {
+                ..
+            } => 54,
+            Error::MsgTypeNotGrantable {
+                ..
+            } => 55,
+            Error::FeePayerSequenceUnexpected => 56,
+            Error::FeeDenomNotAccepted {
+                ..
+            } => 57,
+            Error::SelfGrant {
+                ..
+            } => 58,
+        }
+    }
 }
 
 pub type Result<T> = core::result::Result<T, Error>;
@@ -0,0 +1,258 @@
+//! Events in the shapes emitted by Cosmos SDK chains running wasmd, so that
+//! off-the-shelf explorers and indexers understand them.
+
+use cosmwasm_std::{attr, Addr, Attribute, Event};
+
+use cw_sdk::SdkMsg;
+
+use crate::error::{Error, Result};
+
+/// The attribute that carries the address of the contract that emitted an
+/// event. Contracts can't use attribute keys starting with an underscore, which
+/// are reserved for attributes like this one that are added by the chain.
+pub const CONTRACT_ADDRESS_KEY: &str = "_contract_address";
+
+/// The `message` event, which is emitted for every message that is executed.
+pub fn message_event(msg: &SdkMsg, sender_addr: &Addr) -> Event {
+    Event::new("message")
+        .add_attribute("action", msg.msg_type())
+        .add_attribute("sender", sender_addr)
+        .add_attribute("module", module(msg))
+}
+
+/// The Cosmos SDK module that handles the message's counterpart.
+fn module(msg: &SdkMsg) -> &'static str {
+    match msg {
+        SdkMsg::StoreCode {
+            ..
+        }
+        | SdkMsg::Instantiate {
+            ..
+        }
+        | SdkMsg::Execute {
+            ..
+        }
+        | SdkMsg::Migrate {
+            ..
+        } => "wasm",
+        SdkMsg::GrantFeeAllowance {
+            ..
+        }
+        | SdkMsg::RevokeFeeAllowance {
+            ..
+        } => "feegrant",
+        SdkMsg::Grant {
+            ..
+        }
+        | SdkMsg::Revoke {
+            ..
+        }
+        | SdkMsg::Exec {
+            ..
+        } => "authz",
+        SdkMsg::ScheduleUpgrade {
+            ..
+        } => "upgrade",
+    }
+}
+
+/// The events of a contract call, in order:
+///
+/// - the event of the call itself, e.g. `execute`, to which the contract's
+///   address is added as the first attribute;
+/// - a `wasm` event with the attributes of the contract's response, if there
+///   are any;
+/// - a `wasm-{type}` event for each custom event in the contract's response.
+///
+/// The `wasm` and custom events also carry the contract's address. Return error
+/// if the contract uses reserved attribute keys, empty attributes, or invalid
+/// event types.
+pub fn contract_events(
+    mut call: Event,
+    contract_addr: &Addr,
+    attributes: Vec<Attribute>,
+    custom_events: Vec<Event>,
+) -> Result<Vec<Event>> {
+    call.attributes.insert(0, attr(CONTRACT_ADDRESS_KEY, contract_addr));
+    let mut events = vec![call];
+
+    if !attributes.is_empty() {
+        validate_attributes(&attributes)?;
+        events.push(
+            Event::new("wasm")
+                .add_attribute(CONTRACT_ADDRESS_KEY, contract_addr)
+                .add_attributes(attributes),
+        );
+    }
+
+    for event in custom_events {
+        // the type is prefixed with `wasm-`, so it needs at least two characters
+        // to be distinguishable from the types emitted by the chain
+        if event.ty.trim().len() < 2 {
+            return Err(Error::invalid_event_type(event.ty));
+        }
+
+        validate_attributes(&event.attributes)?;
+        events.push(
+            Event::new(format!("wasm-{}", event.ty.trim()))
+                .add_attribute(CONTRACT_ADDRESS_KEY, contract_addr)
+                .add_attributes(event.attributes),
+        );
+    }
+
+    Ok(events)
+}
+
+fn validate_attributes(attributes: &[Attribute]) -> Result<()> {
+    for attr in attributes {
+        let key = attr.key.trim();
+        if key.is_empty() {
+            return Err(Error::EmptyAttributeKey);
+        }
+        if key.starts_with('_') {
+            return Err(Error::reserved_attribute_key(key));
+        }
+        if attr.value.trim().is_empty() {
+            return Err(Error::empty_attribute_value(key));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::{
+        coin,
+        testing::{mock_dependencies, mock_env, mock_info},
+    };
+    use cw_bank::msg::{Balance, InstantiateMsg};
+    use cw_sdk::BankSudoMsg;
+
+    use super::*;
+
+    fn contract_addr() -> Addr {
+        Addr::unchecked("contract")
+    }
+
+    #[test]
+    fn contract_events_in_order() {
+        let events = contract_events(
+            Event::new("execute").add_attribute("sender", "alice"),
+            &contract_addr(),
+            vec![attr("action", "transfer")],
+            vec![Event::new(" transfer ").add_attribute("amount", "100")],
+        )
+        .unwrap();
+
+        assert_eq!(events, vec![
+            Event::new("execute")
+                .add_attribute(CONTRACT_ADDRESS_KEY, "contract")
+                .add_attribute("sender", "alice"),
+            Event::new("wasm")
+                .add_attribute(CONTRACT_ADDRESS_KEY, "contract")
+                .add_attribute("action", "transfer"),
+            Event::new("wasm-transfer")
+                .add_attribute(CONTRACT_ADDRESS_KEY, "contract")
+                .add_attribute("amount", "100"),
+        ]);
+    }
+
+    #[test]
+    fn contract_events_without_attributes() {
+        // no `wasm` event is emitted if the response has no attributes
+        let events =
+            contract_events(Event::new("instantiate"), &contract_addr(), vec![], vec![]).unwrap();
+        assert_eq!(events, vec![
+            Event::new("instantiate").add_attribute(CONTRACT_ADDRESS_KEY, "contract")
+        ]);
+    }
+
+    #[test]
+    fn rejecting_invalid_attributes() {
+        let err =
+            contract_events(Event::new("execute"), &contract_addr(), vec![attr(" ", "1")], vec![])
+                .unwrap_err();
+        assert!(matches!(err, Error::EmptyAttributeKey));
+
+        let err = contract_events(
+            Event::new("execute"),
+            &contract_addr(),
+            vec![attr(CONTRACT_ADDRESS_KEY, "spoofed")],
+            vec![],
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            Error::ReservedAttributeKey {
+                key,
+            } if key == CONTRACT_ADDRESS_KEY,
+        ));
+
+        // custom events are subject to the same rules
+        let err = contract_events(
+            Event::new("execute"),
+            &contract_addr(),
+            vec![],
+            vec![Event::new("transfer").add_attribute("amount", " ")],
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            Error::EmptyAttributeValue {
+                key,
+            } if key == "amount",
+        ));
+    }
+
+    #[test]
+    fn rejecting_invalid_event_types() {
+        for ty in ["", "x", " x "] {
+            let err = contract_events(
+                Event::new("execute"),
+                &contract_addr(),
+                vec![],
+                vec![Event::new(ty).add_attribute("amount", "100")],
+            )
+            .unwrap_err();
+            assert!(matches!(err, Error::InvalidEventType {
+                ..
+            }));
+        }
+    }
+
+    /// The bank contract's response to the sudo message sent by `fee::pay_fee`
+    /// must pass the rules, or no tx with a fee could be processed.
+    #[test]
+    fn bank_sudo_events() {
+        let mut deps = mock_dependencies();
+
+        cw_bank::contract::instantiate(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("deployer", &[]),
+            InstantiateMsg {
+                owner: "deployer".into(),
+                balances: vec![Balance {
+                    address: "alice".into(),
+                    coins: vec![coin(12345, "uatom"), coin(23456, "uosmo")],
+                }],
+                namespace_cfgs: vec![],
+            },
+        )
+        .unwrap();
+
+        let res = cw_bank::contract::sudo(deps.as_mut(), mock_env(), BankSudoMsg::Transfer {
+            from: "alice".into(),
+            to: "fee_collector".into(),
+            coins: vec![coin(100, "uatom"), coin(200, "uosmo")],
+        })
+        .unwrap();
+        assert!(res.messages.is_empty());
+
+        let events =
+            contract_events(Event::new("sudo"), &contract_addr(), res.attributes, res.events)
+                .unwrap();
+        assert_eq!(events.len(), 2);
+    }
+}
@@ -1,7 +1,9 @@
 use cosmwasm_std::{Addr, Binary, ContractResult, Env, Event, MessageInfo, Response, Storage, BlockInfo, TransactionInfo, ContractInfo};
-use cosmwasm_vm::{call_instantiate, Backend, Instance, InstanceOptions, call_execute};
+use cosmwasm_vm::{
+    call_execute, call_instantiate, call_migrate, call_sudo, Backend, Instance, InstanceOptions,
+};
 
-use cw_sdk::{address, hash::sha256, Account};
+use cw_sdk::{address, hash::sha256, Account, ContractCodeOperation, ContractHistoryEntry};
 use cw_store::Cached;
 
 use tracing::{debug, info};
@@ -9,14 +11,20 @@ use tracing::{debug, info};
 use crate::{
     backend::{BackendApi, BackendQuerier, ContractSubstore},
     error::{Error, Result},
-    state::{ACCOUNTS, CODES, CODE_COUNT, code_by_address},
+    gas::{GasMeter, GAS_PER_WASM_BYTE},
+    metrics::METRICS,
+    state::{ACCOUNTS, CODES, CODE_COUNT, CONTRACT_HISTORY, code_by_address},
 };
 
+/// Save the wasm byte code to the store, charging gas for each byte.
 pub fn store_code(
     store: &mut dyn Storage,
-    sender_addr: &Addr,
     wasm_byte_code: &Binary,
+    gas: &mut GasMeter,
 ) -> Result<Event> {
+    let size: u64 = wasm_byte_code.len().try_into().unwrap_or(u64::MAX);
+    gas.consume(size.saturating_mul(GAS_PER_WASM_BYTE))?;
+
     // increment the code count
     let code_id = CODE_COUNT.update(store, |count| -> Result<_> {
         Ok(count + 1)
@@ -30,9 +38,8 @@ pub fn store_code(
     info!(target: "Stored code", id = code_id, hash = code_hash);
 
     Ok(Event::new("store_code")
-        .add_attribute("sender", sender_addr)
         .add_attribute("code_id", code_id.to_string())
-        .add_attribute("code_hash", code_hash))
+        .add_attribute("code_checksum", code_hash))
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -45,6 +52,7 @@ pub fn instantiate_contract(
     msg: &[u8],
     label: String,
     admin: Option<Addr>,
+    gas: &mut GasMeter,
 ) -> Result<ContractResult<Response>> {
     let cache = Cached::new(store);
 
@@ -76,6 +84,7 @@ pub fn instantiate_contract(
     let code = CODES.load(&cache, code_id)?;
 
     // create the wasm instance and call the instantiate entry point
+    let timer = METRICS.wasm_compile_seconds.with_label_values(&[gas.context()]).start_timer();
     let mut instance = Instance::from_code(
         &code,
         Backend {
@@ -84,12 +93,23 @@ pub fn instantiate_contract(
             querier: BackendQuerier,
         },
         InstanceOptions {
-            gas_limit: u64::MAX,
+            gas_limit: gas.remaining(),
             print_debug: true,
         },
         None,
     )?;
+    timer.observe_duration();
+
+    let timer = METRICS
+        .wasm_execute_seconds
+        .with_label_values(&[gas.context(), "instantiate"])
+        .start_timer();
     let result = call_instantiate(&mut instance, &env, info, msg)?;
+    timer.observe_duration();
+
+    // record the gas consumed by the call
+    let gas_report = instance.create_gas_report();
+    gas.consume(gas_report.limit - gas_report.remaining)?;
 
     // contract execution is finished; we recycle the cached store
     let mut cache = instance
@@ -119,6 +139,16 @@ pub fn instantiate_contract(
                 })
             })?;
 
+            record_history(
+                &mut store,
+                &contract_addr,
+                ContractCodeOperation::Instantiate,
+                code_id,
+                env.block.height,
+                &info.sender,
+                msg,
+            )?;
+
             info!(
                 target: "Instantiated contract",
                 address = contract_addr.to_string(),
@@ -139,6 +169,7 @@ pub fn execute_contract(
     env: &Env,
     info: &MessageInfo,
     msg: &[u8],
+    gas: &mut GasMeter,
 ) -> Result<ContractResult<Response>> {
     let cache = Cached::new(store);
 
@@ -146,6 +177,7 @@ pub fn execute_contract(
     let code = code_by_address(&cache, &env.contract.address)?;
 
     // create the wasm instance and call the execute entry point
+    let timer = METRICS.wasm_compile_seconds.with_label_values(&[gas.context()]).start_timer();
     let mut instance = Instance::from_code(
         &code,
         Backend {
@@ -154,12 +186,23 @@ pub fn execute_contract(
             querier: BackendQuerier,
         },
         InstanceOptions {
-            gas_limit: u64::MAX,
+            gas_limit: gas.remaining(),
             print_debug: true,
         },
         None,
     )?;
+    timer.observe_duration();
+
+    let timer = METRICS
+        .wasm_execute_seconds
+        .with_label_values(&[gas.context(), "execute"])
+        .start_timer();
     let result = call_execute(&mut instance, env, info, msg)?;
+    timer.observe_duration();
+
+    // record the gas consumed by the call
+    let gas_report = instance.create_gas_report();
+    gas.consume(gas_report.limit - gas_report.remaining)?;
 
     // contract execution is finished; we recycle the cached store
     let mut cache = instance
@@ -191,11 +234,326 @@ pub fn execute_contract(
     Ok(result)
 }
 
+pub fn sudo_contract(
+    store: impl Storage + 'static,
+    env: &Env,
+    msg: &[u8],
+    gas: &mut GasMeter,
+) -> Result<ContractResult<Response>> {
+    let cache = Cached::new(store);
+
+    // load wasm binary code
+    let code = code_by_address(&cache, &env.contract.address)?;
+
+    // create the wasm instance and call the sudo entry point
+    let timer = METRICS.wasm_compile_seconds.with_label_values(&[gas.context()]).start_timer();
+    let mut instance = Instance::from_code(
+        &code,
+        Backend {
+            api: BackendApi,
+            storage: ContractSubstore::new(cache, &env.contract.address),
+            querier: BackendQuerier,
+        },
+        InstanceOptions {
+            gas_limit: gas.remaining(),
+            print_debug: true,
+        },
+        None,
+    )?;
+    timer.observe_duration();
+
+    let timer = METRICS
+        .wasm_execute_seconds
+        .with_label_values(&[gas.context(), "sudo"])
+        .start_timer();
+    let result = call_sudo(&mut instance, env, msg)?;
+    timer.observe_duration();
+
+    // record the gas consumed by the call
+    let gas_report = instance.create_gas_report();
+    gas.consume(gas_report.limit - gas_report.remaining)?;
+
+    // contract execution is finished; we recycle the cached store
+    let mut cache = instance
+        .recycle()
+        .expect("[cw-state-machine]: failed to recycle instance")
+        .storage
+        .recycle();
+
+    // if the execution is successful, flush the state changes to the underlying store
+    match &result {
+        ContractResult::Ok(_) => {
+            cache.flush();
+            debug!(
+                target: "Sudo-executed contract",
+                address = env.contract.address.to_string(),
+            );
+        },
+        ContractResult::Err(err) => {
+            debug!(
+                target: "Failed to sudo-execute contract",
+                address = env.contract.address.to_string(),
+                reason = err,
+            );
+        }
+    }
+
+    Ok(result)
+}
+
 pub fn migrate_contract(
-    _store: impl Storage + 'static,
-    _env: &Env,
-    _code_id: u64,
-    _msg: &[u8]
+    store: impl Storage + 'static,
+    env: &Env,
+    sender_addr: &Addr,
+    code_id: u64,
+    msg: &[u8],
+    gas: &mut GasMeter,
 ) -> Result<ContractResult<Response>> {
-    todo!();
+    let cache = Cached::new(store);
+
+    // only the contract's admin can migrate it. if the contract doesn't have
+    // an admin, it can't be migrated.
+    let (label, admin) = match ACCOUNTS.may_load(&cache, &env.contract.address)? {
+        Some(Account::Contract {
+            label,
+            admin,
+            ..
+        }) => (label, admin),
+        Some(Account::Base {
+            ..
+        }) => {
+            return Err(Error::account_is_not_contract(&env.contract.address));
+        },
+        None => {
+            return Err(Error::account_not_found(&env.contract.address));
+        },
+    };
+    if admin.as_ref() != Some(sender_addr) {
+        return Err(Error::not_contract_admin(&env.contract.address, sender_addr));
+    }
+
+    // load the wasm binary code that the contract is to be migrated to
+    let code = CODES.load(&cache, code_id)?;
+
+    // create the wasm instance and call the migrate entry point
+    let timer = METRICS.wasm_compile_seconds.with_label_values(&[gas.context()]).start_timer();
+    let mut instance = Instance::from_code(
+        &code,
+        Backend {
+            api: BackendApi,
+            storage: ContractSubstore::new(cache, &env.contract.address),
+            querier: BackendQuerier,
+        },
+        InstanceOptions {
+            gas_limit: gas.remaining(),
+            print_debug: true,
+        },
+        None,
+    )?;
+    timer.observe_duration();
+
+    let timer = METRICS
+        .wasm_execute_seconds
+        .with_label_values(&[gas.context(), "migrate"])
+        .start_timer();
+    let result = call_migrate(&mut instance, env, msg)?;
+    timer.observe_duration();
+
+    // record the gas consumed by the call
+    let gas_report = instance.create_gas_report();
+    gas.consume(gas_report.limit - gas_report.remaining)?;
+
+    // contract execution is finished; we recycle the cached store
+    let mut cache = instance
+        .recycle()
+        .expect("[cw-state-machine]: failed to recycle instance")
+        .storage
+        .recycle();
+
+    // if the migration is successful, flush the state changes to the underlying
+    // store, and update the contract's code id
+    match &result {
+        ContractResult::Ok(_) => {
+            cache.flush();
+            let mut store = cache.recycle();
+
+            ACCOUNTS.save(
+                &mut store,
+                &env.contract.address,
+                &Account::Contract {
+                    code_id,
+                    label,
+                    admin,
+                },
+            )?;
+
+            record_history(
+                &mut store,
+                &env.contract.address,
+                ContractCodeOperation::Migrate,
+                code_id,
+                env.block.height,
+                sender_addr,
+                msg,
+            )?;
+
+            info!(
+                target: "Migrated contract",
+                address = env.contract.address.to_string(),
+                code_id,
+            );
+        },
+        ContractResult::Err(err) => {
+            debug!(
+                target: "Failed to migrate contract",
+                address = env.contract.address.to_string(),
+                code_id,
+                reason = err,
+            );
+        }
+    }
+
+    Ok(result)
+}
+
+/// Append an entry to the contract's code history.
+fn record_history(
+    store: &mut dyn Storage,
+    contract_addr: &Addr,
+    operation: ContractCodeOperation,
+    code_id: u64,
+    height: u64,
+    sender_addr: &Addr,
+    msg: &[u8],
+) -> Result<()> {
+    let mut history = CONTRACT_HISTORY.may_load(store, contract_addr)?.unwrap_or_default();
+
+    history.push(ContractHistoryEntry {
+        operation,
+        code_id,
+        height,
+        sender: sender_addr.into(),
+        msg_hash: hex::encode(sha256(msg)),
+    });
+
+    CONTRACT_HISTORY.save(store, contract_addr, &history).map_err(Error::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::testing::{mock_env, MockStorage};
+    use cw_store::Shared;
+
+    use super::*;
+    use crate::testing::TestAccount;
+
+    /// Save a contract account with the given admin, and return an env whose
+    /// contract is that account
+    fn setup_contract(store: &mut dyn Storage, admin: Option<Addr>) -> Env {
+        let contract_addr = address::derive_from_label("wallet").unwrap();
+        ACCOUNTS
+            .save(store, &contract_addr, &Account::Contract {
+                code_id: 1,
+                label: "wallet".into(),
+                admin,
+            })
+            .unwrap();
+
+        let mut env = mock_env();
+        env.contract.address = contract_addr;
+        env
+    }
+
+    fn migrate(store: &Shared<MockStorage>, env: &Env, sender_addr: &Addr) -> Result<()> {
+        let mut gas = GasMeter::unlimited();
+        migrate_contract(store.share(), env, sender_addr, 2, b"{}", &mut gas).map(|_| ())
+    }
+
+    #[test]
+    fn migrating_requires_admin() {
+        let mut store = Shared::new(MockStorage::new());
+        let alice = TestAccount::new(1);
+        let bob = TestAccount::new(2);
+        let env = setup_contract(&mut store, Some(alice.addr.clone()));
+
+        let err = migrate(&store, &env, &bob.addr).unwrap_err();
+        assert!(matches!(err, Error::NotContractAdmin {
+            ..
+        }));
+
+        // the admin passes the check, but the code to migrate to doesn't exist
+        let err = migrate(&store, &env, &alice.addr).unwrap_err();
+        assert!(matches!(err, Error::Std(_)));
+
+        // nothing is recorded for failed migrations
+        assert!(CONTRACT_HISTORY.may_load(&store, &env.contract.address).unwrap().is_none());
+    }
+
+    #[test]
+    fn migrating_immutable_contract() {
+        let mut store = Shared::new(MockStorage::new());
+        let alice = TestAccount::new(1);
+        let env = setup_contract(&mut store, None);
+
+        // a contract without an admin can't be migrated by anyone
+        let err = migrate(&store, &env, &alice.addr).unwrap_err();
+        assert!(matches!(err, Error::NotContractAdmin {
+            ..
+        }));
+    }
+
+    #[test]
+    fn migrating_non_contract() {
+        let mut store = Shared::new(MockStorage::new());
+        let alice = TestAccount::new(1);
+
+        let mut env = mock_env();
+        env.contract.address = address::derive_from_label("wallet").unwrap();
+        let err = migrate(&store, &env, &alice.addr).unwrap_err();
+        assert!(matches!(err, Error::AccountNotFound {
+            ..
+        }));
+
+        ACCOUNTS
+            .save(&mut store, &alice.addr, &Account::Base {
+                pubkey: alice.pubkey.clone(),
+                sequence: 1,
+            })
+            .unwrap();
+        env.contract.address = alice.addr.clone();
+        let err = migrate(&store, &env, &alice.addr).unwrap_err();
+        assert!(matches!(err, Error::AccountIsNotContract {
+            ..
+        }));
+    }
+
+    #[test]
+    fn recording_history() {
+        let mut store = MockStorage::new();
+        let alice = TestAccount::new(1);
+        let contract_addr = address::derive_from_label("wallet").unwrap();
+
+        let operations = [
+            (ContractCodeOperation::Instantiate, 1, 10, b"{\"count\":0}".as_slice()),
+            (ContractCodeOperation::Migrate, 2, 20, b"{}".as_slice()),
+        ];
+        for (operation, code_id, height, msg) in operations.clone() {
+            record_history(&mut store, &contract_addr, operation, code_id, height, &alice.addr, msg)
+                .unwrap();
+        }
+
+        // entries are appended in order
+        let history = CONTRACT_HISTORY.load(&store, &contract_addr).unwrap();
+        let expected = operations
+            .into_iter()
+            .map(|(operation, code_id, height, msg)| ContractHistoryEntry {
+                operation,
+                code_id,
+                height,
+                sender: alice.addr.to_string(),
+                msg_hash: hex::encode(sha256(msg)),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(history, expected);
+    }
 }
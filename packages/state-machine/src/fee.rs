@@ -0,0 +1,524 @@
+use cosmwasm_std::{
+    Addr, BlockInfo, Coin, ContractInfo, ContractResult, Env, Event, Response, Storage,
+};
+
+use cw_sdk::{address, label, BankSudoMsg, FeeAllowance, SignDoc, Tx};
+use cw_store::Shared;
+
+use crate::{
+    auth,
+    error::{Error, Result},
+    events, execute,
+    gas::GasMeter,
+    state::FEE_GRANTS,
+};
+
+/// Deduct the tx fee from the fee payer, and transfer it to the fee collector
+/// account, by invoking the "bank" contract's sudo entry point.
+///
+/// The fee payer is the sender unless otherwise specified by the tx, in which
+/// case, the fee payer either:
+///
+/// - co-signs the tx, in which case it is authenticated the same way as the
+///   sender, and its sequence number incremented; or
+/// - has granted a fee allowance to the sender, in which case the fee is
+///   deducted from the allowance's spend limit.
+///
+/// If `skip_signature` is true, the fee payer's signature is not verified.
+///
+/// Denoms whose transfers trigger a hook can't be used to pay fees, as the
+/// hook's messages would have to be executed.
+///
+/// Return the events emitted by the bank contract.
+pub fn pay_fee<S: Storage + 'static>(
+    mut store: Shared<S>,
+    block: &BlockInfo,
+    tx: &Tx,
+    sender_addr: &Addr,
+    skip_signature: bool,
+    gas: &mut GasMeter,
+) -> Result<Vec<Event>> {
+    // the sequence only means something if the fee payer co-signs the tx
+    if tx.body.fee_payer_sequence.is_some() && tx.fee_payer_signature.is_none() {
+        return Err(Error::FeePayerSequenceUnexpected);
+    }
+
+    let fee_payer_addr = match &tx.body.fee_payer {
+        None => sender_addr.clone(),
+        Some(fee_payer) => {
+            let fee_payer_addr = address::validate(fee_payer)?;
+
+            if let Some(signature) = &tx.fee_payer_signature {
+                let Some(sequence) = tx.body.fee_payer_sequence else {
+                    return Err(Error::fee_payer_sequence_missing(fee_payer));
+                };
+
+                auth::authenticate_base_account(
+                    &mut store,
+                    &fee_payer_addr,
+                    tx.fee_payer_pubkey.as_ref(),
+                    sequence,
+                    &SignDoc::new(tx.body.clone()),
+                    signature,
+                    skip_signature,
+                )?;
+            } else {
+                use_fee_allowance(&mut store, block, &fee_payer_addr, sender_addr, &tx.body.fee)?;
+            }
+
+            fee_payer_addr
+        },
+    };
+
+    // nothing to transfer if the tx is free
+    if tx.body.fee.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let env = Env {
+        block: block.clone(),
+        transaction: None,
+        contract: ContractInfo {
+            address: address::derive_from_label(label::BANK)?,
+        },
+    };
+
+    let fee_collector_addr = address::derive_from_label(label::FEE_COLLECTOR)?;
+
+    // the coins are transferred one denom at a time, so that a denom with a
+    // hook can be told apart from the others
+    let mut events = vec![];
+    for coin in &tx.body.fee {
+        let msg = BankSudoMsg::Transfer {
+            from: fee_payer_addr.to_string(),
+            to: fee_collector_addr.to_string(),
+            coins: vec![coin.clone()],
+        };
+
+        match execute::sudo_contract(store.share(), &env, &serde_json::to_vec(&msg)?, gas)? {
+            ContractResult::Ok(res) => {
+                events.extend(fee_transfer_events(&env.contract.address, &coin.denom, res)?);
+            },
+            ContractResult::Err(err) => return Err(Error::Contract(err)),
+        }
+    }
+
+    Ok(events)
+}
+
+/// Convert the bank contract's response to a fee transfer into events. The
+/// response must not include messages, which the bank contract returns to call
+/// the hooks of the denom.
+fn fee_transfer_events(bank_addr: &Addr, denom: &str, res: Response) -> Result<Vec<Event>> {
+    if !res.messages.is_empty() {
+        return Err(Error::fee_denom_not_accepted(denom));
+    }
+
+    events::contract_events(Event::new("sudo"), bank_addr, res.attributes, res.events)
+}
+
+/// Deduct the fee from the allowance granted by the fee payer to the sender.
+/// The allowance is deleted once its spend limit is used up.
+fn use_fee_allowance(
+    store: &mut dyn Storage,
+    block: &BlockInfo,
+    granter: &Addr,
+    grantee: &Addr,
+    fee: &[Coin],
+) -> Result<()> {
+    let Some(mut allowance) = FEE_GRANTS.may_load(store, (granter, grantee))? else {
+        return Err(Error::fee_allowance_not_found(granter, grantee));
+    };
+
+    if let Some(expiration) = allowance.expiration {
+        if block.time > expiration {
+            return Err(Error::fee_allowance_expired(granter, grantee));
+        }
+    }
+
+    if let Some(spend_limit) = &mut allowance.spend_limit {
+        if !deduct_coins(spend_limit, fee) {
+            return Err(Error::fee_allowance_exceeded(granter, grantee));
+        }
+
+        if spend_limit.is_empty() {
+            FEE_GRANTS.remove(store, (granter, grantee));
+            return Ok(());
+        }
+    }
+
+    FEE_GRANTS.save(store, (granter, grantee), &allowance)?;
+
+    Ok(())
+}
+
+/// Deduct the coins from the given balance, removing coins whose amounts reach
+/// zero. Return false if the balance is insufficient, in which case the balance
+/// may have been partially modified and should be discarded.
+fn deduct_coins(balance: &mut Vec<Coin>, coins: &[Coin]) -> bool {
+    for coin in coins {
+        let Some(index) = balance.iter().position(|c| c.denom == coin.denom) else {
+            return false;
+        };
+
+        let Ok(remaining) = balance[index].amount.checked_sub(coin.amount) else {
+            return false;
+        };
+
+        if remaining.is_zero() {
+            balance.remove(index);
+        } else {
+            balance[index].amount = remaining;
+        }
+    }
+
+    true
+}
+
+pub fn grant_fee_allowance(
+    store: &mut dyn Storage,
+    granter_addr: &Addr,
+    grantee: &str,
+    allowance: &FeeAllowance,
+) -> Result<Event> {
+    let grantee_addr = address::validate(grantee)?;
+
+    if grantee_addr == *granter_addr {
+        return Err(Error::self_fee_allowance(granter_addr));
+    }
+
+    FEE_GRANTS.save(store, (granter_addr, &grantee_addr), allowance)?;
+
+    Ok(Event::new("grant_fee_allowance")
+        .add_attribute("granter", granter_addr)
+        .add_attribute("grantee", grantee_addr))
+}
+
+pub fn revoke_fee_allowance(
+    store: &mut dyn Storage,
+    granter_addr: &Addr,
+    grantee: &str,
+) -> Result<Event> {
+    let grantee_addr = address::validate(grantee)?;
+
+    if !FEE_GRANTS.has(store, (granter_addr, &grantee_addr)) {
+        return Err(Error::fee_allowance_not_found(granter_addr, grantee_addr));
+    }
+
+    FEE_GRANTS.remove(store, (granter_addr, &grantee_addr));
+
+    Ok(Event::new("revoke_fee_allowance")
+        .add_attribute("granter", granter_addr)
+        .add_attribute("grantee", grantee_addr))
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::{
+        coin,
+        testing::{mock_dependencies, mock_env, mock_info, MockStorage},
+        Timestamp,
+    };
+    use cw_bank::msg::{Balance, InstantiateMsg, UpdateNamespaceMsg};
+    use cw_sdk::Account;
+
+    use super::*;
+    use crate::{
+        state::ACCOUNTS,
+        testing::{mock_block, TestAccount},
+    };
+
+    fn grant(
+        store: &mut dyn Storage,
+        granter: &TestAccount,
+        grantee: &TestAccount,
+        allowance: FeeAllowance,
+    ) {
+        grant_fee_allowance(store, &granter.addr, grantee.addr.as_str(), &allowance).unwrap();
+    }
+
+    #[test]
+    fn decrementing_spend_limit() {
+        let mut store = MockStorage::new();
+        let alice = TestAccount::new(1);
+        let bob = TestAccount::new(2);
+
+        grant(&mut store, &alice, &bob, FeeAllowance {
+            spend_limit: Some(vec![coin(100, "uatom"), coin(50, "uosmo")]),
+            expiration: None,
+        });
+
+        let block = mock_block(1, 0);
+        let fee = [coin(60, "uatom")];
+        use_fee_allowance(&mut store, &block, &alice.addr, &bob.addr, &fee).unwrap();
+        assert_eq!(
+            FEE_GRANTS.load(&store, (&alice.addr, &bob.addr)).unwrap().spend_limit,
+            Some(vec![coin(40, "uatom"), coin(50, "uosmo")]),
+        );
+
+        // the fee exceeds the remaining limit; the allowance is left unchanged
+        let err = use_fee_allowance(
+            &mut store,
+            &block,
+            &alice.addr,
+            &bob.addr,
+            &[coin(10, "uosmo"), coin(41, "uatom")],
+        )
+        .unwrap_err();
+        assert!(matches!(err, Error::FeeAllowanceExceeded {
+            ..
+        }));
+        assert_eq!(
+            FEE_GRANTS.load(&store, (&alice.addr, &bob.addr)).unwrap().spend_limit,
+            Some(vec![coin(40, "uatom"), coin(50, "uosmo")]),
+        );
+
+        // a denom that isn't in the spend limit can't be paid
+        let err = use_fee_allowance(&mut store, &block, &alice.addr, &bob.addr, &[coin(1, "ujuno")])
+            .unwrap_err();
+        assert!(matches!(err, Error::FeeAllowanceExceeded {
+            ..
+        }));
+
+        // the allowance is deleted once the spend limit is used up
+        let fee = [coin(40, "uatom"), coin(50, "uosmo")];
+        use_fee_allowance(&mut store, &block, &alice.addr, &bob.addr, &fee).unwrap();
+        assert!(!FEE_GRANTS.has(&store, (&alice.addr, &bob.addr)));
+    }
+
+    #[test]
+    fn unlimited_allowance() {
+        let mut store = MockStorage::new();
+        let alice = TestAccount::new(1);
+        let bob = TestAccount::new(2);
+
+        grant(&mut store, &alice, &bob, FeeAllowance {
+            spend_limit: None,
+            expiration: None,
+        });
+
+        let fee = [coin(u128::MAX, "uatom")];
+        use_fee_allowance(&mut store, &mock_block(1, 0), &alice.addr, &bob.addr, &fee).unwrap();
+        assert!(FEE_GRANTS.has(&store, (&alice.addr, &bob.addr)));
+    }
+
+    #[test]
+    fn rejecting_expired_allowance() {
+        let mut store = MockStorage::new();
+        let alice = TestAccount::new(1);
+        let bob = TestAccount::new(2);
+
+        grant(&mut store, &alice, &bob, FeeAllowance {
+            spend_limit: None,
+            expiration: Some(Timestamp::from_seconds(100)),
+        });
+
+        // the allowance can still be used at the expiration time, but not after
+        use_fee_allowance(&mut store, &mock_block(1, 100), &alice.addr, &bob.addr, &[]).unwrap();
+        let err = use_fee_allowance(&mut store, &mock_block(2, 101), &alice.addr, &bob.addr, &[])
+            .unwrap_err();
+        assert!(matches!(err, Error::FeeAllowanceExpired {
+            ..
+        }));
+    }
+
+    #[test]
+    fn rejecting_missing_allowance() {
+        let store = Shared::new(MockStorage::new());
+        let alice = TestAccount::new(1);
+        let bob = TestAccount::new(2);
+
+        // bob names alice as the fee payer, but alice neither co-signs nor has granted an allowance
+        let mut body = bob.tx_body(1, vec![]);
+        body.fee_payer = Some(alice.addr.to_string());
+        let tx = bob.sign_tx(body);
+
+        let mut gas = GasMeter::unlimited();
+        let err = pay_fee(store.share(), &mock_block(1, 0), &tx, &bob.addr, false, &mut gas)
+            .unwrap_err();
+        assert!(matches!(err, Error::FeeAllowanceNotFound {
+            ..
+        }));
+
+        // an allowance granted to someone else doesn't count
+        let charlie = TestAccount::new(3);
+        grant(&mut store.share(), &alice, &charlie, FeeAllowance {
+            spend_limit: None,
+            expiration: None,
+        });
+        let err = pay_fee(store.share(), &mock_block(1, 0), &tx, &bob.addr, false, &mut gas)
+            .unwrap_err();
+        assert!(matches!(err, Error::FeeAllowanceNotFound {
+            ..
+        }));
+
+        // revoking a missing allowance fails as well
+        let err = revoke_fee_allowance(&mut store.share(), &alice.addr, bob.addr.as_str())
+            .unwrap_err();
+        assert!(matches!(err, Error::FeeAllowanceNotFound {
+            ..
+        }));
+    }
+
+    #[test]
+    fn rejecting_self_grant() {
+        let mut store = MockStorage::new();
+        let alice = TestAccount::new(1);
+
+        let allowance = FeeAllowance {
+            spend_limit: None,
+            expiration: None,
+        };
+        let err = grant_fee_allowance(&mut store, &alice.addr, alice.addr.as_str(), &allowance)
+            .unwrap_err();
+        assert!(matches!(err, Error::SelfFeeAllowance {
+            ..
+        }));
+        assert!(!FEE_GRANTS.has(&store, (&alice.addr, &alice.addr)));
+    }
+
+    #[test]
+    fn co_signing_fee_payer() {
+        let store = Shared::new(MockStorage::new());
+        let alice = TestAccount::new(1);
+        let bob = TestAccount::new(2);
+
+        let co_signed_tx = |fee_payer_sequence: u64| {
+            let mut body = bob.tx_body(1, vec![]);
+            body.fee_payer = Some(alice.addr.to_string());
+            body.fee_payer_sequence = Some(fee_payer_sequence);
+            Tx {
+                fee_payer_pubkey: Some(alice.pubkey.clone()),
+                fee_payer_signature: Some(alice.sign(&body)),
+                ..bob.sign_tx(body)
+            }
+        };
+
+        // the fee payer's sequence must match that of its account
+        let mut gas = GasMeter::unlimited();
+        let tx = co_signed_tx(2);
+        let err = pay_fee(store.share(), &mock_block(1, 0), &tx, &bob.addr, false, &mut gas)
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            Error::SequenceMismatch {
+                expect: 1,
+                found: 2,
+                ..
+            },
+        ));
+        assert!(ACCOUNTS.may_load(&store, &alice.addr).unwrap().is_none());
+
+        // the fee is free here, so the bank contract isn't invoked
+        let tx = co_signed_tx(1);
+        let events = pay_fee(store.share(), &mock_block(1, 0), &tx, &bob.addr, false, &mut gas)
+            .unwrap();
+        assert!(events.is_empty());
+        assert_eq!(
+            ACCOUNTS.load(&store, &alice.addr).unwrap(),
+            Account::Base {
+                pubkey: alice.pubkey.clone(),
+                sequence: 1,
+            },
+        );
+
+        // the fee payer's co-signature can't be replayed
+        let err = pay_fee(store.share(), &mock_block(1, 0), &tx, &bob.addr, false, &mut gas)
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            Error::SequenceMismatch {
+                expect: 2,
+                found: 1,
+                ..
+            },
+        ));
+
+        // the co-signing fee payer must provide its sequence
+        let mut tx = co_signed_tx(1);
+        tx.body.fee_payer_sequence = None;
+        let err = pay_fee(store.share(), &mock_block(1, 0), &tx, &bob.addr, false, &mut gas)
+            .unwrap_err();
+        assert!(matches!(err, Error::FeePayerSequenceMissing {
+            ..
+        }));
+    }
+
+    #[test]
+    fn rejecting_unexpected_fee_payer_sequence() {
+        let store = Shared::new(MockStorage::new());
+        let alice = TestAccount::new(1);
+        let bob = TestAccount::new(2);
+
+        grant(&mut store.share(), &alice, &bob, FeeAllowance {
+            spend_limit: None,
+            expiration: None,
+        });
+
+        // the fee is paid from alice's allowance, so the sequence is meaningless
+        let mut body = bob.tx_body(1, vec![]);
+        body.fee_payer = Some(alice.addr.to_string());
+        body.fee_payer_sequence = Some(1);
+        let tx = bob.sign_tx(body);
+
+        let mut gas = GasMeter::unlimited();
+        let err = pay_fee(store.share(), &mock_block(1, 0), &tx, &bob.addr, false, &mut gas)
+            .unwrap_err();
+        assert!(matches!(err, Error::FeePayerSequenceUnexpected));
+
+        // the same goes if the sender pays the fee
+        let mut body = bob.tx_body(1, vec![]);
+        body.fee_payer_sequence = Some(1);
+        let tx = bob.sign_tx(body);
+
+        let err = pay_fee(store.share(), &mock_block(1, 0), &tx, &bob.addr, false, &mut gas)
+            .unwrap_err();
+        assert!(matches!(err, Error::FeePayerSequenceUnexpected));
+    }
+
+    #[test]
+    fn rejecting_hooked_fee_denom() {
+        let mut deps = mock_dependencies();
+
+        cw_bank::contract::instantiate(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("deployer", &[]),
+            InstantiateMsg {
+                owner: "deployer".into(),
+                balances: vec![Balance {
+                    address: "alice".into(),
+                    coins: vec![coin(100, "uatom"), coin(100, "factory/alice/uastro")],
+                }],
+                namespace_cfgs: vec![UpdateNamespaceMsg {
+                    namespace: "factory".into(),
+                    admin: None,
+                    after_transfer_hook: Some("hook".into()),
+                }],
+            },
+        )
+        .unwrap();
+
+        let transfer = |denom: &str| BankSudoMsg::Transfer {
+            from: "alice".into(),
+            to: "fee_collector".into(),
+            coins: vec![coin(10, denom)],
+        };
+        let bank_addr = Addr::unchecked("bank");
+
+        let res = cw_bank::contract::sudo(deps.as_mut(), mock_env(), transfer("uatom")).unwrap();
+        let events = fee_transfer_events(&bank_addr, "uatom", res).unwrap();
+        assert_eq!(events.len(), 2);
+
+        let res =
+            cw_bank::contract::sudo(deps.as_mut(), mock_env(), transfer("factory/alice/uastro"))
+                .unwrap();
+        let err = fee_transfer_events(&bank_addr, "factory/alice/uastro", res).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::FeeDenomNotAccepted {
+                denom,
+            } if denom == "factory/alice/uastro",
+        ));
+    }
+}
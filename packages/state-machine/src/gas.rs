@@ -0,0 +1,177 @@
+use crate::{
+    error::{Error, Result},
+    metrics,
+};
+
+/// Gas limit of txs that don't specify one.
+///
+/// Gas is measured in the unit used by cosmwasm-vm, where 1 Teragas takes
+/// roughly 1 millisecond to execute.
+pub const DEFAULT_TX_GAS_LIMIT: u64 = 100_000_000_000_000;
+
+/// The highest gas limit a tx may specify.
+pub const MAX_TX_GAS_LIMIT: u64 = 1_000_000_000_000_000;
+
+/// Gas charged for each byte of wasm byte code uploaded.
+pub const GAS_PER_WASM_BYTE: u64 = 420_000_000;
+
+/// Gas charged for each byte of the keys and values written to the store by
+/// contracts.
+pub const GAS_PER_BYTE_WRITTEN: u64 = 4_200_000_000;
+
+/// Gas limit of smart queries, so that queries served by the node, e.g. over
+/// the REST API, can't run forever.
+pub const QUERY_GAS_LIMIT: u64 = DEFAULT_TX_GAS_LIMIT;
+
+/// The gas limits applied to txs in DeliverTx.
+///
+/// This affects which txs succeed, so all nodes of a network must use the same
+/// config.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GasConfig {
+    /// Gas limit of txs that don't specify one
+    pub default_tx_gas_limit: u64,
+    /// The highest gas limit a tx may specify
+    pub max_tx_gas_limit: u64,
+}
+
+impl Default for GasConfig {
+    fn default() -> Self {
+        Self {
+            default_tx_gas_limit: DEFAULT_TX_GAS_LIMIT,
+            max_tx_gas_limit: MAX_TX_GAS_LIMIT,
+        }
+    }
+}
+
+impl GasConfig {
+    /// The gas limit of a tx, given the limit it specifies, if any. Return
+    /// error if the limit exceeds the maximum.
+    pub fn tx_gas_limit(&self, gas_limit: Option<u64>) -> Result<u64> {
+        match gas_limit {
+            Some(limit) if limit > self.max_tx_gas_limit => {
+                Err(Error::gas_limit_too_high(limit, self.max_tx_gas_limit))
+            },
+            Some(limit) => Ok(limit),
+            None => Ok(self.default_tx_gas_limit.min(self.max_tx_gas_limit)),
+        }
+    }
+}
+
+/// Keeps track of the gas consumed by the contract calls and code uploads made
+/// during the processing of a tx, so that the total does not exceed the tx's
+/// gas limit.
+pub struct GasMeter {
+    limit: u64,
+    used: u64,
+    /// Whether the gas is consumed delivering txs or serving queries, by which
+    /// the durations of wasm calls are labelled in the metrics
+    context: &'static str,
+}
+
+impl GasMeter {
+    pub fn new(limit: u64) -> Self {
+        Self {
+            limit,
+            used: 0,
+            context: metrics::DELIVER_TX,
+        }
+    }
+
+    /// A gas meter that never runs out, used for genesis messages.
+    pub fn unlimited() -> Self {
+        Self::new(u64::MAX)
+    }
+
+    /// A gas meter used for simulations, which are queries as far as the
+    /// metrics are concerned.
+    pub fn simulation(limit: u64) -> Self {
+        Self {
+            context: metrics::QUERY,
+            ..Self::new(limit)
+        }
+    }
+
+    pub fn context(&self) -> &'static str {
+        self.context
+    }
+
+    pub fn used(&self) -> u64 {
+        self.used
+    }
+
+    /// The amount of gas that can still be consumed. This is to be used as the
+    /// gas limit of the next contract call.
+    pub fn remaining(&self) -> u64 {
+        self.limit.saturating_sub(self.used)
+    }
+
+    /// Record the consumption of the given amount of gas. Return error if the
+    /// total exceeds the limit.
+    pub fn consume(&mut self, amount: u64) -> Result<()> {
+        self.used = self.used.saturating_add(amount);
+        if self.used > self.limit {
+            return Err(Error::out_of_gas(self.limit, self.used));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn consuming_gas() {
+        let mut gas = GasMeter::new(100);
+        gas.consume(60).unwrap();
+        assert_eq!(gas.used(), 60);
+        assert_eq!(gas.remaining(), 40);
+
+        // consuming exactly the remaining amount is fine
+        gas.consume(40).unwrap();
+        assert_eq!(gas.remaining(), 0);
+
+        let err = gas.consume(1).unwrap_err();
+        assert!(matches!(err, Error::OutOfGas {
+            limit: 100,
+            used: 101,
+        }));
+        assert_eq!(gas.remaining(), 0);
+    }
+
+    #[test]
+    fn consuming_gas_without_overflow() {
+        let mut gas = GasMeter::unlimited();
+        gas.consume(u64::MAX).unwrap();
+        assert_eq!(gas.remaining(), 0);
+
+        // the amount used saturates instead of overflowing
+        let err = gas.consume(u64::MAX).unwrap_err();
+        assert!(matches!(err, Error::OutOfGas {
+            ..
+        }));
+        assert_eq!(gas.used(), u64::MAX);
+    }
+
+    #[test]
+    fn applying_tx_gas_limits() {
+        let config = GasConfig {
+            default_tx_gas_limit: 100,
+            max_tx_gas_limit: 1000,
+        };
+        assert_eq!(config.tx_gas_limit(None).unwrap(), 100);
+        assert_eq!(config.tx_gas_limit(Some(1000)).unwrap(), 1000);
+        assert!(matches!(config.tx_gas_limit(Some(1001)).unwrap_err(), Error::GasLimitTooHigh {
+            limit: 1001,
+            max: 1000,
+        }));
+
+        // the default limit is capped by the maximum
+        let config = GasConfig {
+            default_tx_gas_limit: 2000,
+            max_tx_gas_limit: 1000,
+        };
+        assert_eq!(config.tx_gas_limit(None).unwrap(), 1000);
+    }
+}
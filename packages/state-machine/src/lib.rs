@@ -1,20 +1,38 @@
 pub mod auth;
+pub mod authz;
 pub mod backend;
 pub mod error;
+pub mod events;
 pub mod execute;
+pub mod fee;
+pub mod gas;
+pub mod genesis;
+pub mod metrics;
 pub mod query;
 pub mod state;
+pub mod upgrade;
+
+#[cfg(test)]
+mod testing;
+
+use std::path::Path;
 
 use cosmwasm_std::{
     to_binary, Addr, Binary, BlockInfo, ContractInfo, Env, Event, MessageInfo, Storage, Timestamp,
     TransactionInfo,
 };
-use cw_sdk::{address, hash::HASH_LENGTH, label::resolve_raw_address, GenesisState, SdkMsg, SdkQuery, Tx};
-use cw_store::{Cached, Shared, Store};
+use cw_sdk::{
+    address, hash::HASH_LENGTH, label::resolve_raw_address, GenesisState, SdkMsg, SdkQuery,
+    SimulateResponse, Tx,
+};
+use cw_store::{Cached, Restorer, Shared, SnapshotInfo, SnapshotManager, Store, SNAPSHOT_FORMAT};
+use tracing::{error, info};
 
 use crate::{
     error::{Error, Result},
-    state::{ACCOUNTS, BLOCK, CODE_COUNT},
+    gas::{GasConfig, GasMeter},
+    metrics::{self, METRICS},
+    state::{BLOCK, CODE_COUNT, UPGRADE_AUTHORITY},
 };
 
 pub struct StateMachine {
@@ -35,27 +53,84 @@ pub struct StateMachine {
     ///   state using the BLOCK storage constant.
     pending_block: Option<BlockInfo>,
 
+    /// The number of txs delivered in the pending block, which is recorded in
+    /// the metrics upon commit.
+    pending_txs: u64,
+
+    /// Takes snapshots of the store periodically, which are served to nodes
+    /// that are state syncing. None if snapshots are disabled.
+    snapshots: Option<SnapshotManager>,
+
+    /// The snapshot being restored, if the node is state syncing.
+    ///
+    /// Set to Some during the OfferSnapshot ABCI method, if the snapshot is
+    /// accepted; set back to None once all chunks have been applied.
+    restorer: Option<Restorer>,
+
+    /// The gas limits applied to txs in DeliverTx.
+    gas_config: GasConfig,
+
     // TODO: load pinned contracts and codes
 }
 
+/// The outcome of a tx that has been authenticated and has paid its fee.
+struct TxOutcome {
+    /// Events emitted during authentication, fee payment, and by the messages
+    events: Vec<Event>,
+    /// The data returned by each message that was executed
+    data: Vec<Option<Binary>>,
+    /// The result of executing the messages
+    result: Result<()>,
+}
+
 impl StateMachine {
     pub fn new(store: Store) -> Self {
         Self {
             store,
             pending_block: None,
+            pending_txs: 0,
+            snapshots: None,
+            restorer: None,
+            gas_config: GasConfig::default(),
         }
     }
 
+    /// Enable taking snapshots of the store for state sync.
+    pub fn with_snapshots(mut self, snapshots: SnapshotManager) -> Self {
+        self.snapshots = Some(snapshots);
+        self
+    }
+
+    /// Override the default gas limits of txs.
+    pub fn with_gas_config(mut self, gas_config: GasConfig) -> Self {
+        self.gas_config = gas_config;
+        self
+    }
+
     /// Decode genesis bytes and run genesis messages. Return app hash.
     ///
     /// TODO: Once a staking contract is created, return the validator set as well
     pub fn init_chain(&self, chain_id: String, gen_state: GenesisState) -> Result<[u8; HASH_LENGTH]> {
+        self.init_chain_inspect(chain_id, gen_state, |_, _| ())
+    }
+
+    /// Same as `init_chain`, but the index and events of each genesis message
+    /// are passed to `on_msg` once the message is executed successfully. This
+    /// is used to check a genesis state without starting a node.
+    pub fn init_chain_inspect(
+        &self,
+        chain_id: String,
+        gen_state: GenesisState,
+        mut on_msg: impl FnMut(usize, Vec<Event>),
+    ) -> Result<[u8; HASH_LENGTH]> {
         // make a cache of the store. only flush it if the entire init chain
         // flow is successful.
         // additionally, wrap the cached store in `Rc<RefCell<T>>` so that it
         // can be shared across the execution of multiple messages.
         let mut cache = Shared::new(Cached::new(self.store.pending_wrap()));
 
+        let deployer_addr = address::validate(&gen_state.deployer)?;
+
         let block = BlockInfo {
             height: 0,
             time: Timestamp::default(),
@@ -65,18 +140,32 @@ impl StateMachine {
         BLOCK.save(&mut cache, &block)?;
         CODE_COUNT.save(&mut cache, &0)?;
 
-        let deployer_addr = address::validate(&gen_state.deployer)?;
+        if let Some(upgrade_authority) = &gen_state.upgrade_authority {
+            UPGRADE_AUTHORITY.save(&mut cache, upgrade_authority)?;
+        }
+
+        // import the state exported from another chain, if provided
+        if let Some(snapshot) = gen_state.snapshot {
+            genesis::import(&mut cache, snapshot)?;
+        }
 
         // execute messages in order.
-        // ResponseInitChain doesn't take events, so we discard the emitted events here.
-        for msg in gen_state.msgs {
-            self.handle_msg(
+        // ResponseInitChain doesn't take events, so the emitted events are only
+        // passed to the callback.
+        //
+        // genesis messages are not subject to gas limits
+        let mut gas = GasMeter::unlimited();
+
+        for (idx, msg) in gen_state.msgs.into_iter().enumerate() {
+            let (events, _) = self.handle_msg(
                 cache.share(),
                 block.clone(),
                 None,
                 &deployer_addr,
                 msg,
+                &mut gas,
             )?;
+            on_msg(idx, events);
         }
 
         // init chain is successful; flush the state changes
@@ -86,68 +175,178 @@ impl StateMachine {
     }
 
     pub fn begin_block(&mut self, block: BlockInfo) -> Result<Vec<Event>> {
-        // current we just update pending block and do nothing else
-        // TODO: read cosmos-sdk code and see what to do here
+        let mut events = vec![];
+
+        // apply the scheduled chain upgrade if it's due. if this binary can't
+        // apply it, an error is returned, which halts the chain
+        let mut cache = Cached::new(self.store.pending_wrap());
+        if let Some(event) = upgrade::maybe_apply_upgrade(&mut cache, &block)? {
+            events.push(event);
+        }
+        cache.flush();
+
+        // TODO: read cosmos-sdk code and see what else to do here
         self.pending_block = Some(block);
+        self.pending_txs = 0;
 
-        Ok(vec![])
+        Ok(events)
     }
 
-    pub fn deliver_tx(&self, tx: Tx) -> Result<Vec<Event>> {
-        // make a cache of the store. it will only be flushed if the entire tx
-        // is successful
-        let mut cache = Cached::new(self.store.pending_wrap());
+    /// Check a tx before it is admitted into the mempool.
+    ///
+    /// For now only the checks that don't depend on the sender's account are
+    /// performed, i.e. gas limit, chain id and timeouts. They are done against
+    /// the block that would follow the last committed one, assuming the same
+    /// block time.
+    pub fn check_tx(&self, tx: &Tx) -> Result<()> {
+        self.gas_config.tx_gas_limit(tx.body.gas_limit)?;
 
-        // authenticate signature, chain id, sequence, etc.
-        let sender = auth::authenticate_tx(&cache, self.pending_block.as_ref().unwrap(), &tx)?;
+        let mut block = BLOCK.load(&self.store.wrap())?;
+        block.height += 1;
+
+        auth::validate_tx(&block, tx)
+    }
 
-        // update the sender's account in the store
-        ACCOUNTS.save(&mut cache, &sender.address, &sender.account)?;
+    pub fn deliver_tx(&mut self, tx: Tx) -> Result<Vec<Event>> {
+        self.pending_txs += 1;
 
-        // wrap the cached store in a `Rc<RefCell<T>>` so that it can be shared
-        // as an owned value across the execution of multiple messages
-        let mut cache = Shared::new(cache);
+        let block = self.pending_block.as_ref().unwrap();
 
-        let mut events = vec![];
+        // make a cache of the store. wrap it in a `Rc<RefCell<T>>` so that it
+        // can be shared as an owned value across authentication, fee payment,
+        // and the execution of multiple messages
+        let mut cache = Shared::new(Cached::new(self.store.pending_wrap()));
 
-        tx
+        // txs that don't specify a gas limit get the default one; those that
+        // specify one above the maximum are rejected
+        let mut gas = GasMeter::new(0);
+
+        let result = self
+            .gas_config
+            .tx_gas_limit(tx.body.gas_limit)
+            .and_then(|gas_limit| {
+                gas = GasMeter::new(gas_limit);
+                self.run_tx(&cache, block, tx, false, &mut gas)
+            })
+            .and_then(|outcome| {
+                // once the tx is authenticated and the fee paid, the sequence
+                // number increments and the fee payment are committed even if
+                // the messages fail, so that failed txs can't be replayed, and
+                // are not free.
+                cache.borrow_mut().flush();
+
+                outcome.result.map(|_| outcome.events)
+            });
+
+        METRICS.observe_tx(&result, gas.used());
+
+        result
+    }
+
+    /// Run a tx against the last committed state, in the block that would
+    /// follow the last committed one, assuming the same block time. The state
+    /// changes are discarded.
+    ///
+    /// Unlike in DeliverTx, the tx is not subject to its gas limit, so that the
+    /// amount of gas it consumes can be found out. It's subject to the maximum
+    /// gas limit instead, as a tx can't consume more than that anyway.
+    fn simulate(&self, tx: Tx, skip_signature: bool) -> Result<SimulateResponse> {
+        let mut block = BLOCK.load(&self.store.wrap())?;
+        block.height += 1;
+
+        // the cache is never flushed, so a read-only wrapper of the store works
+        let cache = Shared::new(Cached::new(self.store.wrap()));
+
+        let mut gas = GasMeter::simulation(self.gas_config.max_tx_gas_limit);
+
+        let outcome = self.run_tx(&cache, &block, tx, skip_signature, &mut gas)?;
+        outcome.result?;
+
+        let writes = cache.borrow().pending_keys().into_iter().map(Binary::from).collect();
+
+        Ok(SimulateResponse {
+            events: outcome.events,
+            data: outcome.data,
+            gas_used: gas.used(),
+            writes,
+        })
+    }
+
+    /// Authenticate the tx, deduct the fee, and execute the messages.
+    ///
+    /// Return error if authentication or fee payment fails, in which case the
+    /// cache should be discarded. Otherwise, the result of executing the
+    /// messages is included in the outcome. The state changes made by the
+    /// messages are written to the cache only if all of them are successful.
+    fn run_tx<T: Storage + 'static>(
+        &self,
+        cache: &Shared<T>,
+        block: &BlockInfo,
+        tx: Tx,
+        skip_signature: bool,
+        gas: &mut GasMeter,
+    ) -> Result<TxOutcome> {
+        // authenticate signature, chain id, sequence, etc.
+        // if successful, the sender's account is updated in the store
+        let (sender_addr, mut events) =
+            auth::authenticate_tx(cache.share(), block, &tx, skip_signature, gas)?;
+
+        // deduct the fee from the fee payer
+        events.extend(fee::pay_fee(cache.share(), block, &tx, &sender_addr, skip_signature, gas)?);
+        let mut data = vec![];
+
+        // make another layer of cache for executing the messages. it will only
+        // be flushed if all messages are successful.
+        let mut msg_cache = Shared::new(Cached::new(cache.share()));
+
+        let result = tx
             .body
             .msgs
             .into_iter()
-            .map(|msg| {
-                self.handle_msg(
-                    cache.share(),
-                    self.pending_block.clone().unwrap(),
+            .try_for_each(|msg| -> Result<_> {
+                let (msg_events, msg_data) = self.handle_msg(
+                    msg_cache.share(),
+                    block.clone(),
                     None,
-                    &sender.address,
+                    &sender_addr,
                     msg,
-                )
-            })
-            .try_for_each(|res| -> Result<_> {
-                events.extend(res?);
+                    gas,
+                )?;
+                events.extend(msg_events);
+                data.push(msg_data);
                 Ok(())
-            })?;
+            });
 
-        // tx is successful: flush the state changes
-        cache.borrow_mut().flush();
+        if result.is_ok() {
+            msg_cache.borrow_mut().flush();
+        }
 
-        Ok(events)
+        Ok(TxOutcome {
+            events,
+            data,
+            result,
+        })
     }
 
-    fn handle_msg(
+    fn handle_msg<S: Storage + 'static>(
         &self,
-        mut store: impl Storage + 'static,
+        mut store: Shared<S>,
         block: BlockInfo,
         transaction: Option<TransactionInfo>,
         sender_addr: &Addr,
         msg: SdkMsg,
-    ) -> Result<Vec<Event>> {
-        match msg {
+        gas: &mut GasMeter,
+    ) -> Result<(Vec<Event>, Option<Binary>)> {
+        // every message emits a `message` event, preceding those emitted while
+        // executing it
+        let message_event = events::message_event(&msg, sender_addr);
+
+        let (mut events, data) = match msg {
             SdkMsg::StoreCode {
                 wasm_byte_code,
             } => {
-                let event = execute::store_code(&mut store, sender_addr, &wasm_byte_code)?;
-                Ok(vec![event])
+                let event = execute::store_code(&mut store, &wasm_byte_code, gas)?;
+                (vec![event], None)
             },
             SdkMsg::Instantiate {
                 code_id,
@@ -157,6 +356,7 @@ impl StateMachine {
                 admin,
             } => {
                 let admin_addr = admin.map(|admin| resolve_raw_address(&admin)).transpose()?;
+                let contract_addr = address::derive_from_label(&label)?;
 
                 if !funds.is_empty() {
                     return Err(Error::FundsUnsupported);
@@ -166,7 +366,7 @@ impl StateMachine {
                     funds,
                 };
 
-                let result = execute::instantiate_contract(
+                let res = execute::instantiate_contract(
                     store,
                     block,
                     transaction,
@@ -175,16 +375,20 @@ impl StateMachine {
                     &serde_json::to_vec(&msg)?,
                     label,
                     admin_addr,
+                    gas,
                 )?
-                .into_result();
+                .into_result()
+                .map_err(Error::Contract)?;
 
-                if let Ok(res) = &result {
-                    if !res.messages.is_empty() {
-                        return Err(Error::SubmessagesUnsupported);
-                    }
+                if !res.messages.is_empty() {
+                    return Err(Error::SubmessagesUnsupported);
                 }
 
-                result.map(|res| res.events).map_err(Error::Contract)
+                let call = Event::new("instantiate").add_attribute("code_id", code_id.to_string());
+                let events =
+                    events::contract_events(call, &contract_addr, res.attributes, res.events)?;
+
+                (events, res.data)
             },
             SdkMsg::Execute {
                 contract,
@@ -207,21 +411,28 @@ impl StateMachine {
                     funds,
                 };
 
-                let result = execute::execute_contract(
+                let res = execute::execute_contract(
                     store,
                     &env,
                     &info,
                     &serde_json::to_vec(&msg)?,
+                    gas,
                 )?
-                .into_result();
+                .into_result()
+                .map_err(Error::Contract)?;
 
-                if let Ok(res) = &result {
-                    if !res.messages.is_empty() {
-                        return Err(Error::SubmessagesUnsupported);
-                    }
+                if !res.messages.is_empty() {
+                    return Err(Error::SubmessagesUnsupported);
                 }
 
-                result.map(|res| res.events).map_err(Error::Contract)
+                let events = events::contract_events(
+                    Event::new("execute"),
+                    &env.contract.address,
+                    res.attributes,
+                    res.events,
+                )?;
+
+                (events, res.data)
             },
             SdkMsg::Migrate {
                 contract,
@@ -236,27 +447,124 @@ impl StateMachine {
                     },
                 };
 
-                let result = execute::migrate_contract(
+                let res = execute::migrate_contract(
                     store,
                     &env,
+                    sender_addr,
                     code_id,
                     &serde_json::to_vec(&msg)?,
+                    gas,
                 )?
-                .into_result();
+                .into_result()
+                .map_err(Error::Contract)?;
 
-                if let Ok(res) = &result {
-                    if !res.messages.is_empty() {
-                        return Err(Error::SubmessagesUnsupported);
-                    }
+                if !res.messages.is_empty() {
+                    return Err(Error::SubmessagesUnsupported);
                 }
 
-                result.map(|res| res.events).map_err(Error::Contract)
+                let call = Event::new("migrate").add_attribute("code_id", code_id.to_string());
+                let events = events::contract_events(
+                    call,
+                    &env.contract.address,
+                    res.attributes,
+                    res.events,
+                )?;
+
+                (events, res.data)
             },
-        }
+            SdkMsg::GrantFeeAllowance {
+                grantee,
+                allowance,
+            } => {
+                let event = fee::grant_fee_allowance(&mut store, sender_addr, &grantee, &allowance)?;
+                (vec![event], None)
+            },
+            SdkMsg::RevokeFeeAllowance {
+                grantee,
+            } => {
+                let event = fee::revoke_fee_allowance(&mut store, sender_addr, &grantee)?;
+                (vec![event], None)
+            },
+            SdkMsg::Grant {
+                grantee,
+                authorization,
+                expiration,
+            } => {
+                let event =
+                    authz::grant(&mut store, sender_addr, &grantee, authorization, expiration)?;
+                (vec![event], None)
+            },
+            SdkMsg::Revoke {
+                grantee,
+            } => {
+                let event = authz::revoke(&mut store, sender_addr, &grantee)?;
+                (vec![event], None)
+            },
+            SdkMsg::Exec {
+                granter,
+                msgs,
+            } => {
+                let granter_addr = address::validate(&granter)?;
+
+                let mut events = vec![Event::new("exec")
+                    .add_attribute("granter", &granter_addr)
+                    .add_attribute("grantee", sender_addr)];
+                let mut data = vec![];
+
+                // each message must be permitted by the grant, and is executed
+                // as if sent by the granter
+                for msg in msgs {
+                    authz::check_grant(&store, &block, &granter_addr, sender_addr, &msg)?;
+
+                    let (msg_events, msg_data) = self.handle_msg(
+                        store.share(),
+                        block.clone(),
+                        transaction.clone(),
+                        &granter_addr,
+                        msg,
+                        gas,
+                    )?;
+                    events.extend(msg_events);
+                    data.push(msg_data);
+                }
+
+                // the data returned by the individual messages, in order
+                (events, Some(to_binary(&data)?))
+            },
+            SdkMsg::ScheduleUpgrade {
+                name,
+                height,
+                info,
+            } => {
+                let event =
+                    upgrade::schedule_upgrade(&mut store, &block, sender_addr, name, height, info)?;
+                (vec![event], None)
+            },
+        };
+
+        events.insert(0, message_event);
+
+        Ok((events, data))
     }
 
-    pub fn info(&self) -> Result<(i64, [u8; HASH_LENGTH])> {
-        let block = BLOCK.may_load(&self.store.wrap())?;
+    /// Export the last committed state as a genesis state, which includes the
+    /// snapshot and no messages. The deployer is left empty, and must be set
+    /// before the genesis state can be used.
+    pub fn export_genesis(&self) -> Result<GenesisState> {
+        let store = self.store.wrap();
+        Ok(GenesisState {
+            deployer: String::new(),
+            upgrade_authority: UPGRADE_AUTHORITY.may_load(&store)?,
+            snapshot: Some(genesis::export(&store)?),
+            msgs: vec![],
+        })
+    }
+
+    /// Return the last committed block height, the app hash, and the version
+    /// of the application.
+    pub fn info(&self) -> Result<(i64, [u8; HASH_LENGTH], u64)> {
+        let store = self.store.wrap();
+        let block = BLOCK.may_load(&store)?;
         let app_hash = self.store.root_hash();
         Ok((
             // when initializing a new chain scratch, Tendermint sends an Info
@@ -265,6 +573,7 @@ impl StateMachine {
             // we do unwrap_or(0)
             block.map(|b| b.height as i64).unwrap_or(0),
             app_hash,
+            upgrade::app_version(&store)?,
         ))
     }
 
@@ -286,6 +595,19 @@ impl StateMachine {
                 start_after,
                 limit,
             } => to_binary(&query::contracts(&store, start_after, limit)?),
+            SdkQuery::ContractsByCode {
+                code_id,
+                start_after,
+                limit,
+            } => to_binary(&query::contracts_by_code(&store, code_id, start_after, limit)?),
+            SdkQuery::ContractsByAdmin {
+                admin,
+                start_after,
+                limit,
+            } => to_binary(&query::contracts_by_admin(&store, admin, start_after, limit)?),
+            SdkQuery::ContractHistory {
+                contract,
+            } => to_binary(&query::contract_history(&store, &contract)?),
             SdkQuery::Code {
                 code_id,
             } => to_binary(&query::code(&store, code_id)?),
@@ -293,14 +615,45 @@ impl StateMachine {
                 start_after,
                 limit,
             } => to_binary(&query::codes(&store, start_after, limit)?),
+            SdkQuery::FeeAllowance {
+                granter,
+                grantee,
+            } => to_binary(&query::fee_allowance(&store, granter, grantee)?),
+            SdkQuery::FeeAllowances {
+                granter,
+                start_after,
+                limit,
+            } => to_binary(&query::fee_allowances(&store, granter, start_after, limit)?),
+            SdkQuery::Grants {
+                granter,
+                start_after,
+                limit,
+            } => to_binary(&query::grants(&store, granter, start_after, limit)?),
             SdkQuery::WasmRaw {
                 contract,
                 key,
             } => to_binary(&query::wasm_raw(store, &contract, &key)?),
+            SdkQuery::WasmRawRange {
+                contract,
+                start_after,
+                end,
+                limit,
+                order,
+            } => {
+                to_binary(&query::wasm_raw_range(store, &contract, start_after, end, limit, order)?)
+            },
             SdkQuery::WasmSmart {
                 contract,
                 msg,
             } => to_binary(&query::wasm_smart(store, &contract, &serde_json::to_vec(&msg)?)?),
+            SdkQuery::Simulate {
+                tx,
+                skip_signature,
+            } => to_binary(&self.simulate(tx, skip_signature)?),
+            SdkQuery::UpgradePlan {} => to_binary(&query::upgrade_plan(&store)?),
+            SdkQuery::AppliedUpgrade {
+                name,
+            } => to_binary(&query::applied_upgrade(&store, &name)?),
         }
         .map_err(Error::from)
     }
@@ -313,9 +666,122 @@ impl StateMachine {
         self.pending_block = None;
 
         // commit pending ops to the underlying store
+        let timer = METRICS.store_commit_seconds.start_timer();
         self.store.commit()?;
+        timer.observe_duration();
 
         // return the block height and app hash that was just committed
-        self.info()
+        let (height, app_hash, _) = self.info()?;
+
+        METRICS.block_height.set(height);
+        METRICS.block_txs.observe(self.pending_txs as f64);
+        // measuring the store's size walks its directory, so it's not done in
+        // every block, except for the first one after the node starts
+        if height % metrics::STORE_SIZE_INTERVAL == 0 || METRICS.store_size_bytes.get() == 0 {
+            if let Ok(size) = self.store.size_on_disk() {
+                METRICS.store_size_bytes.set(size as i64);
+            }
+        }
+
+        // take a snapshot if one is due. failing to take a snapshot doesn't
+        // affect the chain's state, so we log the error instead of halting
+        if let Some(snapshots) = &mut self.snapshots {
+            match snapshots.poll() {
+                Some(Ok(snapshot)) => {
+                    info!(
+                        target: "Took snapshot",
+                        height = snapshot.height,
+                        chunks = snapshot.chunks,
+                    );
+                },
+                Some(Err(err)) => {
+                    error!(target: "Failed to take snapshot", reason = err.to_string());
+                },
+                None => (),
+            }
+
+            match snapshots.maybe_take(&self.store, height as u64) {
+                Ok(true) => info!(target: "Taking snapshot", height),
+                Ok(false) => (),
+                Err(err) => {
+                    error!(target: "Failed to take snapshot", height, reason = err.to_string());
+                },
+            }
+        }
+
+        Ok((height, app_hash))
+    }
+
+    /// Create a checkpoint of the last committed state at the given path. A
+    /// `StateMachine` wrapping a store opened at the checkpoint can serve
+    /// queries independently of this one, e.g. on another thread.
+    pub fn checkpoint(&self, path: impl AsRef<Path>) -> Result<()> {
+        Ok(self.store.checkpoint(path)?)
+    }
+
+    /// List the snapshots available to be served to state syncing nodes, the
+    /// most recent first.
+    pub fn list_snapshots(&self) -> Result<Vec<SnapshotInfo>> {
+        match &self.snapshots {
+            Some(snapshots) => Ok(snapshots.list()?),
+            None => Ok(vec![]),
+        }
+    }
+
+    /// Load a chunk of a snapshot. Return None if not found.
+    pub fn load_snapshot_chunk(
+        &self,
+        height: u64,
+        format: u32,
+        chunk: u32,
+    ) -> Result<Option<Vec<u8>>> {
+        match &self.snapshots {
+            Some(snapshots) => Ok(snapshots.load_chunk(height, format, chunk)?),
+            None => Ok(None),
+        }
+    }
+
+    /// Accept a snapshot offered by a peer, and start restoring the store from
+    /// it. The snapshot's chunks are verified against the app hash, which is
+    /// provided by Tendermint from the light client and is thus trusted, rather
+    /// than the hash included in the snapshot's metadata.
+    pub fn offer_snapshot(&mut self, snapshot: SnapshotInfo, app_hash: &[u8]) -> Result<()> {
+        if snapshot.format != SNAPSHOT_FORMAT {
+            return Err(Error::unsupported_snapshot_format(snapshot.format));
+        }
+
+        let expected_hash: [u8; HASH_LENGTH] =
+            app_hash.try_into().map_err(|_| Error::InvalidAppHash)?;
+
+        self.restorer = Some(self.store.begin_restore(expected_hash, snapshot.chunks)?);
+
+        Ok(())
+    }
+
+    /// Apply a chunk of the snapshot being restored. Chunks must be applied in
+    /// order. Return true if this was the last chunk, in which case the store
+    /// has been replaced with the restored one.
+    pub fn apply_snapshot_chunk(&mut self, chunk: &[u8]) -> Result<bool> {
+        let Some(restorer) = &mut self.restorer else {
+            return Err(Error::NoSnapshotOffered);
+        };
+
+        let remaining = match restorer.process_chunk(chunk) {
+            Ok(remaining) => remaining,
+            Err(err) => {
+                // the chunk is invalid; abort restoring this snapshot
+                self.restorer = None;
+                return Err(err.into());
+            },
+        };
+
+        if remaining > 0 {
+            return Ok(false);
+        }
+
+        let restorer = self.restorer.take().unwrap();
+        self.store.finish_restore(restorer)?;
+
+        Ok(true)
     }
 }
@@ -0,0 +1,140 @@
+use once_cell::sync::Lazy;
+use prometheus::{
+    Encoder, Histogram, HistogramOpts, HistogramVec, IntCounterVec, IntGauge, Opts, Registry,
+    TextEncoder,
+};
+
+use crate::error::Result;
+
+/// The state machine's metrics, which are recorded as blocks and txs are
+/// processed, and served by the node in the Prometheus text format.
+pub static METRICS: Lazy<Metrics> = Lazy::new(|| {
+    Metrics::new().expect("[cw-state-machine]: failed to register metrics")
+});
+
+/// Label of the wasm calls made while delivering txs, including genesis
+/// messages
+pub const DELIVER_TX: &str = "deliver_tx";
+
+/// Label of the wasm calls made while serving queries, including simulations,
+/// which don't contribute to the chain's state
+pub const QUERY: &str = "query";
+
+/// Number of blocks between two updates of the store size
+pub const STORE_SIZE_INTERVAL: i64 = 100;
+
+/// Buckets for the durations of wasm calls and store commits, in seconds
+const DURATION_BUCKETS: &[f64] =
+    &[0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0];
+
+pub struct Metrics {
+    registry: Registry,
+
+    /// Height of the last committed block
+    pub block_height: IntGauge,
+
+    /// Number of txs included in each block
+    pub block_txs: Histogram,
+
+    /// Number of txs delivered, labelled by whether they succeeded and, if not,
+    /// by the code of the error
+    pub txs: IntCounterVec,
+
+    /// Amount of gas consumed by each delivered tx
+    pub tx_gas_used: Histogram,
+
+    /// Time taken to compile wasm byte codes into instances, labelled by
+    /// whether it's for delivering txs or serving queries
+    pub wasm_compile_seconds: HistogramVec,
+
+    /// Time taken to execute the entry points of wasm contracts, labelled by
+    /// whether it's for delivering txs or serving queries, and by the entry
+    /// point
+    pub wasm_execute_seconds: HistogramVec,
+
+    /// Time taken to commit pending ops to the Merk store
+    pub store_commit_seconds: Histogram,
+
+    /// Size of the Merk store on disk, in bytes, updated every
+    /// `STORE_SIZE_INTERVAL` blocks
+    pub store_size_bytes: IntGauge,
+}
+
+impl Metrics {
+    fn new() -> prometheus::Result<Self> {
+        let registry = Registry::new_custom(Some("cw_sdk".into()), None)?;
+
+        let block_height = IntGauge::new("block_height", "Height of the last committed block")?;
+        registry.register(Box::new(block_height.clone()))?;
+
+        let block_txs = Histogram::with_opts(
+            HistogramOpts::new("block_txs", "Number of txs included in each block")
+                .buckets(vec![0.0, 1.0, 2.0, 5.0, 10.0, 20.0, 50.0, 100.0, 200.0, 500.0]),
+        )?;
+        registry.register(Box::new(block_txs.clone()))?;
+
+        let txs = IntCounterVec::new(
+            Opts::new("txs", "Number of txs delivered, by result and error code"),
+            &["result", "code"],
+        )?;
+        registry.register(Box::new(txs.clone()))?;
+
+        let tx_gas_used = Histogram::with_opts(
+            HistogramOpts::new("tx_gas_used", "Amount of gas consumed by each delivered tx")
+                .buckets(prometheus::exponential_buckets(100_000.0, 4.0, 10)?),
+        )?;
+        registry.register(Box::new(tx_gas_used.clone()))?;
+
+        let wasm_compile_seconds = HistogramVec::new(
+            HistogramOpts::new("wasm_compile_seconds", "Time taken to compile wasm byte codes")
+                .buckets(DURATION_BUCKETS.to_vec()),
+            &["context"],
+        )?;
+        registry.register(Box::new(wasm_compile_seconds.clone()))?;
+
+        let wasm_execute_seconds = HistogramVec::new(
+            HistogramOpts::new("wasm_execute_seconds", "Time taken to execute wasm entry points")
+                .buckets(DURATION_BUCKETS.to_vec()),
+            &["context", "entry_point"],
+        )?;
+        registry.register(Box::new(wasm_execute_seconds.clone()))?;
+
+        let store_commit_seconds = Histogram::with_opts(
+            HistogramOpts::new("store_commit_seconds", "Time taken to commit to the Merk store")
+                .buckets(DURATION_BUCKETS.to_vec()),
+        )?;
+        registry.register(Box::new(store_commit_seconds.clone()))?;
+
+        let store_size_bytes = IntGauge::new("store_size_bytes", "Size of the Merk store on disk")?;
+        registry.register(Box::new(store_size_bytes.clone()))?;
+
+        Ok(Self {
+            registry,
+            block_height,
+            block_txs,
+            txs,
+            tx_gas_used,
+            wasm_compile_seconds,
+            wasm_execute_seconds,
+            store_commit_seconds,
+            store_size_bytes,
+        })
+    }
+
+    /// Record the result of a delivered tx and the gas it consumed.
+    pub fn observe_tx<T>(&self, result: &Result<T>, gas_used: u64) {
+        match result {
+            Ok(_) => self.txs.with_label_values(&["success", "0"]).inc(),
+            Err(err) => self.txs.with_label_values(&["failure", &err.code().to_string()]).inc(),
+        }
+
+        self.tx_gas_used.observe(gas_used as f64);
+    }
+
+    /// Encode the current values of all metrics in the Prometheus text format.
+    pub fn encode(&self) -> prometheus::Result<Vec<u8>> {
+        let mut buf = vec![];
+        TextEncoder::new().encode(&self.registry.gather(), &mut buf)?;
+        Ok(buf)
+    }
+}
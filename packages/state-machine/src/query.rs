@@ -1,24 +1,32 @@
-use cosmwasm_std::{Binary, ContractInfo, Env, Order, Storage};
+use cosmwasm_std::{Addr, Binary, ContractInfo, Env, Order, Storage};
 use cosmwasm_vm::{call_query, Backend, Instance, InstanceOptions, Storage as VmStorage};
 use cw_paginate::{collect, paginate_indexed_map, paginate_map};
 use cw_storage_plus::Bound;
 
 use cw_sdk::{
     label::resolve_raw_address,
-    Account, AccountResponse, CodeResponse, ContractResponse, InfoResponse, WasmRawResponse,
-    WasmSmartResponse,
+    address, Account, AccountResponse, CodeResponse, ContractHistoryEntry, ContractResponse,
+    FeeAllowanceResponse, GrantResponse, InfoResponse, UpgradePlan, WasmRawRecord,
+    WasmRawResponse, WasmSmartResponse,
 };
 
 use crate::{
     backend::{BackendApi, BackendQuerier, ContractSubstore},
     error::Result,
-    state::{code_by_address, ACCOUNTS, BLOCK, CODES, CODE_COUNT},
+    gas::QUERY_GAS_LIMIT,
+    metrics::{self, METRICS},
+    state::{
+        code_by_address, ACCOUNTS, BLOCK, CODES, CODE_COUNT, CONTRACT_HISTORY, DONE_UPGRADES,
+        FEE_GRANTS, GRANTS, UPGRADE_PLAN,
+    },
+    upgrade,
 };
 
 pub fn info(store: &dyn Storage) -> Result<InfoResponse> {
     Ok(InfoResponse {
         last_committed_block: BLOCK.load(store)?,
         code_count: CODE_COUNT.load(store)?,
+        app_version: upgrade::app_version(store)?,
     })
 }
 
@@ -47,19 +55,7 @@ pub fn accounts(
 
 pub fn contract(store: &dyn Storage, label: String) -> Result<ContractResponse> {
     let (address, account) = ACCOUNTS.idx.label.load(store, label)?;
-    match account {
-        Account::Contract {
-            code_id,
-            label,
-            admin,
-        } => Ok(ContractResponse {
-            address: address.into(),
-            code_id,
-            label,
-            admin: admin.map(String::from),
-        }),
-        _ => unreachable!(),
-    }
+    Ok(contract_response(address, account))
 }
 
 pub fn contracts(
@@ -69,19 +65,59 @@ pub fn contracts(
 ) -> Result<Vec<ContractResponse>> {
     let start = start_after.map(Bound::exclusive);
     let iter = ACCOUNTS.idx.label.range(store, start, None, Order::Ascending);
-    collect(iter, limit, |address, account| match account {
+    collect(iter, limit, |address, account| Ok(contract_response(address, account)))
+}
+
+pub fn contracts_by_code(
+    store: &dyn Storage,
+    code_id: u64,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> Result<Vec<ContractResponse>> {
+    let start = start_after.map(|address| Bound::ExclusiveRaw(address.into_bytes()));
+    let iter = ACCOUNTS.idx.code_id.prefix(code_id).range(store, start, None, Order::Ascending);
+    collect(iter, limit, |address, account| Ok(contract_response(address, account)))
+}
+
+pub fn contracts_by_admin(
+    store: &dyn Storage,
+    admin: String,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> Result<Vec<ContractResponse>> {
+    let admin_addr = resolve_raw_address(&admin)?;
+    let start = start_after.map(|address| Bound::ExclusiveRaw(address.into_bytes()));
+    let iter = ACCOUNTS
+        .idx
+        .admin
+        .prefix(admin_addr.into())
+        .range(store, start, None, Order::Ascending);
+    collect(iter, limit, |address, account| Ok(contract_response(address, account)))
+}
+
+/// The code history of the contract. Empty if the contract doesn't exist, or
+/// was created before code histories were recorded.
+pub fn contract_history(store: &dyn Storage, contract: &str) -> Result<Vec<ContractHistoryEntry>> {
+    let contract_addr = resolve_raw_address(contract)?;
+    Ok(CONTRACT_HISTORY.may_load(store, &contract_addr)?.unwrap_or_default())
+}
+
+/// Convert an account, loaded from one of the contract indexes, into a
+/// contract response. Base accounts are never indexed there.
+fn contract_response(address: Addr, account: Account<Addr>) -> ContractResponse {
+    match account {
         Account::Contract {
             code_id,
             label,
             admin,
-        } => Ok(ContractResponse {
+        } => ContractResponse {
             address: address.into(),
             code_id,
             label,
             admin: admin.map(String::from),
-        }),
+        },
         _ => unreachable!(),
-    })
+    }
 }
 
 pub fn code(store: &dyn Storage, code_id: u64) -> Result<CodeResponse> {
@@ -105,6 +141,64 @@ pub fn codes(
     })
 }
 
+pub fn fee_allowance(
+    store: &dyn Storage,
+    granter: String,
+    grantee: String,
+) -> Result<FeeAllowanceResponse> {
+    let granter_addr = address::validate(&granter)?;
+    let grantee_addr = address::validate(&grantee)?;
+    Ok(FeeAllowanceResponse {
+        granter,
+        grantee,
+        allowance: FEE_GRANTS.load(store, (&granter_addr, &grantee_addr))?,
+    })
+}
+
+pub fn fee_allowances(
+    store: &dyn Storage,
+    granter: String,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> Result<Vec<FeeAllowanceResponse>> {
+    let granter_addr = address::validate(&granter)?;
+    let start = start_after.map(|grantee| Bound::ExclusiveRaw(grantee.into_bytes()));
+    let iter = FEE_GRANTS.prefix(&granter_addr).range(store, start, None, Order::Ascending);
+    collect(iter, limit, |grantee, allowance| {
+        Ok(FeeAllowanceResponse {
+            granter: granter.clone(),
+            grantee: grantee.into(),
+            allowance,
+        })
+    })
+}
+
+pub fn grants(
+    store: &dyn Storage,
+    granter: String,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> Result<Vec<GrantResponse>> {
+    let granter_addr = address::validate(&granter)?;
+    let start = start_after.map(|grantee| Bound::ExclusiveRaw(grantee.into_bytes()));
+    let iter = GRANTS.prefix(&granter_addr).range(store, start, None, Order::Ascending);
+    collect(iter, limit, |grantee, grant| {
+        Ok(GrantResponse {
+            granter: granter.clone(),
+            grantee: grantee.into(),
+            grant,
+        })
+    })
+}
+
+pub fn upgrade_plan(store: &dyn Storage) -> Result<Option<UpgradePlan>> {
+    Ok(UPGRADE_PLAN.may_load(store)?)
+}
+
+pub fn applied_upgrade(store: &dyn Storage, name: &str) -> Result<Option<u64>> {
+    Ok(DONE_UPGRADES.may_load(store, name)?)
+}
+
 pub fn wasm_raw(store: impl Storage, contract: &str, key: &[u8]) -> Result<WasmRawResponse> {
     let contract_addr = resolve_raw_address(contract)?;
     let substore = ContractSubstore::new(store, &contract_addr);
@@ -114,6 +208,50 @@ pub fn wasm_raw(store: impl Storage, contract: &str, key: &[u8]) -> Result<WasmR
     })
 }
 
+const DEFAULT_RAW_RANGE_LIMIT: u32 = 10;
+const MAX_RAW_RANGE_LIMIT: u32 = 100;
+
+pub fn wasm_raw_range(
+    store: impl Storage,
+    contract: &str,
+    start_after: Option<Binary>,
+    end: Option<Binary>,
+    limit: Option<u32>,
+    order: Option<Order>,
+) -> Result<Vec<WasmRawRecord>> {
+    let contract_addr = resolve_raw_address(contract)?;
+    let mut substore = ContractSubstore::new(store, &contract_addr);
+
+    let order = order.unwrap_or(Order::Ascending);
+    let limit = limit.unwrap_or(DEFAULT_RAW_RANGE_LIMIT).min(MAX_RAW_RANGE_LIMIT) as usize;
+
+    // `scan` takes an inclusive lower bound and an exclusive upper bound. the
+    // smallest key greater than a given key is the key with a zero byte
+    // appended, which we use to turn an exclusive lower bound inclusive.
+    let exclusive_to_inclusive = |key: Binary| [key.as_slice(), &[0]].concat();
+    let (min, max) = match order {
+        Order::Ascending => (start_after.map(exclusive_to_inclusive), end.map(|key| key.0)),
+        Order::Descending => (end.map(exclusive_to_inclusive), start_after.map(|key| key.0)),
+    };
+
+    let (iterator_id, _) = substore.scan(min.as_deref(), max.as_deref(), order);
+    let iterator_id = iterator_id?;
+
+    let mut records = vec![];
+    while records.len() < limit {
+        let (record, _) = substore.next(iterator_id);
+        let Some((key, value)) = record? else {
+            break;
+        };
+        records.push(WasmRawRecord {
+            key: key.into(),
+            value: value.into(),
+        });
+    }
+
+    Ok(records)
+}
+
 pub fn wasm_smart(
     store: impl Storage + 'static,
     contract: &str,
@@ -140,6 +278,7 @@ pub fn wasm_smart(
         },
     };
 
+    let timer = METRICS.wasm_compile_seconds.with_label_values(&[metrics::QUERY]).start_timer();
     let mut instance = Instance::from_code(
         &code,
         Backend {
@@ -148,13 +287,18 @@ pub fn wasm_smart(
             querier: BackendQuerier,
         },
         InstanceOptions {
-            gas_limit: u64::MAX,
+            gas_limit: QUERY_GAS_LIMIT,
             print_debug: true,
         },
         None,
     )?;
 
+    timer.observe_duration();
+
+    let timer =
+        METRICS.wasm_execute_seconds.with_label_values(&[metrics::QUERY, "query"]).start_timer();
     let result = call_query(&mut instance, &env, msg)?;
+    timer.observe_duration();
 
     Ok(WasmSmartResponse {
         result,
@@ -1,6 +1,6 @@
 use cosmwasm_std::{Addr, Binary, Storage, BlockInfo};
-use cw_optional_indexes::OptionalUniqueIndex;
-use cw_sdk::Account;
+use cw_optional_indexes::{OptionalMultiIndex, OptionalUniqueIndex};
+use cw_sdk::{Account, ContractHistoryEntry, FeeAllowance, Grant, UpgradePlan};
 use cw_storage_plus::{Index, IndexList, IndexedMap, Item, Map};
 
 use crate::error::{Error, Result};
@@ -16,21 +16,66 @@ pub const CODES: Map<u64, Binary> = Map::new("codes");
 
 /// Accounts, either base (i.e. externally-owned) accounts or smart contract
 /// accounts, indexed by addresses.
-/// Contracts are additionally indexed by their labels, which must be unique.
+/// Contracts are additionally indexed by their labels, which must be unique,
+/// as well as by their code ids and admins.
 pub const ACCOUNTS: IndexedMap<&Addr, Account<Addr>, AccountIndexes> = IndexedMap::new(
     "accounts",
-    AccountIndexes::new("accounts__label"),
+    AccountIndexes::new("accounts", "accounts__label", "accounts__code_id", "accounts__admin"),
 );
 
+/// The code history of each contract, i.e. its instantiation and migrations in
+/// chronological order, indexed by contract addresses.
+pub const CONTRACT_HISTORY: Map<&Addr, Vec<ContractHistoryEntry>> = Map::new("contract_history");
+
+/// Fee allowances, indexed by granter and grantee addresses, in that order.
+pub const FEE_GRANTS: Map<(&Addr, &Addr), FeeAllowance> = Map::new("fee_grants");
+
+/// Grants of permission to execute messages on behalf of the granter, indexed
+/// by granter and grantee addresses, in that order.
+pub const GRANTS: Map<(&Addr, &Addr), Grant> = Map::new("grants");
+
+/// Sequence numbers of contract accounts, indexed by addresses.
+///
+/// Base accounts keep their sequence numbers in `ACCOUNTS`. Contract accounts
+/// only get an entry here once they have sent their first tx.
+pub const CONTRACT_SEQUENCES: Map<&Addr, u64> = Map::new("contract_sequences");
+
+/// Address or label of the account allowed to schedule chain upgrades. Not set
+/// if upgrades are disabled.
+pub const UPGRADE_AUTHORITY: Item<String> = Item::new("upgrade_authority");
+
+/// The chain upgrade that is scheduled but not yet applied.
+pub const UPGRADE_PLAN: Item<UpgradePlan> = Item::new("upgrade_plan");
+
+/// Chain upgrades that have been applied, indexed by names. The values are the
+/// heights at which they were applied.
+pub const DONE_UPGRADES: Map<&str, u64> = Map::new("done_upgrades");
+
+/// The version of the application, incremented each time a chain upgrade is
+/// applied. Not set until the first upgrade, in which case it is 1.
+pub const APP_VERSION: Item<u64> = Item::new("app_version");
+
 /// The index types used to index accounts in cw-sdk
 pub struct AccountIndexes<'a> {
     /// Index accounts by contract labels. If an account is a base account
     /// then it is not indexed.
     pub label: OptionalUniqueIndex<'a, String, Account<Addr>, &'a Addr>,
+
+    /// Index contracts by code ids. Base accounts are not indexed.
+    pub code_id: OptionalMultiIndex<'a, u64, Account<Addr>, &'a Addr>,
+
+    /// Index contracts by admin addresses. Base accounts and contracts without
+    /// admins are not indexed.
+    pub admin: OptionalMultiIndex<'a, String, Account<Addr>, &'a Addr>,
 }
 
 impl<'a> AccountIndexes<'a> {
-    pub const fn new(label_namespace: &'a str) -> Self {
+    pub const fn new(
+        pk_namespace: &'a str,
+        label_namespace: &'a str,
+        code_id_namespace: &'a str,
+        admin_namespace: &'a str,
+    ) -> Self {
         Self {
             label: OptionalUniqueIndex::new(
                 |account| match account {
@@ -44,13 +89,39 @@ impl<'a> AccountIndexes<'a> {
                 },
                 label_namespace,
             ),
+            code_id: OptionalMultiIndex::new(
+                |_, account| match account {
+                    Account::Base {
+                        ..
+                    } => None,
+                    Account::Contract {
+                        code_id,
+                        ..
+                    } => Some(*code_id),
+                },
+                pk_namespace,
+                code_id_namespace,
+            ),
+            admin: OptionalMultiIndex::new(
+                |_, account| match account {
+                    Account::Base {
+                        ..
+                    } => None,
+                    Account::Contract {
+                        admin,
+                        ..
+                    } => admin.as_ref().map(String::from),
+                },
+                pk_namespace,
+                admin_namespace,
+            ),
         }
     }
 }
 
 impl<'a> IndexList<Account<Addr>> for AccountIndexes<'a> {
     fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Account<Addr>>> + '_> {
-        let v: Vec<&dyn Index<Account<Addr>>> = vec![&self.label];
+        let v: Vec<&dyn Index<Account<Addr>>> = vec![&self.label, &self.code_id, &self.admin];
         Box::new(v.into_iter())
     }
 }
@@ -77,6 +148,7 @@ pub fn code_by_address(store: &dyn Storage, contract_addr: &Addr) -> Result<Bina
 #[cfg(test)]
 mod tests {
     use cosmwasm_std::{testing::MockStorage, Order, StdError, StdResult};
+    use cw_sdk::PublicKey;
 
     use super::*;
 
@@ -84,18 +156,18 @@ mod tests {
     fn indexing_accounts() {
         let mut store = MockStorage::new();
 
-        let addresses = ["base1", "base2", "bank", "token-factory"]
+        let addresses = ["base1", "base2", "bank", "token-factory", "token-factory-2"]
             .into_iter()
             .map(Addr::unchecked)
             .collect::<Vec<_>>();
 
         let accounts = [
             Account::Base {
-                pubkey: b"base1pubkey".into(),
+                pubkey: PublicKey::Secp256k1(b"base1pubkey".into()),
                 sequence: 0,
             },
             Account::Base {
-                pubkey: b"base2pubkey".into(),
+                pubkey: PublicKey::Secp256k1(b"base2pubkey".into()),
                 sequence: 123,
             },
             Account::Contract {
@@ -108,6 +180,11 @@ mod tests {
                 label: "token-factory".into(),
                 admin: Some(Addr::unchecked("larry")),
             },
+            Account::Contract {
+                code_id: 345,
+                label: "token-factory-2".into(),
+                admin: Some(Addr::unchecked("larry")),
+            },
         ];
 
         addresses
@@ -137,14 +214,58 @@ mod tests {
         assert_eq!(acct, accounts[3]);
 
         // the base accounts should not have been indexed
-        // meaning the total number of entries in the idx_map should be 2
+        // meaning the total number of entries in the idx_map should be 3
         let items = ACCOUNTS
             .idx
             .label
             .range(&store, None, None, Order::Ascending)
             .collect::<StdResult<Vec<_>>>()
             .unwrap();
-        assert_eq!(items.len(), 2)
+        assert_eq!(items.len(), 3);
+
+        // both token-factory contracts run code 345
+        let items = ACCOUNTS
+            .idx
+            .code_id
+            .prefix(345)
+            .range(&store, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()
+            .unwrap();
+        assert_eq!(
+            items,
+            vec![
+                (addresses[3].clone(), accounts[3].clone()),
+                (addresses[4].clone(), accounts[4].clone()),
+            ],
+        );
+
+        // the base accounts should not have been indexed by code id either
+        let items = ACCOUNTS
+            .idx
+            .code_id
+            .range(&store, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()
+            .unwrap();
+        assert_eq!(items.len(), 3);
+
+        // larry administers both token-factory contracts
+        let items = ACCOUNTS
+            .idx
+            .admin
+            .prefix("larry".into())
+            .keys(&store, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()
+            .unwrap();
+        assert_eq!(items, &addresses[3..]);
+
+        // the bank contract has no admin, so only two contracts are indexed
+        let items = ACCOUNTS
+            .idx
+            .admin
+            .range(&store, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()
+            .unwrap();
+        assert_eq!(items.len(), 2);
     }
 
     #[test]
@@ -0,0 +1,70 @@
+//! Helpers for the unit tests of this crate.
+
+use cosmwasm_std::{Addr, Binary, BlockInfo, Timestamp};
+use k256::ecdsa::{signature::Signer, Signature, SigningKey};
+
+use cw_sdk::{address, PublicKey, SdkMsg, SignDoc, Tx, TxBody};
+
+pub const CHAIN_ID: &str = "cw-test-1";
+
+pub fn mock_block(height: u64, seconds: u64) -> BlockInfo {
+    BlockInfo {
+        height,
+        time: Timestamp::from_seconds(seconds),
+        chain_id: CHAIN_ID.into(),
+    }
+}
+
+/// A secp256k1 key pair, and the address of the base account it controls.
+pub struct TestAccount {
+    sk: SigningKey,
+    pub pubkey: PublicKey,
+    pub addr: Addr,
+}
+
+impl TestAccount {
+    /// Create a key pair deterministically from a non-zero seed.
+    pub fn new(seed: u8) -> Self {
+        let sk = SigningKey::from_bytes(&[seed; 32]).unwrap();
+        let pubkey = PublicKey::Secp256k1(sk.verifying_key().to_bytes().to_vec().into());
+        let addr = address::derive_from_pubkey(&pubkey).unwrap();
+        Self {
+            sk,
+            pubkey,
+            addr,
+        }
+    }
+
+    /// A tx body sent by this account, with no fee, timeouts or gas limit.
+    pub fn tx_body(&self, sequence: u64, msgs: Vec<SdkMsg>) -> TxBody {
+        TxBody {
+            sender: self.addr.to_string(),
+            chain_id: CHAIN_ID.into(),
+            sequence,
+            msgs,
+            timeout_height: None,
+            timeout_timestamp: None,
+            fee: vec![],
+            fee_payer: None,
+            fee_payer_sequence: None,
+            gas_limit: None,
+        }
+    }
+
+    pub fn sign(&self, body: &TxBody) -> Binary {
+        let sign_bytes = SignDoc::new(body.clone()).sign_bytes().unwrap();
+        let signature: Signature = self.sk.sign(&sign_bytes);
+        signature.as_ref().to_vec().into()
+    }
+
+    /// Sign the tx body, and include the pubkey in the tx.
+    pub fn sign_tx(&self, body: TxBody) -> Tx {
+        Tx {
+            pubkey: Some(self.pubkey.clone()),
+            signature: self.sign(&body),
+            fee_payer_pubkey: None,
+            fee_payer_signature: None,
+            body,
+        }
+    }
+}
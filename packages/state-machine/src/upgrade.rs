@@ -0,0 +1,235 @@
+use cosmwasm_std::{Addr, BlockInfo, Event, Storage};
+use tracing::info;
+
+use cw_sdk::{label::resolve_raw_address, UpgradePlan};
+
+use crate::{
+    error::{Error, Result},
+    state::{APP_VERSION, DONE_UPGRADES, UPGRADE_AUTHORITY, UPGRADE_PLAN},
+};
+
+/// A migration that is run once, when the chain upgrade it is registered for is
+/// applied. For example, it may rewrite `ACCOUNTS` or migrate system contracts.
+pub type UpgradeHandler = fn(&mut dyn Storage, &BlockInfo) -> Result<()>;
+
+/// The chain upgrades this binary is able to apply, and their handlers.
+///
+/// To ship an upgrade, register a handler here under the name of the upgrade
+/// before scheduling it, and release the binary to node operators.
+pub const HANDLERS: &[(&str, UpgradeHandler)] = &[];
+
+/// The version of the application, which starts from 1 and is incremented each
+/// time a chain upgrade is applied.
+pub fn app_version(store: &dyn Storage) -> Result<u64> {
+    Ok(APP_VERSION.may_load(store)?.unwrap_or(1))
+}
+
+pub fn schedule_upgrade(
+    store: &mut dyn Storage,
+    block: &BlockInfo,
+    sender_addr: &Addr,
+    name: String,
+    height: u64,
+    info: Option<String>,
+) -> Result<Event> {
+    let Some(authority) = UPGRADE_AUTHORITY.may_load(store)? else {
+        return Err(Error::UpgradesDisabled);
+    };
+
+    if *sender_addr != resolve_raw_address(&authority)? {
+        return Err(Error::not_upgrade_authority(sender_addr));
+    }
+
+    if height <= block.height {
+        return Err(Error::upgrade_height_passed(height, block.height));
+    }
+
+    if DONE_UPGRADES.has(store, &name) {
+        return Err(Error::upgrade_already_applied(name));
+    }
+
+    UPGRADE_PLAN.save(
+        store,
+        &UpgradePlan {
+            name: name.clone(),
+            height,
+            info,
+        },
+    )?;
+
+    Ok(Event::new("schedule_upgrade")
+        .add_attribute("name", name)
+        .add_attribute("height", height.to_string()))
+}
+
+/// Apply the scheduled upgrade, if the block is at the upgrade's height, by
+/// running its handler.
+///
+/// Return error, which halts the chain, if this binary doesn't have a handler
+/// for the upgrade, so that node operators can switch to one that does. Also
+/// return error if the binary has a handler for an upgrade whose height hasn't
+/// been reached yet, which means the binary was switched too early.
+pub fn maybe_apply_upgrade(store: &mut dyn Storage, block: &BlockInfo) -> Result<Option<Event>> {
+    apply_upgrade_with(store, block, HANDLERS)
+}
+
+fn apply_upgrade_with(
+    store: &mut dyn Storage,
+    block: &BlockInfo,
+    handlers: &[(&str, UpgradeHandler)],
+) -> Result<Option<Event>> {
+    let Some(plan) = UPGRADE_PLAN.may_load(store)? else {
+        return Ok(None);
+    };
+
+    let handler = handlers
+        .iter()
+        .find(|(name, _)| *name == plan.name)
+        .map(|(_, handler)| handler);
+
+    if block.height < plan.height {
+        if handler.is_some() {
+            return Err(Error::upgrade_too_early(plan.name, plan.height));
+        }
+        return Ok(None);
+    }
+
+    let Some(handler) = handler else {
+        return Err(Error::upgrade_needed(plan.name, plan.height, plan.info));
+    };
+
+    handler(store, block)?;
+
+    let app_version = app_version(store)? + 1;
+    APP_VERSION.save(store, &app_version)?;
+    DONE_UPGRADES.save(store, &plan.name, &block.height)?;
+    UPGRADE_PLAN.remove(store);
+
+    info!(target: "Applied upgrade", name = plan.name, app_version);
+
+    Ok(Some(
+        Event::new("upgrade")
+            .add_attribute("name", plan.name)
+            .add_attribute("app_version", app_version.to_string()),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::testing::MockStorage;
+
+    use super::*;
+    use crate::testing::{mock_block, TestAccount};
+
+    const MARKER: &[u8] = b"migrated";
+
+    fn mark_migrated(store: &mut dyn Storage, block: &BlockInfo) -> Result<()> {
+        store.set(MARKER, &block.height.to_be_bytes());
+        Ok(())
+    }
+
+    const TEST_HANDLERS: &[(&str, UpgradeHandler)] =
+        &[("v2", mark_migrated), ("v3", mark_migrated)];
+
+    /// Set up a store whose upgrade authority is the given account, and
+    /// schedule an upgrade at the given height.
+    fn setup_upgrade(store: &mut dyn Storage, authority: &TestAccount, name: &str, height: u64) {
+        UPGRADE_AUTHORITY.save(store, &authority.addr.to_string()).unwrap();
+        schedule_upgrade(store, &mock_block(1, 0), &authority.addr, name.into(), height, None)
+            .unwrap();
+    }
+
+    #[test]
+    fn applying_upgrade() {
+        let mut store = MockStorage::new();
+        let alice = TestAccount::new(1);
+        setup_upgrade(&mut store, &alice, "v2", 10);
+
+        let event = apply_upgrade_with(&mut store, &mock_block(10, 0), TEST_HANDLERS)
+            .unwrap()
+            .unwrap();
+        assert_eq!(event.ty, "upgrade");
+
+        assert_eq!(store.get(MARKER), Some(10u64.to_be_bytes().to_vec()));
+        assert_eq!(app_version(&store).unwrap(), 2);
+        assert_eq!(DONE_UPGRADES.load(&store, "v2").unwrap(), 10);
+        assert!(UPGRADE_PLAN.may_load(&store).unwrap().is_none());
+
+        // nothing left to apply in the next block
+        let result = apply_upgrade_with(&mut store, &mock_block(11, 0), TEST_HANDLERS).unwrap();
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn bumping_app_version() {
+        let mut store = MockStorage::new();
+        let alice = TestAccount::new(1);
+        assert_eq!(app_version(&store).unwrap(), 1);
+
+        for (name, height, version) in [("v2", 10, 2), ("v3", 20, 3)] {
+            setup_upgrade(&mut store, &alice, name, height);
+            apply_upgrade_with(&mut store, &mock_block(height, 0), TEST_HANDLERS).unwrap();
+            assert_eq!(app_version(&store).unwrap(), version);
+        }
+    }
+
+    #[test]
+    fn upgrade_needed() {
+        let mut store = MockStorage::new();
+        let alice = TestAccount::new(1);
+        setup_upgrade(&mut store, &alice, "v4", 10);
+
+        // this binary doesn't know the upgrade, but it's not due yet
+        let result = apply_upgrade_with(&mut store, &mock_block(9, 0), TEST_HANDLERS).unwrap();
+        assert!(result.is_none());
+
+        let err = apply_upgrade_with(&mut store, &mock_block(10, 0), TEST_HANDLERS).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::UpgradeNeeded {
+                name,
+                height: 10,
+                ..
+            } if name == "v4",
+        ));
+
+        // the store isn't modified
+        assert!(UPGRADE_PLAN.may_load(&store).unwrap().is_some());
+        assert_eq!(app_version(&store).unwrap(), 1);
+    }
+
+    #[test]
+    fn upgrade_too_early() {
+        let mut store = MockStorage::new();
+        let alice = TestAccount::new(1);
+        setup_upgrade(&mut store, &alice, "v2", 10);
+
+        let err = apply_upgrade_with(&mut store, &mock_block(9, 0), TEST_HANDLERS).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::UpgradeTooEarly {
+                name,
+                height: 10,
+            } if name == "v2",
+        ));
+        assert!(store.get(MARKER).is_none());
+    }
+
+    #[test]
+    fn rejecting_applied_upgrade() {
+        let mut store = MockStorage::new();
+        let alice = TestAccount::new(1);
+        setup_upgrade(&mut store, &alice, "v2", 10);
+        apply_upgrade_with(&mut store, &mock_block(10, 0), TEST_HANDLERS).unwrap();
+
+        let block = mock_block(11, 0);
+        let err =
+            schedule_upgrade(&mut store, &block, &alice.addr, "v2".into(), 20, None).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::UpgradeAlreadyApplied {
+                name,
+            } if name == "v2",
+        ));
+    }
+}
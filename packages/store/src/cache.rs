@@ -44,6 +44,12 @@ impl<T: Storage> Cached<T> {
         }
     }
 
+    /// Return the keys that have been written to or deleted from the cache but
+    /// not yet flushed, in ascending order.
+    pub fn pending_keys(&self) -> Vec<Vec<u8>> {
+        self.pending_ops.keys().cloned().collect()
+    }
+
     /// Consume self, discard the pending ops, return the underlying store.
     pub fn recycle(self) -> T {
         self.store
@@ -145,6 +151,23 @@ mod tests {
         assert_eq!(items, kv());
     }
 
+    #[test]
+    fn listing_pending_keys() {
+        let mut store = MockStorage::default();
+        setup_store(&mut store);
+
+        let mut cache = Cached::new(store);
+        setup_cache(&mut cache);
+
+        assert_eq!(
+            cache.pending_keys(),
+            vec![b"key2".to_vec(), b"key3".to_vec(), b"key3333".to_vec()],
+        );
+
+        cache.flush();
+        assert!(cache.pending_keys().is_empty());
+    }
+
     #[cfg(feature = "iterator")]
     #[test]
     fn iterating() {
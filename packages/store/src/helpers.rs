@@ -1,3 +1,5 @@
+use std::{fs, io, path::Path};
+
 use merk::Merk;
 
 /// Read a value from the Merk store; panicks if fails.
@@ -10,3 +12,18 @@ pub(crate) fn must_get(merk: &Merk, key: &[u8]) -> Option<Vec<u8>> {
         );
     })
 }
+
+/// The total size of the files in a directory and its subdirectories.
+pub(crate) fn dir_size(path: &Path) -> io::Result<u64> {
+    let mut size = 0;
+    for entry in fs::read_dir(path)? {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        size += if metadata.is_dir() {
+            dir_size(&entry.path())?
+        } else {
+            metadata.len()
+        };
+    }
+    Ok(size)
+}
@@ -5,10 +5,12 @@ mod helpers;
 pub mod iterators;
 pub mod prefix;
 mod share;
+mod snapshot;
 mod store;
 
 pub use crate::cache::Cached;
 pub use crate::share::Shared;
+pub use crate::snapshot::{SnapshotError, SnapshotInfo, SnapshotManager, SNAPSHOT_FORMAT};
 pub use crate::store::{PendingStoreWrapper, Store, StoreBase, StoreWrapper};
 
-pub use merk::Error as MerkError;
+pub use merk::{restore::Restorer, Error as MerkError};
@@ -0,0 +1,420 @@
+use std::{
+    fs, mem,
+    path::{Path, PathBuf},
+    thread::{self, JoinHandle},
+};
+
+use cw_sdk::hash::HASH_LENGTH;
+use merk::Merk;
+use serde::{Deserialize, Serialize};
+
+use crate::{MerkError, Restorer, Store};
+
+/// The only snapshot format supported. Each chunk is a Merk chunk proof, which
+/// can be verified against the Merk root hash, i.e. the app hash.
+pub const SNAPSHOT_FORMAT: u32 = 1;
+
+#[derive(Debug, thiserror::Error)]
+pub enum SnapshotError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+
+    #[error(transparent)]
+    Merk(#[from] MerkError),
+
+    #[error("the snapshot at height {height} is still being taken")]
+    InProgress {
+        height: u64,
+    },
+}
+
+/// Metadata of a snapshot, saved alongside its chunks.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotInfo {
+    pub height: u64,
+    pub format: u32,
+    pub chunks: u32,
+    /// The Merk root hash, i.e. the app hash, at the snapshot's height
+    pub hash: Vec<u8>,
+}
+
+/// Takes snapshots of the Merk store every `interval` blocks, and saves them
+/// to disk, under the directory `{dir}/{height}`, one file for each chunk plus
+/// a `metadata.json` file. Only the `keep_recent` most recent snapshots are
+/// kept.
+///
+/// A snapshot is taken from a RocksDB checkpoint of the store, on a background
+/// thread, so that block processing can go on in the meantime. Only one
+/// snapshot is taken at a time.
+pub struct SnapshotManager {
+    dir: PathBuf,
+    interval: u64,
+    keep_recent: usize,
+    /// The height and thread of the snapshot being taken, if any
+    pending: Option<(u64, JoinHandle<Result<SnapshotInfo, SnapshotError>>)>,
+}
+
+impl SnapshotManager {
+    pub fn new(dir: impl Into<PathBuf>, interval: u64, keep_recent: u32) -> Self {
+        Self {
+            dir: dir.into(),
+            interval,
+            keep_recent: keep_recent as usize,
+            pending: None,
+        }
+    }
+
+    /// Start taking a snapshot in the background if the height is a multiple of
+    /// the interval. Once the snapshot is taken, the snapshots that are no
+    /// longer to be kept are deleted. Return whether a snapshot is started.
+    ///
+    /// Return error if the previous snapshot hasn't been collected by `poll` or
+    /// `wait` yet.
+    ///
+    /// This should only be called right after the store is committed, when
+    /// there are no pending ops.
+    pub fn maybe_take(&mut self, store: &Store, height: u64) -> Result<bool, SnapshotError> {
+        if self.interval == 0 || height % self.interval != 0 {
+            return Ok(false);
+        }
+
+        if let Some((height, _)) = &self.pending {
+            return Err(SnapshotError::InProgress {
+                height: *height,
+            });
+        }
+
+        // the checkpoint shares the immutable files of the store's database,
+        // so it's cheap to create, and isn't affected by subsequent commits
+        let checkpoint_dir = self.dir.join(format!("{height}.checkpoint"));
+        if checkpoint_dir.exists() {
+            fs::remove_dir_all(&checkpoint_dir)?;
+        }
+        fs::create_dir_all(&self.dir)?;
+        let checkpoint = store.borrow().merk.checkpoint(&checkpoint_dir)?;
+
+        let dir = self.dir.clone();
+        let keep_recent = self.keep_recent;
+        let handle = thread::spawn(move || -> Result<_, SnapshotError> {
+            let result = take(&dir, &checkpoint, height);
+            checkpoint.destroy()?;
+            let info = result?;
+            prune(&dir, keep_recent)?;
+            Ok(info)
+        });
+        self.pending = Some((height, handle));
+
+        Ok(true)
+    }
+
+    /// Return the outcome of the snapshot being taken, if it has finished.
+    pub fn poll(&mut self) -> Option<Result<SnapshotInfo, SnapshotError>> {
+        match &self.pending {
+            Some((_, handle)) if handle.is_finished() => self.wait(),
+            _ => None,
+        }
+    }
+
+    /// Block until the snapshot being taken, if any, has finished, and return
+    /// its outcome.
+    pub fn wait(&mut self) -> Option<Result<SnapshotInfo, SnapshotError>> {
+        let (_, handle) = self.pending.take()?;
+        Some(handle.join().expect("[cw-store]: snapshot thread panicked"))
+    }
+
+    /// List the snapshots available on disk, the most recent first.
+    pub fn list(&self) -> Result<Vec<SnapshotInfo>, SnapshotError> {
+        list(&self.dir)
+    }
+
+    /// Load a chunk of a snapshot. Return None if not found.
+    pub fn load_chunk(
+        &self,
+        height: u64,
+        format: u32,
+        chunk: u32,
+    ) -> Result<Option<Vec<u8>>, SnapshotError> {
+        if format != SNAPSHOT_FORMAT {
+            return Ok(None);
+        }
+
+        let path = snapshot_dir(&self.dir, height).join(chunk.to_string());
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        Ok(Some(fs::read(path)?))
+    }
+}
+
+fn take(dir: &Path, merk: &Merk, height: u64) -> Result<SnapshotInfo, SnapshotError> {
+    // write the chunks to a temporary directory first, and rename it once all
+    // chunks are written, so that incomplete snapshots are never served
+    let tmp_dir = dir.join(format!("{height}.tmp"));
+    if tmp_dir.exists() {
+        fs::remove_dir_all(&tmp_dir)?;
+    }
+    fs::create_dir_all(&tmp_dir)?;
+
+    let mut chunks = 0u32;
+    for chunk in merk.chunks()? {
+        fs::write(tmp_dir.join(chunks.to_string()), chunk?)?;
+        chunks += 1;
+    }
+
+    let info = SnapshotInfo {
+        height,
+        format: SNAPSHOT_FORMAT,
+        chunks,
+        hash: merk.root_hash().to_vec(),
+    };
+    fs::write(tmp_dir.join("metadata.json"), serde_json::to_vec(&info)?)?;
+
+    let snapshot_dir = snapshot_dir(dir, height);
+    if snapshot_dir.exists() {
+        fs::remove_dir_all(&snapshot_dir)?;
+    }
+    fs::rename(&tmp_dir, &snapshot_dir)?;
+
+    Ok(info)
+}
+
+/// Delete all but the most recent snapshots.
+fn prune(dir: &Path, keep_recent: usize) -> Result<(), SnapshotError> {
+    for info in list(dir)?.into_iter().skip(keep_recent) {
+        fs::remove_dir_all(snapshot_dir(dir, info.height))?;
+    }
+    Ok(())
+}
+
+fn list(dir: &Path) -> Result<Vec<SnapshotInfo>, SnapshotError> {
+    if !dir.exists() {
+        return Ok(vec![]);
+    }
+
+    let mut snapshots = vec![];
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+
+        // skip the checkpoints and temporary directories of snapshots being
+        // taken
+        let is_snapshot = entry
+            .file_name()
+            .to_str()
+            .map_or(false, |name| name.parse::<u64>().is_ok());
+        if !is_snapshot {
+            continue;
+        }
+
+        let metadata = fs::read(entry.path().join("metadata.json"))?;
+        snapshots.push(serde_json::from_slice::<SnapshotInfo>(&metadata)?);
+    }
+
+    snapshots.sort_by(|a, b| b.height.cmp(&a.height));
+
+    Ok(snapshots)
+}
+
+fn snapshot_dir(dir: &Path, height: u64) -> PathBuf {
+    dir.join(height.to_string())
+}
+
+impl Store {
+    /// Start restoring the store from a snapshot. The chunks are verified
+    /// against the expected root hash as they are processed by the restorer.
+    ///
+    /// The restored Merk tree is built in a temporary directory next to the
+    /// store's, and only takes the store's place in `finish_restore`.
+    pub fn begin_restore(
+        &self,
+        expected_hash: [u8; HASH_LENGTH],
+        chunks: u32,
+    ) -> Result<Restorer, SnapshotError> {
+        let restore_path = self.borrow().path.with_extension("restore");
+        if restore_path.exists() {
+            fs::remove_dir_all(&restore_path)?;
+        }
+
+        Ok(merk::Merk::restore(restore_path, expected_hash, chunks as usize)?)
+    }
+
+    /// Replace the store's Merk tree with the one restored from a snapshot,
+    /// once all chunks have been processed. The current tree is deleted; state
+    /// sync only happens on nodes that haven't got any state yet.
+    pub fn finish_restore(&self, restorer: Restorer) -> Result<(), SnapshotError> {
+        let restored = restorer.finalize()?;
+
+        let mut base = self.borrow_mut();
+        let path = base.path.clone();
+
+        // delete the current tree, to make room for the restored one at the
+        // store's path
+        let current = mem::replace(&mut base.merk, restored);
+        current.destroy()?;
+
+        // the restored tree lives in the temporary directory. make a checkpoint
+        // of it at the store's path, and delete the temporary one
+        let checkpoint = base.merk.checkpoint(&path)?;
+        let restored = mem::replace(&mut base.merk, checkpoint);
+        restored.destroy()?;
+
+        base.pending_ops.clear();
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env::temp_dir, time::SystemTime};
+
+    use cosmwasm_std::Storage;
+
+    use super::*;
+
+    /// An autogenerated, temporary path
+    fn temp_path(name: &str) -> PathBuf {
+        let time = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        temp_dir().join(format!("{name}-{time}"))
+    }
+
+    /// Open a `Store` at a temporary path, and commit some key-values to it
+    fn setup_store() -> Store {
+        let store = Store::open(temp_path("merk-temp")).unwrap();
+
+        let mut wrapper = store.pending_wrap();
+        for i in 0..100u32 {
+            wrapper.set(format!("key{i}").as_bytes(), format!("value{i}").as_bytes());
+        }
+        store.commit().unwrap();
+
+        store
+    }
+
+    #[test]
+    fn taking_snapshot() {
+        let store = setup_store();
+        let dir = temp_path("snapshots");
+        let mut snapshots = SnapshotManager::new(&dir, 10, 2);
+
+        // not due at this height
+        assert!(!snapshots.maybe_take(&store, 15).unwrap());
+        assert!(snapshots.wait().is_none());
+
+        assert!(snapshots.maybe_take(&store, 20).unwrap());
+
+        // only one snapshot is taken at a time
+        let err = snapshots.maybe_take(&store, 30).unwrap_err();
+        assert!(matches!(err, SnapshotError::InProgress {
+            height: 20,
+        }));
+
+        let info = snapshots.wait().unwrap().unwrap();
+        assert_eq!(info.height, 20);
+        assert_eq!(info.format, SNAPSHOT_FORMAT);
+        assert!(info.chunks > 0);
+        assert_eq!(info.hash, store.root_hash().to_vec());
+        assert_eq!(snapshots.list().unwrap(), vec![info.clone()]);
+
+        // the checkpoint and temporary directory are deleted
+        assert!(!dir.join("20.checkpoint").exists());
+        assert!(!dir.join("20.tmp").exists());
+
+        // every chunk can be loaded, in the supported format only
+        for chunk in 0..info.chunks {
+            assert!(snapshots.load_chunk(20, SNAPSHOT_FORMAT, chunk).unwrap().is_some());
+        }
+        assert!(snapshots.load_chunk(20, SNAPSHOT_FORMAT, info.chunks).unwrap().is_none());
+        assert!(snapshots.load_chunk(20, SNAPSHOT_FORMAT + 1, 0).unwrap().is_none());
+        assert!(snapshots.load_chunk(10, SNAPSHOT_FORMAT, 0).unwrap().is_none());
+    }
+
+    #[test]
+    fn pruning_snapshots() {
+        let store = setup_store();
+        let mut snapshots = SnapshotManager::new(temp_path("snapshots"), 10, 2);
+
+        for height in [10, 20, 30] {
+            assert!(snapshots.maybe_take(&store, height).unwrap());
+            snapshots.wait().unwrap().unwrap();
+        }
+
+        // only the two most recent snapshots are kept, the most recent first
+        let heights = snapshots
+            .list()
+            .unwrap()
+            .into_iter()
+            .map(|info| info.height)
+            .collect::<Vec<_>>();
+        assert_eq!(heights, vec![30, 20]);
+        assert!(snapshots.load_chunk(10, SNAPSHOT_FORMAT, 0).unwrap().is_none());
+    }
+
+    #[test]
+    fn polling_snapshot() {
+        let store = setup_store();
+        let mut snapshots = SnapshotManager::new(temp_path("snapshots"), 10, 2);
+
+        assert!(snapshots.poll().is_none());
+        assert!(snapshots.maybe_take(&store, 10).unwrap());
+
+        let info = loop {
+            if let Some(result) = snapshots.poll() {
+                break result.unwrap();
+            }
+            thread::yield_now();
+        };
+        assert_eq!(info.height, 10);
+
+        // the outcome is only returned once
+        assert!(snapshots.poll().is_none());
+    }
+
+    #[test]
+    fn snapshot_ignores_later_commits() {
+        let store = setup_store();
+        let mut snapshots = SnapshotManager::new(temp_path("snapshots"), 10, 2);
+
+        let hash = store.root_hash();
+        assert!(snapshots.maybe_take(&store, 10).unwrap());
+
+        // commit more changes while the snapshot is being taken
+        store.pending_wrap().set(b"key100", b"value100");
+        store.commit().unwrap();
+
+        let info = snapshots.wait().unwrap().unwrap();
+        assert_eq!(info.hash, hash.to_vec());
+    }
+
+    #[test]
+    fn restoring_snapshot() {
+        let store = setup_store();
+        let mut snapshots = SnapshotManager::new(temp_path("snapshots"), 10, 2);
+        assert!(snapshots.maybe_take(&store, 10).unwrap());
+        let info = snapshots.wait().unwrap().unwrap();
+
+        let hash: [u8; HASH_LENGTH] = info.hash.clone().try_into().unwrap();
+        let restored = Store::open(temp_path("merk-restored")).unwrap();
+        let mut restorer = restored.begin_restore(hash, info.chunks).unwrap();
+        for chunk in 0..info.chunks {
+            let bytes = snapshots.load_chunk(10, SNAPSHOT_FORMAT, chunk).unwrap().unwrap();
+            restorer.process_chunk(&bytes).unwrap();
+        }
+        restored.finish_restore(restorer).unwrap();
+
+        assert_eq!(restored.root_hash(), store.root_hash());
+        let wrapper = restored.wrap();
+        for i in 0..100u32 {
+            assert_eq!(
+                wrapper.get(format!("key{i}").as_bytes()),
+                Some(format!("value{i}").into_bytes()),
+            );
+        }
+    }
+}
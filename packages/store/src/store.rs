@@ -1,8 +1,8 @@
 use std::{
     cell::{RefCell, Ref, RefMut},
     collections::BTreeMap,
-    iter,
-    path::Path,
+    io, iter,
+    path::{Path, PathBuf},
     rc::Rc,
 };
 
@@ -11,7 +11,7 @@ use cw_sdk::hash::HASH_LENGTH;
 use merk::{Merk, Op};
 
 use crate::{
-    helpers::must_get,
+    helpers::{dir_size, must_get},
     iterators::{range_bounds, MemIter, MergedIter, MerkIter},
     MerkError,
 };
@@ -26,6 +26,9 @@ pub struct StoreBase {
     /// Upon an ABCI "Commit" request, these ops will be committed to the Merk
     /// store, and this map cleared.
     pub(crate) pending_ops: BTreeMap<Vec<u8>, Op>,
+
+    /// The path where the Merk tree is stored on disk.
+    pub(crate) path: PathBuf,
 }
 
 /// Wrap a storage object inside an `Rc<RefCell<T>>` so that it can be shared as
@@ -41,8 +44,9 @@ pub struct Store(Rc<RefCell<StoreBase>>);
 impl Store {
     pub fn open(path: impl AsRef<Path>) -> Result<Self, MerkError> {
         let base = StoreBase {
-            merk: Merk::open(path)?,
+            merk: Merk::open(&path)?,
             pending_ops: BTreeMap::new(),
+            path: path.as_ref().to_path_buf(),
         };
         Ok(Self(Rc::new(RefCell::new(base))))
     }
@@ -51,11 +55,11 @@ impl Store {
         Self(Rc::clone(&self.0))
     }
 
-    fn borrow(&self) -> Ref<StoreBase> {
+    pub(crate) fn borrow(&self) -> Ref<StoreBase> {
         self.0.borrow()
     }
 
-    fn borrow_mut(&self) -> RefMut<StoreBase> {
+    pub(crate) fn borrow_mut(&self) -> RefMut<StoreBase> {
         self.0.borrow_mut()
     }
 
@@ -80,6 +84,22 @@ impl Store {
         unsafe { ref_mut.merk.apply_unchecked(&batch, &[]) }
     }
 
+    /// Create a checkpoint of the committed state at the given path, which can
+    /// be opened as a separate store with `Store::open`. The pending ops are not
+    /// included.
+    ///
+    /// The checkpoint shares the immutable files of the store's database, so
+    /// it's cheap to create, and isn't affected by subsequent commits.
+    pub fn checkpoint(&self, path: impl AsRef<Path>) -> Result<(), MerkError> {
+        self.borrow().merk.checkpoint(path)?;
+        Ok(())
+    }
+
+    /// The total size of the files of the underlying Merk store on disk.
+    pub fn size_on_disk(&self) -> io::Result<u64> {
+        dir_size(&self.borrow().path)
+    }
+
     /// Wrap the store into a StoreWrapper.
     ///
     /// StoreWrapper implements the Storage trait, and reads directly from the
@@ -266,6 +286,27 @@ mod tests {
         assert_eq!(wrapper.get(b"key3333"), Some(b"value3333".to_vec()));
     }
 
+    #[test]
+    fn checkpointing() {
+        let store = setup_test();
+
+        let path = store.borrow().path.with_extension("checkpoint");
+        store.checkpoint(&path).unwrap();
+        let checkpoint = Store::open(&path).unwrap();
+
+        // the checkpoint has the committed state, without the pending ops
+        assert_eq!(checkpoint.root_hash(), store.root_hash());
+        let wrapper = checkpoint.wrap();
+        assert_eq!(wrapper.get(b"key2"), Some(b"value2".to_vec()));
+        assert_eq!(wrapper.get(b"key3"), Some(b"value3".to_vec()));
+        assert_eq!(wrapper.get(b"key3333"), None);
+
+        // subsequent commits don't affect the checkpoint
+        store.commit().unwrap();
+        assert_ne!(checkpoint.root_hash(), store.root_hash());
+        assert_eq!(checkpoint.wrap().get(b"key2"), Some(b"value2".to_vec()));
+    }
+
     #[test]
     fn committing() {
         let store = setup_test();